@@ -26,6 +26,11 @@ pub enum Source {
     #[cfg(all(feature = "online", not(target_arch = "wasm32")))]
     Explorer(Explorer, ethers_core::types::Address),
 
+    /// An address of a smart contract verified on [Sourcify](https://sourcify.dev), with its
+    /// chain id.
+    #[cfg(all(feature = "online", not(target_arch = "wasm32")))]
+    Sourcify(u64, ethers_core::types::Address),
+
     /// The package identifier of an npm package with a path to a Truffle artifact or ABI to be
     /// retrieved from `unpkg.io`.
     #[cfg(all(feature = "online", not(target_arch = "wasm32")))]
@@ -78,6 +83,9 @@ impl Source {
     ///   - `bscscan`     -> `bsc`
     ///   - `polygonscan` -> `polygon`
     ///   - `snowtrace`   -> `avalanche`
+    ///
+    /// - `sourcify:<address>` or `sourcify:<chain>:<address>`: an address of a contract verified on
+    ///   Sourcify, where `<chain>` is a chain name or id and defaults to `mainnet`.
     pub fn parse(source: impl AsRef<str>) -> Result<Self> {
         let source = source.as_ref().trim();
         match source.chars().next() {
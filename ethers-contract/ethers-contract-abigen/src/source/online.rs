@@ -1,7 +1,7 @@
 use super::Source;
 use crate::util;
 use ethers_core::types::{Address, Chain};
-use ethers_etherscan::Client;
+use ethers_etherscan::{sourcify::SourcifyClient, Client};
 use eyre::{Context, Result};
 use std::{fmt, str::FromStr};
 use url::Url;
@@ -77,11 +77,7 @@ impl Explorer {
     pub fn get(self, address: Address) -> Result<String> {
         // TODO: Improve this
         let client = self.client(None)?;
-        let future = client.contract_abi(address);
-        let abi = match tokio::runtime::Handle::try_current() {
-            Ok(handle) => handle.block_on(future),
-            _ => tokio::runtime::Runtime::new().expect("Could not start runtime").block_on(future),
-        }?;
+        let abi = block_on(client.contract_abi(address))?;
         Ok(serde_json::to_string(&abi)?)
     }
 }
@@ -97,6 +93,9 @@ impl Source {
                 // npm:<npm package>
                 "npm" => Ok(Self::npm(url.path())),
 
+                // sourcify:[<chain>:]<address>
+                "sourcify" => Self::from_sourcify(url.path()),
+
                 // try first: <explorer url>/.../<address>
                 // then: any http url
                 "http" | "https" => Ok(url
@@ -126,6 +125,27 @@ impl Source {
         Ok(Self::Explorer(explorer, address))
     }
 
+    /// Parse `s` as `<address>` or `<chain>:<address>`, where the chain is either a name or an id.
+    fn from_sourcify(s: &str) -> Result<Self> {
+        let (chain, address) = match s.rsplit_once(':') {
+            Some((chain, address)) => {
+                let chain = match chain.parse::<u64>() {
+                    Ok(id) => id,
+                    Err(_) => chain.parse::<Chain>()?.into(),
+                };
+                (chain, address)
+            }
+            None => (Chain::Mainnet.into(), s),
+        };
+        let address = address.parse().wrap_err_with(|| format!("Invalid address: {address}"))?;
+        Ok(Self::Sourcify(chain, address))
+    }
+
+    /// Creates a Sourcify source from a chain and an address.
+    pub fn sourcify(chain: impl Into<u64>, address: Address) -> Self {
+        Self::Sourcify(chain.into(), address)
+    }
+
     /// Creates an HTTP source from a URL.
     pub fn http(url: impl AsRef<str>) -> Result<Self> {
         Ok(Self::Http(Url::parse(url.as_ref())?))
@@ -149,6 +169,12 @@ impl Source {
                 util::http_get(url.clone()).wrap_err("Failed to retrieve ABI from URL")
             }
             Self::Explorer(explorer, address) => explorer.get(*address),
+            Self::Sourcify(chain, address) => {
+                let client = SourcifyClient::new();
+                let abi = block_on(client.contract_abi(*chain, *address))
+                    .wrap_err("Failed to retrieve ABI from Sourcify")?;
+                Ok(serde_json::to_string(&abi)?)
+            }
             Self::Npm(package) => {
                 // TODO: const?
                 let unpkg = Url::parse("https://unpkg.io/").unwrap();
@@ -160,6 +186,14 @@ impl Source {
     }
 }
 
+/// Runs the future to completion on the current runtime, or on a new one if there is none.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.block_on(future),
+        _ => tokio::runtime::Runtime::new().expect("Could not start runtime").block_on(future),
+    }
+}
+
 fn last_segment_address(url: &Url) -> Option<Address> {
     url.path().rsplit('/').next()?.parse().ok()
 }
@@ -180,6 +214,20 @@ mod tests {
             Source::npm("@openzeppelin/contracts@2.5.0/build/contracts/IERC20.json")
         );
 
+        let address: Address = "0x0102030405060708091011121314151617181920".parse().unwrap();
+        assert_eq!(
+            Source::parse(format!("sourcify:{address:?}")).unwrap(),
+            Source::sourcify(Chain::Mainnet, address)
+        );
+        assert_eq!(
+            Source::parse(format!("sourcify:gnosis:{address:?}")).unwrap(),
+            Source::sourcify(Chain::Gnosis, address)
+        );
+        assert_eq!(
+            Source::parse(format!("sourcify:1101:{address:?}")).unwrap(),
+            Source::sourcify(1101u64, address)
+        );
+
         let explorers = &[
             ("mainnet:", "etherscan:", "https://etherscan.io/address/", Chain::Mainnet),
             ("bsc:", "bscscan:", "https://bscscan.com/address/", Chain::BinanceSmartChain),
@@ -187,7 +235,6 @@ mod tests {
             ("avalanche:", "snowtrace:", "https://snowtrace.io/address/", Chain::Avalanche),
         ];
 
-        let address: Address = "0x0102030405060708091011121314151617181920".parse().unwrap();
         for &(chain_s, scan_s, url_s, chain) in explorers {
             let expected = Source::explorer(chain, address).unwrap();
 
@@ -246,6 +246,20 @@ where
         self.tx.set_nonce(nonce);
         self
     }
+
+    /// Discards the return value of the call, so that calls to functions returning different
+    /// types can be used interchangeably
+    #[cfg(feature = "abigen")]
+    pub(crate) fn discard_output(self) -> FunctionCall<B, M, ()> {
+        FunctionCall {
+            tx: self.tx,
+            function: self.function,
+            block: self.block,
+            client: self.client,
+            datatype: PhantomData,
+            _m: self._m,
+        }
+    }
 }
 
 impl<B, M, D> FunctionCall<B, M, D>
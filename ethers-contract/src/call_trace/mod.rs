@@ -0,0 +1,676 @@
+//! Decoding of call traces into human-readable call trees.
+//!
+//! A [`CallTraceDecoder`] is a registry of ABIs that resolves the selectors of calls and errors
+//! and the topics of events in a geth [`CallFrame`] or in parity [`Trace`]s. The resulting
+//! [`DecodedCall`] tree can be rendered like the output of `cast run`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ethers_contract::call_trace::CallTraceDecoder;
+//! use ethers_core::{abi::parse_abi, types::CallFrame};
+//!
+//! # fn foo(frame: CallFrame) -> Result<(), Box<dyn std::error::Error>> {
+//! let abi = parse_abi(&[
+//!     "function transfer(address to, uint256 amount) returns (bool)",
+//!     "event Transfer(address indexed from, address indexed to, uint256 value)",
+//! ])?;
+//! let decoder = CallTraceDecoder::new().with_contract("0x...".parse()?, "Token", &abi);
+//!
+//! // `frame` is the output of `debug_traceTransaction` with the `callTracer`
+//! println!("{}", decoder.decode_call_frame(&frame));
+//! # Ok(())
+//! # }
+//! ```
+
+mod render;
+
+use crate::EthError;
+use ethers_core::{
+    abi::{Abi, FunctionExt, RawLog, SignatureDb, SignatureDbError, Token},
+    types::{
+        Action, Address, Bytes, CallFrame, CallLogFrame, NameOrAddress, Res, Selector, Trace, H256,
+        U256,
+    },
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+};
+
+/// The selector of `Panic(uint256)`
+const PANIC_SELECTOR: Selector = [0x4e, 0x48, 0x7b, 0x71];
+
+/// A registry of ABIs that decodes call traces.
+///
+/// ABIs can be added from:
+/// - bindings, e.g. the `IERC20_ABI` generated by `abigen!`, with [`Self::with_abi`] and
+///   [`Self::with_contract`]
+/// - `ethers-solc` artifacts with `with_artifacts`, if the `solc` feature is enabled
+/// - verified contracts on Etherscan with `with_etherscan`, if the `etherscan` feature is enabled
+/// - a signature database file with [`Self::with_signatures_file`] or a [`SignatureDb`] with
+///   [`Self::with_signature_db`]
+///
+/// Calls to a contract registered with [`Self::with_contract`] are decoded with its ABI first,
+/// otherwise all known functions, events and errors with a matching selector are tried, see
+/// [`SignatureDb`].
+#[derive(Debug, Clone, Default)]
+pub struct CallTraceDecoder {
+    /// The names of known addresses
+    labels: HashMap<Address, String>,
+    /// The ABIs of known addresses
+    contracts: HashMap<Address, SignatureDb>,
+    /// All known signatures
+    signatures: SignatureDb,
+}
+
+impl CallTraceDecoder {
+    /// Creates an empty decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds all functions, events and errors of `abi`.
+    #[must_use]
+    pub fn with_abi(mut self, abi: &Abi) -> Self {
+        self.signatures.extend_abi(abi);
+        self
+    }
+
+    /// Adds the ABI of the contract deployed at `address` and labels the address with `name`.
+    #[must_use]
+    pub fn with_contract(mut self, address: Address, name: impl Into<String>, abi: &Abi) -> Self {
+        self.signatures.extend_abi(abi);
+        self.contracts.entry(address).or_default().extend_abi(abi);
+        self.labels.insert(address, name.into());
+        self
+    }
+
+    /// Labels `address` with `name` in the rendered trace.
+    #[must_use]
+    pub fn with_label(mut self, address: Address, name: impl Into<String>) -> Self {
+        self.labels.insert(address, name.into());
+        self
+    }
+
+    /// Adds human-readable function, event and error signatures, see [`SignatureDb::insert`].
+    ///
+    /// Empty lines and lines that start with `#` are skipped, lines that can not be parsed are
+    /// ignored.
+    #[must_use]
+    pub fn with_signatures<'a>(mut self, signatures: impl IntoIterator<Item = &'a str>) -> Self {
+        for signature in signatures.into_iter().map(str::trim) {
+            if !signature.is_empty() && !signature.starts_with('#') {
+                let _ = self.signatures.insert(signature);
+            }
+        }
+        self
+    }
+
+    /// Adds the signatures of a signature database file, see [`SignatureDb::load`].
+    pub fn with_signatures_file(self, path: impl AsRef<Path>) -> Result<Self, SignatureDbError> {
+        Ok(self.with_signature_db(&SignatureDb::load(path)?))
+    }
+
+    /// Adds all signatures of a [`SignatureDb`].
+    #[must_use]
+    pub fn with_signature_db(mut self, db: &SignatureDb) -> Self {
+        self.signatures.merge(db.clone());
+        self
+    }
+
+    /// Adds the ABIs of compiled `ethers-solc` artifacts.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_contract::call_trace::CallTraceDecoder;
+    /// use ethers_solc::Project;
+    ///
+    /// # fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let output = Project::builder().build()?.compile()?;
+    /// let decoder = CallTraceDecoder::new()
+    ///     .with_artifacts(output.into_artifacts().map(|(_, artifact)| artifact));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "solc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "solc")))]
+    #[must_use]
+    pub fn with_artifacts<I, A>(mut self, artifacts: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: ethers_solc::Artifact,
+    {
+        for artifact in artifacts {
+            if let Some(abi) = artifact.get_abi() {
+                self.signatures.extend_abi(&abi);
+            }
+        }
+        self
+    }
+
+    /// Fetches the ABIs and names of the given contracts from Etherscan, unverified contracts and
+    /// contracts that are already known are skipped.
+    ///
+    /// [`Self::addresses`] returns all addresses of a trace.
+    #[cfg(feature = "etherscan")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "etherscan")))]
+    pub async fn with_etherscan(
+        mut self,
+        client: &ethers_etherscan::Client,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<Self, ethers_etherscan::errors::EtherscanError> {
+        use ethers_etherscan::errors::EtherscanError;
+
+        for address in addresses {
+            if self.contracts.contains_key(&address) {
+                continue
+            }
+            let metadata = match client.contract_source_code(address).await {
+                Ok(metadata) => metadata,
+                Err(EtherscanError::ContractCodeNotVerified(_)) => continue,
+                Err(err) => return Err(err),
+            };
+            if let Some(item) = metadata.items.first() {
+                let abi = item.abi()?;
+                self = self.with_contract(address, item.contract_name.clone(), &abi);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Returns all addresses that are called or that emit events in `frame`, e.g. to fetch their
+    /// ABIs.
+    pub fn addresses(frame: &CallFrame) -> BTreeSet<Address> {
+        let mut addresses = BTreeSet::new();
+        let mut frames = vec![frame];
+        while let Some(frame) = frames.pop() {
+            if let Some(NameOrAddress::Address(to)) = frame.to {
+                addresses.insert(to);
+            }
+            addresses.extend(frame.logs.iter().flatten().filter_map(|log| log.address));
+            frames.extend(frame.calls.iter().flatten());
+        }
+        addresses
+    }
+
+    /// Decodes a call tree returned by the geth `callTracer`.
+    pub fn decode_call_frame(&self, frame: &CallFrame) -> DecodedCall {
+        let to = match frame.to {
+            Some(NameOrAddress::Address(to)) => Some(to),
+            _ => None,
+        };
+        let (function, returns) = self.decode_function(to, &frame.input, frame.output.as_ref());
+        let revert_reason = frame
+            .error
+            .is_some()
+            .then(|| frame.output.as_ref().and_then(|output| self.decode_revert(to, output)))
+            .flatten();
+
+        DecodedCall {
+            kind: frame.typ.to_uppercase(),
+            from: frame.from,
+            to,
+            label: to.and_then(|to| self.labels.get(&to).cloned()),
+            value: frame.value.unwrap_or_default(),
+            gas_used: frame.gas_used,
+            function,
+            returns,
+            input: frame.input.clone(),
+            output: frame.output.clone(),
+            error: frame.error.clone(),
+            revert_reason,
+            logs: frame.logs.iter().flatten().map(|log| self.decode_log(log)).collect(),
+            calls: frame.calls.iter().flatten().map(|call| self.decode_call_frame(call)).collect(),
+        }
+    }
+
+    /// Decodes the traces of a transaction returned by `trace_transaction` or the `flatCallTracer`.
+    ///
+    /// Returns one call tree per transaction if the traces of several transactions are passed.
+    /// Rewards are skipped.
+    pub fn decode_traces(&self, traces: &[Trace]) -> Vec<DecodedCall> {
+        call_frames(traces).iter().map(|frame| self.decode_call_frame(frame)).collect()
+    }
+
+    /// Decodes the revert data of a call to `to`.
+    ///
+    /// `Error(string)` reverts are decoded into the message, panics into their code and custom
+    /// errors into their name and arguments.
+    pub fn decode_revert(&self, to: Option<Address>, data: &[u8]) -> Option<String> {
+        let selector: Selector = data.get(..4)?.try_into().expect("checked by get");
+        if let Some(reason) = String::decode_with_selector(data) {
+            return Some(reason)
+        }
+        if selector == PANIC_SELECTOR {
+            let code = U256::from_big_endian(data.get(4..36)?);
+            return Some(format!("panic: {} ({code:#x})", panic_reason(code)))
+        }
+
+        let contract = to.and_then(|to| self.contracts.get(&to));
+        let (error, tokens) = contract
+            .into_iter()
+            .chain([&self.signatures])
+            .find_map(|db| db.decode_error(data).into_iter().next())?;
+        let args = tokens.iter().map(render::fmt_token).collect::<Vec<_>>();
+        Some(format!("{}({})", error.name, args.join(", ")))
+    }
+
+    /// Decodes a log, emitted by a contract in a call trace.
+    pub fn decode_log(&self, log: &CallLogFrame) -> DecodedLog {
+        let topics = log.topics.clone().unwrap_or_default();
+        let data = log.data.clone().unwrap_or_default();
+        let mut decoded = DecodedLog {
+            address: log.address,
+            label: log.address.and_then(|address| self.labels.get(&address).cloned()),
+            event: None,
+            params: Vec::new(),
+            topics,
+            data,
+        };
+        let raw = RawLog { topics: decoded.topics.clone(), data: decoded.data.to_vec() };
+        let contract = log.address.and_then(|address| self.contracts.get(&address));
+        if let Some((event, log)) = contract
+            .into_iter()
+            .chain([&self.signatures])
+            .find_map(|db| db.decode_log(&raw).into_iter().next())
+        {
+            decoded.event = Some(event.name.clone());
+            decoded.params = log
+                .params
+                .into_iter()
+                .map(|param| DecodedParam { name: param.name, value: param.value })
+                .collect();
+        }
+        decoded
+    }
+
+    fn decode_function(
+        &self,
+        to: Option<Address>,
+        input: &[u8],
+        output: Option<&Bytes>,
+    ) -> (Option<DecodedFunction>, Option<Vec<DecodedParam>>) {
+        let contract = to.and_then(|to| self.contracts.get(&to));
+        let Some((function, inputs)) = contract
+            .into_iter()
+            .chain([&self.signatures])
+            .find_map(|db| db.decode_calldata(input).into_iter().next())
+        else {
+            return (None, None)
+        };
+
+        let returns = output
+            .and_then(|output| function.decode_output(output).ok())
+            .map(|tokens| params(function.outputs.iter().map(|param| param.name.as_str()), tokens));
+        let decoded = DecodedFunction {
+            name: function.name.clone(),
+            signature: function.abi_signature(),
+            inputs: params(function.inputs.iter().map(|param| param.name.as_str()), inputs),
+        };
+        (Some(decoded), returns)
+    }
+}
+
+/// A decoded call of a call trace.
+///
+/// The [`Display`](std::fmt::Display) implementation renders the call tree like `cast run`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedCall {
+    /// The type of the call, e.g. `CALL`, `STATICCALL` or `CREATE2`.
+    pub kind: String,
+    /// The caller.
+    pub from: Address,
+    /// The called contract, or the created contract.
+    pub to: Option<Address>,
+    /// The label of the called contract.
+    pub label: Option<String>,
+    /// The transferred value.
+    pub value: U256,
+    /// The gas used by the call.
+    pub gas_used: U256,
+    /// The decoded function and its arguments.
+    pub function: Option<DecodedFunction>,
+    /// The decoded return values of a successful call.
+    pub returns: Option<Vec<DecodedParam>>,
+    /// The input of the call.
+    pub input: Bytes,
+    /// The output of the call.
+    pub output: Option<Bytes>,
+    /// The error of a failed call, as reported by the node.
+    pub error: Option<String>,
+    /// The decoded revert reason of a failed call.
+    pub revert_reason: Option<String>,
+    /// The events emitted by the called contract.
+    pub logs: Vec<DecodedLog>,
+    /// The sub calls.
+    pub calls: Vec<DecodedCall>,
+}
+
+impl DecodedCall {
+    /// Returns whether the call failed.
+    pub fn is_error(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// A decoded function call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedFunction {
+    /// The name of the function.
+    pub name: String,
+    /// The signature of the function, e.g. `transfer(address,uint256)`.
+    pub signature: String,
+    /// The decoded arguments.
+    pub inputs: Vec<DecodedParam>,
+}
+
+/// A decoded event of a call trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedLog {
+    /// The emitting contract.
+    pub address: Option<Address>,
+    /// The label of the emitting contract.
+    pub label: Option<String>,
+    /// The name of the event, if it is known.
+    pub event: Option<String>,
+    /// The decoded parameters of the event.
+    pub params: Vec<DecodedParam>,
+    /// The raw topics.
+    pub topics: Vec<H256>,
+    /// The raw data.
+    pub data: Bytes,
+}
+
+/// A decoded argument, return value or event parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedParam {
+    /// The name of the parameter, which may be empty.
+    pub name: String,
+    /// The decoded value.
+    pub value: Token,
+}
+
+fn params<'a>(names: impl Iterator<Item = &'a str>, tokens: Vec<Token>) -> Vec<DecodedParam> {
+    names
+        .chain(std::iter::repeat(""))
+        .zip(tokens)
+        .map(|(name, value)| DecodedParam { name: name.to_string(), value })
+        .collect()
+}
+
+/// Returns the description of a Solidity panic code
+fn panic_reason(code: U256) -> &'static str {
+    match code.low_u64() {
+        0x00 => "generic panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic underflow or overflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to uninitialized function",
+        _ => "unknown panic code",
+    }
+}
+
+/// Converts parity traces into call trees, using the trace address of each trace
+fn call_frames(traces: &[Trace]) -> Vec<CallFrame> {
+    let mut roots: Vec<CallFrame> = Vec::new();
+    for trace in traces {
+        let Some(frame) = call_frame(trace) else { continue };
+        let Some((_, parents)) = trace.trace_address.split_last() else {
+            roots.push(frame);
+            continue
+        };
+
+        let mut parent = roots.last_mut();
+        for index in parents {
+            parent = parent.and_then(|p| p.calls.as_mut()?.get_mut(*index));
+        }
+        match parent {
+            Some(parent) => parent.calls.get_or_insert_with(Vec::new).push(frame),
+            // the parent is missing, e.g. because the traces are incomplete
+            None => roots.push(frame),
+        }
+    }
+    roots
+}
+
+/// Converts a single parity trace into a call frame without sub calls
+fn call_frame(trace: &Trace) -> Option<CallFrame> {
+    let mut frame = match &trace.action {
+        Action::Call(call) => CallFrame {
+            typ: serde_json::to_value(&call.call_type).ok()?.as_str()?.to_string(),
+            from: call.from,
+            to: Some(call.to.into()),
+            value: Some(call.value),
+            gas: call.gas,
+            input: call.input.clone(),
+            ..Default::default()
+        },
+        Action::Create(create) => CallFrame {
+            typ: "create".to_string(),
+            from: create.from,
+            value: Some(create.value),
+            gas: create.gas,
+            input: create.init.clone(),
+            ..Default::default()
+        },
+        Action::Suicide(suicide) => CallFrame {
+            typ: "selfdestruct".to_string(),
+            from: suicide.address,
+            to: Some(suicide.refund_address.into()),
+            value: Some(suicide.balance),
+            ..Default::default()
+        },
+        Action::Reward(_) => return None,
+    };
+    match &trace.result {
+        Some(Res::Call(result)) => {
+            frame.gas_used = result.gas_used;
+            frame.output = Some(result.output.clone());
+        }
+        Some(Res::Create(result)) => {
+            frame.gas_used = result.gas_used;
+            frame.to = Some(result.address.into());
+            frame.output = Some(result.code.clone());
+        }
+        Some(Res::None) | None => {}
+    }
+    frame.error = trace.error.clone();
+    Some(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::{
+        abi::{encode, parse_abi, AbiEncode},
+        types::{ActionType, Call, CallResult, CallType},
+        utils::id,
+    };
+
+    fn token_abi() -> Abi {
+        parse_abi(&[
+            "function transfer(address to, uint256 amount) returns (bool)",
+            "function balanceOf(address owner) view returns (uint256)",
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+            "error InsufficientBalance(uint256 available, uint256 required)",
+        ])
+        .unwrap()
+    }
+
+    fn transfer_frame() -> CallFrame {
+        let token = Address::repeat_byte(0x11);
+        let sender = Address::repeat_byte(0x22);
+        let recipient = Address::repeat_byte(0x33);
+        let transfer = token_abi().function("transfer").unwrap().clone();
+        let balance_of = token_abi().function("balanceOf").unwrap().clone();
+
+        CallFrame {
+            typ: "CALL".to_string(),
+            from: sender,
+            to: Some(token.into()),
+            value: Some(U256::zero()),
+            gas_used: 30_000u64.into(),
+            input: transfer
+                .encode_input(&[Token::Address(recipient), Token::Uint(100u64.into())])
+                .unwrap()
+                .into(),
+            output: Some(encode(&[Token::Bool(true)]).into()),
+            calls: Some(vec![CallFrame {
+                typ: "STATICCALL".to_string(),
+                from: token,
+                to: Some(token.into()),
+                gas_used: 2_000u64.into(),
+                input: balance_of.encode_input(&[Token::Address(sender)]).unwrap().into(),
+                output: Some(U256::from(1_000u64).encode().into()),
+                ..Default::default()
+            }]),
+            logs: Some(vec![CallLogFrame {
+                address: Some(token),
+                topics: Some(vec![
+                    token_abi().event("Transfer").unwrap().signature(),
+                    sender.into(),
+                    recipient.into(),
+                ]),
+                data: Some(U256::from(100u64).encode().into()),
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn can_decode_call_frame() {
+        let decoder = CallTraceDecoder::new().with_contract(
+            Address::repeat_byte(0x11),
+            "Token",
+            &token_abi(),
+        );
+        let call = decoder.decode_call_frame(&transfer_frame());
+
+        let function = call.function.as_ref().unwrap();
+        assert_eq!(function.signature, "transfer(address,uint256)");
+        assert_eq!(function.inputs[0].name, "to");
+        assert_eq!(function.inputs[1].value, Token::Uint(100u64.into()));
+        assert_eq!(call.returns.as_ref().unwrap()[0].value, Token::Bool(true));
+        assert_eq!(call.label.as_deref(), Some("Token"));
+        assert_eq!(call.logs[0].event.as_deref(), Some("Transfer"));
+        assert_eq!(call.calls[0].function.as_ref().unwrap().name, "balanceOf");
+
+        let rendered = call.to_string();
+        assert!(rendered.starts_with(&format!(
+            "[30000] Token::transfer(to: {:?}, amount: 100)",
+            Address::repeat_byte(0x33)
+        )));
+        assert!(rendered.contains("emit Transfer(from: "));
+        assert!(rendered.contains("├─ [2000] Token::balanceOf("));
+        assert!(rendered.contains("└─ ← 1000"));
+        assert!(rendered.trim_end().ends_with("└─ ← true"));
+    }
+
+    #[test]
+    fn can_decode_with_signatures() {
+        let decoder = CallTraceDecoder::new().with_signatures([
+            "# comment",
+            "transfer(address,uint256)",
+            "event Transfer(address,address,uint256)",
+        ]);
+        let call = decoder.decode_call_frame(&transfer_frame());
+        assert_eq!(call.function.as_ref().unwrap().name, "transfer");
+        assert_eq!(call.label, None);
+        // the output is decoded with the outputs of the signature, which are unknown
+        assert_eq!(call.returns, Some(vec![]));
+        assert_eq!(call.logs[0].event.as_deref(), Some("Transfer"));
+        assert_eq!(call.logs[0].params[2].value, Token::Uint(100u64.into()));
+        assert_eq!(call.calls[0].function, None);
+    }
+
+    #[test]
+    fn can_decode_reverts() {
+        let token_address = Address::repeat_byte(0x11);
+        let decoder = CallTraceDecoder::new().with_contract(token_address, "Token", &token_abi());
+
+        let reason = encode(&[Token::String("not allowed".to_string())]);
+        let data = [&String::selector()[..], &reason].concat();
+        assert_eq!(decoder.decode_revert(None, &data).unwrap(), "not allowed");
+
+        let data = [&PANIC_SELECTOR[..], &U256::from(0x11).encode()].concat();
+        assert_eq!(
+            decoder.decode_revert(None, &data).unwrap(),
+            "panic: arithmetic underflow or overflow (0x11)"
+        );
+
+        let error = id("InsufficientBalance(uint256,uint256)");
+        let data =
+            [&error[..], &encode(&[Token::Uint(1u64.into()), Token::Uint(2u64.into())])].concat();
+        assert_eq!(
+            decoder.decode_revert(Some(token_address), &data).unwrap(),
+            "InsufficientBalance(1, 2)"
+        );
+        assert_eq!(decoder.decode_revert(None, &[0xde, 0xad, 0xbe, 0xef]), None);
+    }
+
+    #[test]
+    fn can_decode_parity_traces() {
+        let token_address = Address::repeat_byte(0x11);
+        let frame = transfer_frame();
+        let trace = |action: Call, output: &Option<Bytes>, trace_address: Vec<usize>| Trace {
+            action: Action::Call(action),
+            result: Some(Res::Call(CallResult {
+                gas_used: 0u64.into(),
+                output: output.clone().unwrap(),
+            })),
+            subtraces: usize::from(trace_address.is_empty()),
+            trace_address,
+            transaction_position: Some(0),
+            transaction_hash: None,
+            block_number: 1,
+            block_hash: H256::zero(),
+            action_type: ActionType::Call,
+            error: None,
+        };
+        let inner = &frame.calls.as_ref().unwrap()[0];
+        let traces = vec![
+            trace(
+                Call {
+                    from: frame.from,
+                    to: token_address,
+                    input: frame.input.clone(),
+                    call_type: CallType::Call,
+                    ..Default::default()
+                },
+                &frame.output,
+                vec![],
+            ),
+            trace(
+                Call {
+                    from: token_address,
+                    to: token_address,
+                    input: inner.input.clone(),
+                    call_type: CallType::StaticCall,
+                    ..Default::default()
+                },
+                &inner.output,
+                vec![0],
+            ),
+        ];
+
+        let decoder = CallTraceDecoder::new().with_contract(token_address, "Token", &token_abi());
+        let calls = decoder.decode_traces(&traces);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].kind, "CALL");
+        assert_eq!(calls[0].calls[0].kind, "STATICCALL");
+        assert_eq!(calls[0].calls[0].function.as_ref().unwrap().name, "balanceOf");
+    }
+
+    #[test]
+    fn can_collect_addresses() {
+        let addresses = CallTraceDecoder::addresses(&transfer_frame());
+        assert_eq!(addresses.into_iter().collect::<Vec<_>>(), vec![Address::repeat_byte(0x11)]);
+    }
+}
@@ -0,0 +1,158 @@
+//! Renders decoded call trees like `cast run`.
+
+use super::{DecodedCall, DecodedLog, DecodedParam};
+use ethers_core::{abi::Token, types::I256};
+use std::fmt;
+
+impl fmt::Display for DecodedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f, "")
+    }
+}
+
+impl DecodedCall {
+    /// Writes the call and its children, `indent` is the prefix of the child lines
+    fn render(&self, f: &mut fmt::Formatter<'_>, indent: &str) -> fmt::Result {
+        self.fmt_header(f)?;
+        writeln!(f)?;
+
+        let child_indent = format!("{indent}│  ");
+        for call in &self.calls {
+            write!(f, "{indent}├─ ")?;
+            call.render(f, &child_indent)?;
+        }
+        for log in &self.logs {
+            writeln!(f, "{indent}├─ emit {log}")?;
+        }
+        write!(f, "{indent}└─ ← ")?;
+        self.fmt_result(f)?;
+        writeln!(f)
+    }
+
+    fn target(&self) -> String {
+        match (&self.label, self.to) {
+            (Some(label), _) => label.clone(),
+            (None, Some(to)) => format!("{to:?}"),
+            (None, None) => "<unknown>".to_string(),
+        }
+    }
+
+    fn fmt_header(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] ", self.gas_used)?;
+        if self.kind.starts_with("CREATE") {
+            write!(f, "→ new {}", self.label.as_deref().unwrap_or("<unknown>"))?;
+            if let Some(to) = self.to {
+                write!(f, "@{to:?}")?;
+            }
+            return Ok(())
+        }
+        if self.kind == "SELFDESTRUCT" {
+            return write!(f, "{:?}::selfdestruct({})", self.from, self.target())
+        }
+
+        write!(f, "{}::", self.target())?;
+        match &self.function {
+            Some(function) => write!(f, "{}", function.name)?,
+            None if self.input.is_empty() => write!(f, "fallback")?,
+            None => write!(f, "{}", self.input)?,
+        }
+        if !self.value.is_zero() {
+            write!(f, "{{value: {}}}", self.value)?;
+        }
+        if let Some(function) = &self.function {
+            write!(f, "({})", fmt_params(&function.inputs, true))?;
+        } else if self.input.is_empty() {
+            write!(f, "()")?;
+        }
+        match self.kind.as_str() {
+            "CALL" => Ok(()),
+            kind => write!(f, " [{}]", kind.to_lowercase()),
+        }
+    }
+
+    fn fmt_result(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = self.output.as_ref().filter(|output| !output.is_empty());
+        if let Some(error) = &self.error {
+            return match (&self.revert_reason, output) {
+                (Some(reason), _) => write!(f, "[Revert] {reason}"),
+                (None, Some(output)) => write!(f, "[Revert] {output}"),
+                (None, None) => write!(f, "[{error}]"),
+            }
+        }
+        if self.kind.starts_with("CREATE") {
+            return write!(f, "{} bytes of code", output.map_or(0, |output| output.len()))
+        }
+        match (&self.returns, output) {
+            (Some(returns), _) if !returns.is_empty() => {
+                write!(f, "{}", fmt_params(returns, false))
+            }
+            (_, Some(output)) => write!(f, "{output}"),
+            (_, None) => write!(f, "[Stop]"),
+        }
+    }
+}
+
+impl fmt::Display for DecodedLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(event) = &self.event {
+            return write!(f, "{event}({})", fmt_params(&self.params, true))
+        }
+        for (i, topic) in self.topics.iter().enumerate() {
+            write!(f, "topic {i}: {topic:?}, ")?;
+        }
+        write!(f, "data: {}", self.data)
+    }
+}
+
+/// Formats parameters as a comma separated list, optionally prefixed with their names
+fn fmt_params(params: &[DecodedParam], with_names: bool) -> String {
+    params
+        .iter()
+        .map(|param| match param.name.as_str() {
+            name if with_names && !name.is_empty() => {
+                format!("{name}: {}", fmt_token(&param.value))
+            }
+            _ => fmt_token(&param.value),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats a token like Solidity literals, numbers are formatted as decimals
+pub(super) fn fmt_token(token: &Token) -> String {
+    let fmt_list = |tokens: &[Token]| tokens.iter().map(fmt_token).collect::<Vec<_>>().join(", ");
+    match token {
+        Token::Address(address) => format!("{address:?}"),
+        Token::Uint(value) => value.to_string(),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => format!("{value:?}"),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Array(tokens) | Token::FixedArray(tokens) => format!("[{}]", fmt_list(tokens)),
+        Token::Tuple(tokens) => format!("({})", fmt_list(tokens)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, U256};
+
+    #[test]
+    fn can_format_tokens() {
+        let token = Token::Tuple(vec![
+            Token::Int(I256::from(-5).into_raw()),
+            Token::Array(vec![Token::Uint(U256::from(7u64)), Token::Uint(U256::MAX)]),
+            Token::String("hi".to_string()),
+            Token::FixedBytes(vec![0xab, 0xcd]),
+            Token::Address(Address::zero()),
+        ]);
+        assert_eq!(
+            fmt_token(&token),
+            format!(
+                "(-5, [7, {}], \"hi\", 0xabcd, 0x0000000000000000000000000000000000000000)",
+                U256::MAX
+            )
+        );
+    }
+}
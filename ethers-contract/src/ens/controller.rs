@@ -0,0 +1,36 @@
+//! Bindings for the [`ETHRegistrarController`](https://docs.ens.domains/registry/eth), which
+//! registers and renews `.eth` names with a commit/reveal scheme.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        ETHRegistrarController,
+        r#"[
+            struct Price { uint256 base; uint256 premium; }
+            function rentPrice(string name, uint256 duration) external view returns (Price price)
+            function valid(string name) external pure returns (bool)
+            function available(string name) external view returns (bool)
+            function minCommitmentAge() external view returns (uint256)
+            function maxCommitmentAge() external view returns (uint256)
+            function commitments(bytes32 commitment) external view returns (uint256)
+            function makeCommitment(string name, address owner, uint256 duration, bytes32 secret, address resolver, bytes[] data, bool reverseRecord, uint16 ownerControlledFuses) external pure returns (bytes32)
+            function commit(bytes32 commitment) external
+            function register(string name, address owner, uint256 duration, bytes32 secret, address resolver, bytes[] data, bool reverseRecord, uint16 ownerControlledFuses) external payable
+            function renew(string name, uint256 duration) external payable
+            event NameRegistered(string name, bytes32 indexed label, address indexed owner, uint256 baseCost, uint256 premium, uint256 expires)
+            event NameRenewed(string name, bytes32 indexed label, uint256 cost, uint256 expires)
+        ]"#
+    );
+}
+pub use generated::*;
+
+use ethers_core::types::U256;
+
+impl Price {
+    /// Returns the total price, in wei.
+    pub fn total(&self) -> U256 {
+        self.base.saturating_add(self.premium)
+    }
+}
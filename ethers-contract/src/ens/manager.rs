@@ -0,0 +1,596 @@
+use super::{
+    controller::{ETHRegistrarController, Price},
+    fuses, labelhash,
+    name_wrapper::NameWrapper,
+    registry::ENSRegistry,
+    resolver::{PublicResolver, Record},
+    reverse_registrar::ReverseRegistrar,
+    EnsDeployment,
+};
+use crate::{ContractCall, ContractError};
+use ethers_core::{
+    abi::{self, Token},
+    types::{Address, Bytes, H256, U256},
+    utils::keccak256,
+};
+use ethers_providers::{ens::namehash, Middleware};
+use std::{sync::Arc, time::Duration};
+
+/// The registration and renewal fees are sent with this margin, in percent, in case the price
+/// rises before the transaction is mined. The controller refunds any excess.
+const PRICE_MARGIN_PERCENT: u64 = 3;
+
+/// Errors of the [`EnsManager`]
+#[derive(Debug, thiserror::Error)]
+pub enum EnsManagerError<M: Middleware> {
+    /// Contract call returned an error
+    #[error(transparent)]
+    ContractError(#[from] ContractError<M>),
+
+    /// Unsupported chain
+    #[error("ENS is not deployed on chain {0}. Provide the deployment instead.")]
+    UnsupportedChain(u64),
+
+    /// The name cannot be registered with the `.eth` registrar
+    #[error("`{0}` is not a valid .eth name to register")]
+    InvalidName(String),
+
+    /// The name has no resolver to set records on
+    #[error("`{0}` has no resolver")]
+    NoResolver(String),
+
+    /// Fuses were set on a name that is not wrapped
+    #[error("`{0}` is not wrapped")]
+    NotWrapped(String),
+
+    /// Fuses not controlled by the owner of a name were passed to [`EnsManager::set_fuses`]
+    #[error("fuses {0:#x} are not owner-controlled")]
+    InvalidFuses(u32),
+}
+
+/// Manages ENS names through the contracts of an [`EnsDeployment`]: registration and renewal of
+/// `.eth` names, records, fuses and subnames of wrapped names, and primary names.
+///
+/// The transactions are returned as [`ContractCall`]s, to be sent by a `Middleware` with a signer
+/// for the owner of the names.
+#[derive(Debug)]
+pub struct EnsManager<M> {
+    deployment: EnsDeployment,
+    registry: ENSRegistry<M>,
+    controller: ETHRegistrarController<M>,
+    name_wrapper: NameWrapper<M>,
+    reverse_registrar: ReverseRegistrar<M>,
+    client: Arc<M>,
+}
+
+impl<M> Clone for EnsManager<M> {
+    fn clone(&self) -> Self {
+        Self {
+            deployment: self.deployment,
+            registry: self.registry.clone(),
+            controller: self.controller.clone(),
+            name_wrapper: self.name_wrapper.clone(),
+            reverse_registrar: self.reverse_registrar.clone(),
+            client: self.client.clone(),
+        }
+    }
+}
+
+impl<M: Middleware> EnsManager<M> {
+    /// Creates a new manager for the deployment on the chain of the client.
+    ///
+    /// Returns [`EnsManagerError::UnsupportedChain`] if there is no known deployment on the chain,
+    /// use [`EnsManager::with_deployment`] instead.
+    pub async fn new(client: impl Into<Arc<M>>) -> Result<Self, EnsManagerError<M>> {
+        let client = client.into();
+        let chain_id =
+            client.get_chainid().await.map_err(ContractError::from_middleware_error)?.as_u64();
+        let deployment = EnsDeployment::for_chain(chain_id)
+            .ok_or(EnsManagerError::UnsupportedChain(chain_id))?;
+        Ok(Self::with_deployment(client, deployment))
+    }
+
+    /// Creates a new manager for the given deployment.
+    pub fn with_deployment(client: impl Into<Arc<M>>, deployment: EnsDeployment) -> Self {
+        let client = client.into();
+        Self {
+            deployment,
+            registry: ENSRegistry::new(deployment.registry, client.clone()),
+            controller: ETHRegistrarController::new(deployment.controller, client.clone()),
+            name_wrapper: NameWrapper::new(deployment.name_wrapper, client.clone()),
+            reverse_registrar: ReverseRegistrar::new(deployment.reverse_registrar, client.clone()),
+            client,
+        }
+    }
+
+    /// Returns the addresses of the contracts.
+    pub fn deployment(&self) -> &EnsDeployment {
+        &self.deployment
+    }
+
+    /// Returns the ENS registry.
+    pub fn registry(&self) -> &ENSRegistry<M> {
+        &self.registry
+    }
+
+    /// Returns the `ETHRegistrarController`.
+    pub fn controller(&self) -> &ETHRegistrarController<M> {
+        &self.controller
+    }
+
+    /// Returns the `NameWrapper`.
+    pub fn name_wrapper(&self) -> &NameWrapper<M> {
+        &self.name_wrapper
+    }
+
+    /// Returns the `ReverseRegistrar`.
+    pub fn reverse_registrar(&self) -> &ReverseRegistrar<M> {
+        &self.reverse_registrar
+    }
+
+    /// Returns the resolver of `name`, as set in the registry.
+    pub async fn resolver(&self, name: &str) -> Result<PublicResolver<M>, EnsManagerError<M>> {
+        let resolver = self.registry.resolver(namehash(name).0).call().await?;
+        if resolver.is_zero() {
+            return Err(EnsManagerError::NoResolver(name.to_string()))
+        }
+        Ok(PublicResolver::new(resolver, self.client.clone()))
+    }
+
+    /// Returns whether `name` is wrapped by the `NameWrapper`.
+    pub async fn is_wrapped(&self, name: &str) -> Result<bool, EnsManagerError<M>> {
+        let owner = self.registry.owner(namehash(name).0).call().await?;
+        Ok(owner == self.deployment.name_wrapper)
+    }
+
+    // === Registration ===
+
+    /// Returns a new registration of `name`, e.g. `vitalik` or `vitalik.eth`, for `owner` for
+    /// the given duration.
+    ///
+    /// The registration uses the public resolver and a random secret. It must be kept between
+    /// [`commit`](Self::commit) and [`register`](Self::register), since registering requires the
+    /// same parameters.
+    pub fn registration(
+        &self,
+        name: &str,
+        owner: Address,
+        duration: Duration,
+    ) -> Result<Registration, EnsManagerError<M>> {
+        let label = name.strip_suffix(".eth").unwrap_or(name);
+        if label.contains('.') || label.chars().count() < 3 {
+            return Err(EnsManagerError::InvalidName(name.to_string()))
+        }
+        Ok(Registration {
+            label: label.to_string(),
+            owner,
+            duration,
+            secret: H256::random(),
+            resolver: self.deployment.public_resolver,
+            records: Vec::new(),
+            reverse_record: false,
+            fuses: 0,
+        })
+    }
+
+    /// Returns whether `name` is available for registration.
+    pub async fn available(&self, name: &str) -> Result<bool, EnsManagerError<M>> {
+        let label = name.strip_suffix(".eth").unwrap_or(name);
+        Ok(self.controller.available(label.to_string()).call().await?)
+    }
+
+    /// Returns the price of registering or renewing `name` for the given duration, in wei.
+    pub async fn rent_price(
+        &self,
+        name: &str,
+        duration: Duration,
+    ) -> Result<Price, EnsManagerError<M>> {
+        let label = name.strip_suffix(".eth").unwrap_or(name);
+        let (base, premium) =
+            self.controller.rent_price(label.to_string(), duration.as_secs().into()).call().await?;
+        Ok(Price { base, premium })
+    }
+
+    /// Returns the time to wait between the commitment and the registration.
+    pub async fn min_commitment_age(&self) -> Result<Duration, EnsManagerError<M>> {
+        let age = self.controller.min_commitment_age().call().await?;
+        Ok(Duration::from_secs(age.low_u64()))
+    }
+
+    /// Returns the call committing to the registration, the first step of the commit/reveal
+    /// scheme that prevents front-running.
+    pub fn commit(&self, registration: &Registration) -> ContractCall<M, ()> {
+        self.controller.commit(registration.commitment().0)
+    }
+
+    /// Returns the call registering the name, after the minimum commitment age passed since the
+    /// commitment was mined.
+    ///
+    /// The call pays the current rent price.
+    pub async fn register(
+        &self,
+        registration: &Registration,
+    ) -> Result<ContractCall<M, ()>, EnsManagerError<M>> {
+        let price = self.rent_price(&registration.label, registration.duration).await?;
+        let call = self
+            .controller
+            .register(
+                registration.label.clone(),
+                registration.owner,
+                registration.duration.as_secs().into(),
+                registration.secret.0,
+                registration.resolver,
+                registration.data(),
+                registration.reverse_record,
+                registration.fuses,
+            )
+            .value(with_margin(price.total()));
+        Ok(call)
+    }
+
+    /// Returns the call renewing the `.eth` name for the given duration, paying the current rent
+    /// price.
+    pub async fn renew(
+        &self,
+        name: &str,
+        duration: Duration,
+    ) -> Result<ContractCall<M, ()>, EnsManagerError<M>> {
+        let label = name.strip_suffix(".eth").unwrap_or(name);
+        let price = self.rent_price(label, duration).await?;
+        let call = self
+            .controller
+            .renew(label.to_string(), duration.as_secs().into())
+            .value(with_margin(price.base));
+        Ok(call)
+    }
+
+    // === Records ===
+
+    /// Returns the call setting the records of `name` on its resolver, in a single transaction.
+    pub async fn set_records(
+        &self,
+        name: &str,
+        records: &[Record],
+    ) -> Result<ContractCall<M, Vec<Bytes>>, EnsManagerError<M>> {
+        let resolver = self.resolver(name).await?;
+        let node = namehash(name);
+        Ok(resolver.multicall(records.iter().map(|record| record.encode(node)).collect()))
+    }
+
+    // === NameWrapper ===
+
+    /// Returns the call burning the owner-controlled `fuses` of the wrapped `name`.
+    pub fn set_fuses(
+        &self,
+        name: &str,
+        fuses: u32,
+    ) -> Result<ContractCall<M, u32>, EnsManagerError<M>> {
+        if fuses & !fuses::OWNER_CONTROLLED != 0 {
+            return Err(EnsManagerError::InvalidFuses(fuses))
+        }
+        Ok(self.name_wrapper.set_fuses(namehash(name).0, fuses as u16))
+    }
+
+    /// Returns the call setting the `fuses` and the expiry of the subname `label` of the wrapped
+    /// `parent`.
+    pub fn set_child_fuses(
+        &self,
+        parent: &str,
+        label: &str,
+        fuses: u32,
+        expiry: u64,
+    ) -> ContractCall<M, ()> {
+        self.name_wrapper.set_child_fuses(namehash(parent).0, labelhash(label).0, fuses, expiry)
+    }
+
+    /// Returns the call creating or updating a subname of `parent`.
+    ///
+    /// Subnames of wrapped names are created by the `NameWrapper`, subnames of other names by the
+    /// registry, in which case the subname cannot have fuses.
+    pub async fn create_subname(
+        &self,
+        parent: &str,
+        subname: &Subname,
+    ) -> Result<ContractCall<M, ()>, EnsManagerError<M>> {
+        let wrapped = self.is_wrapped(parent).await?;
+        self.subname_call(parent, subname, wrapped)
+    }
+
+    /// Returns the calls creating or updating the subnames of `parent`.
+    ///
+    /// See [`create_subname`](Self::create_subname).
+    pub async fn create_subnames(
+        &self,
+        parent: &str,
+        subnames: &[Subname],
+    ) -> Result<Vec<ContractCall<M, ()>>, EnsManagerError<M>> {
+        let wrapped = self.is_wrapped(parent).await?;
+        subnames.iter().map(|subname| self.subname_call(parent, subname, wrapped)).collect()
+    }
+
+    fn subname_call(
+        &self,
+        parent: &str,
+        subname: &Subname,
+        wrapped: bool,
+    ) -> Result<ContractCall<M, ()>, EnsManagerError<M>> {
+        let parent_node = namehash(parent).0;
+        let resolver = subname.resolver.unwrap_or(self.deployment.public_resolver);
+        if wrapped {
+            let call = self.name_wrapper.set_subnode_record(
+                parent_node,
+                subname.label.clone(),
+                subname.owner,
+                resolver,
+                subname.ttl,
+                subname.fuses,
+                subname.expiry,
+            );
+            return Ok(call.discard_output())
+        }
+        if subname.fuses != 0 || subname.expiry != 0 {
+            return Err(EnsManagerError::NotWrapped(parent.to_string()))
+        }
+        Ok(self.registry.set_subnode_record(
+            parent_node,
+            labelhash(&subname.label).0,
+            subname.owner,
+            resolver,
+            subname.ttl,
+        ))
+    }
+
+    // === Reverse records ===
+
+    /// Returns the call setting `name` as the primary name of the sender.
+    ///
+    /// The name should resolve to the sender's address, otherwise clients ignore it.
+    pub fn set_primary_name(&self, name: &str) -> ContractCall<M, [u8; 32]> {
+        self.reverse_registrar.set_name(name.to_string())
+    }
+
+    /// Returns the call setting `name` as the primary name of `addr`, which the sender must
+    /// control, e.g. a contract owned by the sender.
+    pub fn set_primary_name_for(
+        &self,
+        addr: Address,
+        owner: Address,
+        name: &str,
+    ) -> ContractCall<M, [u8; 32]> {
+        self.reverse_registrar.set_name_for_addr(
+            addr,
+            owner,
+            self.deployment.public_resolver,
+            name.to_string(),
+        )
+    }
+}
+
+fn with_margin(price: U256) -> U256 {
+    price.saturating_add(price.saturating_mul(PRICE_MARGIN_PERCENT.into()) / 100)
+}
+
+/// The parameters of a `.eth` name registration, created with [`EnsManager::registration`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registration {
+    /// The label of the name, without the `.eth` suffix.
+    pub label: String,
+    /// The owner of the name.
+    pub owner: Address,
+    /// The duration of the registration.
+    pub duration: Duration,
+    /// The secret of the commitment.
+    pub secret: H256,
+    /// The resolver of the name.
+    pub resolver: Address,
+    /// The records set on the resolver on registration.
+    pub records: Vec<Record>,
+    /// Whether to set the name as the primary name of the sender.
+    pub reverse_record: bool,
+    /// The owner-controlled fuses burned on registration.
+    pub fuses: u16,
+}
+
+impl Registration {
+    /// Sets the records set on the resolver on registration.
+    pub fn with_records(mut self, records: Vec<Record>) -> Self {
+        self.records = records;
+        self
+    }
+
+    /// Sets whether to set the name as the primary name of the sender.
+    pub fn with_reverse_record(mut self, reverse_record: bool) -> Self {
+        self.reverse_record = reverse_record;
+        self
+    }
+
+    /// Sets the owner-controlled fuses burned on registration.
+    pub fn with_fuses(mut self, fuses: u16) -> Self {
+        self.fuses = fuses;
+        self
+    }
+
+    /// Sets the resolver of the name.
+    pub fn with_resolver(mut self, resolver: Address) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Sets the secret of the commitment.
+    pub fn with_secret(mut self, secret: H256) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Returns the registered name.
+    pub fn name(&self) -> String {
+        format!("{}.eth", self.label)
+    }
+
+    /// Returns the resolver calldata setting the records.
+    fn data(&self) -> Vec<Bytes> {
+        let node = namehash(&self.name());
+        self.records.iter().map(|record| record.encode(node)).collect()
+    }
+
+    /// Returns the commitment to the registration, as computed by the controller's
+    /// `makeCommitment`.
+    pub fn commitment(&self) -> H256 {
+        let data = self.data().into_iter().map(|data| Token::Bytes(data.to_vec())).collect();
+        let encoded = abi::encode(&[
+            Token::FixedBytes(labelhash(&self.label).0.to_vec()),
+            Token::Address(self.owner),
+            Token::Uint(self.duration.as_secs().into()),
+            Token::FixedBytes(self.secret.0.to_vec()),
+            Token::Address(self.resolver),
+            Token::Array(data),
+            Token::Bool(self.reverse_record),
+            Token::Uint(self.fuses.into()),
+        ]);
+        keccak256(encoded).into()
+    }
+}
+
+/// A subname created with [`EnsManager::create_subname`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subname {
+    /// The label of the subname, e.g. `alice` for `alice.example.eth`.
+    pub label: String,
+    /// The owner of the subname.
+    pub owner: Address,
+    /// The resolver of the subname, the public resolver if `None`.
+    pub resolver: Option<Address>,
+    /// The TTL of the records of the subname, in seconds.
+    pub ttl: u64,
+    /// The fuses burned on the subname, which requires a wrapped parent.
+    pub fuses: u32,
+    /// The expiry of the subname as a UNIX timestamp, capped to the expiry of the parent.
+    /// Requires a wrapped parent.
+    pub expiry: u64,
+}
+
+impl Subname {
+    /// Returns a new subname `label` owned by `owner`.
+    pub fn new(label: impl Into<String>, owner: Address) -> Self {
+        Self { label: label.into(), owner, resolver: None, ttl: 0, fuses: 0, expiry: 0 }
+    }
+
+    /// Sets the resolver of the subname.
+    pub fn with_resolver(mut self, resolver: Address) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Sets the TTL of the records of the subname.
+    pub fn with_ttl(mut self, ttl: u64) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the fuses burned on the subname.
+    pub fn with_fuses(mut self, fuses: u32) -> Self {
+        self.fuses = fuses;
+        self
+    }
+
+    /// Sets the expiry of the subname.
+    pub fn with_expiry(mut self, expiry: u64) -> Self {
+        self.expiry = expiry;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ens::{coin_type, name_wrapper::SetSubnodeRecordCall, resolver::MulticallCall};
+    use ethers_core::{
+        abi::AbiEncode,
+        types::{transaction::eip2718::TypedTransaction, BlockNumber},
+    };
+    use ethers_providers::Provider;
+
+    #[tokio::test]
+    async fn can_set_records() {
+        let (provider, mock) = Provider::mocked();
+        let ens = EnsManager::with_deployment(provider, EnsDeployment::MAINNET);
+        let resolver = Address::repeat_byte(1);
+        mock.push::<String, _>(resolver.encode_hex()).unwrap();
+
+        let records = [
+            Record::Addr(Address::repeat_byte(2)),
+            Record::evm_addr(10, Address::repeat_byte(3)),
+            Record::text("url", "https://example.com"),
+        ];
+        let call = ens.set_records("example.eth", &records).await.unwrap();
+        assert_eq!(call.tx.to_addr(), Some(&resolver));
+
+        let node = namehash("example.eth");
+        let data = records.iter().map(|record| record.encode(node)).collect();
+        assert_eq!(call.calldata().unwrap(), Bytes::from(MulticallCall { data }.encode()));
+        assert!(matches!(
+            &records[1],
+            Record::CoinAddr { coin_type, .. } if *coin_type == coin_type::evm(10)
+        ));
+
+        let tx: TypedTransaction = ens.registry().resolver(node.0).tx;
+        mock.assert_request("eth_call", (tx, BlockNumber::Latest)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn creates_subnames_of_wrapped_names() {
+        let (provider, mock) = Provider::mocked();
+        let ens = EnsManager::with_deployment(provider, EnsDeployment::MAINNET);
+        mock.push::<String, _>(EnsDeployment::MAINNET.name_wrapper.encode_hex()).unwrap();
+
+        let subnames = [
+            Subname::new("alice", Address::repeat_byte(1)),
+            Subname::new("bob", Address::repeat_byte(2))
+                .with_fuses(fuses::PARENT_CANNOT_CONTROL | fuses::CANNOT_UNWRAP)
+                .with_expiry(u64::MAX),
+        ];
+        let calls = ens.create_subnames("example.eth", &subnames).await.unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].tx.to_addr(), Some(&EnsDeployment::MAINNET.name_wrapper));
+        let expected = SetSubnodeRecordCall {
+            parent_node: namehash("example.eth").0,
+            label: "bob".to_string(),
+            owner: Address::repeat_byte(2),
+            resolver: EnsDeployment::MAINNET.public_resolver,
+            ttl: 0,
+            fuses: fuses::PARENT_CANNOT_CONTROL | fuses::CANNOT_UNWRAP,
+            expiry: u64::MAX,
+        };
+        assert_eq!(calls[1].calldata().unwrap(), Bytes::from(expected.encode()));
+
+        // fuses cannot be burned on subnames of unwrapped names
+        mock.push::<String, _>(Address::repeat_byte(3).encode_hex()).unwrap();
+        let err = ens.create_subname("example.eth", &subnames[1]).await.unwrap_err();
+        assert!(matches!(err, EnsManagerError::NotWrapped(_)));
+    }
+
+    #[tokio::test]
+    async fn can_commit_and_register() {
+        let (provider, mock) = Provider::mocked();
+        let ens = EnsManager::with_deployment(provider, EnsDeployment::MAINNET);
+        let owner = Address::repeat_byte(1);
+
+        assert!(ens.registration("ab", owner, Duration::from_secs(1)).is_err());
+        assert!(ens.registration("a.example.eth", owner, Duration::from_secs(1)).is_err());
+
+        let registration = ens
+            .registration("example.eth", owner, Duration::from_secs(31_536_000))
+            .unwrap()
+            .with_records(vec![Record::Addr(owner)])
+            .with_reverse_record(true);
+        assert_eq!(registration.name(), "example.eth");
+        let commit = ens.commit(&registration);
+        assert_eq!(commit.calldata().unwrap()[4..], registration.commitment().0);
+
+        let price = (U256::from(100u64), U256::zero()).encode_hex();
+        mock.push::<String, _>(price).unwrap();
+        let register = ens.register(&registration).await.unwrap();
+        assert_eq!(register.tx.value(), Some(&U256::from(103u64)));
+        assert_eq!(register.tx.to_addr(), Some(&EnsDeployment::MAINNET.controller));
+    }
+}
@@ -0,0 +1,168 @@
+//! Typed bindings and helpers for managing [ENS](https://docs.ens.domains/) names.
+//!
+//! The read side of ENS, i.e. resolving names and looking up addresses, is part of
+//! `ethers-providers`. This module covers the write side, which requires a `Middleware` with a
+//! signer:
+//!
+//! - [`controller`]: commit/reveal registration and renewal of `.eth` names
+//! - [`resolver`]: address ([ENSIP-9](https://docs.ens.domains/ensip/9)), text and contenthash
+//!   records
+//! - [`name_wrapper`]: [`fuses`] and subnames of wrapped names
+//! - [`reverse_registrar`]: the primary name of an address
+//! - [`registry`]: owners and resolvers of names
+//!
+//! `EnsManager` ties these together for the deployment of a chain.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ethers_contract::ens::{resolver::Record, EnsManager};
+//! use ethers_core::types::Address;
+//! use ethers_providers::{Http, Provider};
+//! use std::{convert::TryFrom, sync::Arc, time::Duration};
+//!
+//! # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+//! let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545")?);
+//! let ens = EnsManager::<Provider<Http>>::new(provider).await?;
+//! let owner = Address::random();
+//!
+//! // commit to the registration, wait for the minimum commitment age, then register
+//! let registration = ens
+//!     .registration("vitalik2", owner, Duration::from_secs(365 * 24 * 60 * 60))?
+//!     .with_records(vec![Record::Addr(owner), Record::text("url", "https://example.com")])
+//!     .with_reverse_record(true);
+//! ens.commit(&registration).send().await?.await?;
+//! tokio::time::sleep(ens.min_commitment_age().await?).await;
+//! ens.register(&registration).await?.send().await?.await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod controller;
+pub mod name_wrapper;
+pub mod registry;
+pub mod resolver;
+pub mod reverse_registrar;
+
+mod manager;
+pub use manager::{EnsManager, EnsManagerError, Registration, Subname};
+
+pub use ethers_providers::ens::coin_type;
+
+use ethers_core::{
+    types::{Address, Chain, H160, H256},
+    utils::keccak256,
+};
+
+/// The address of the ENS registry, which is the same on all chains ENS is deployed to.
+pub const ENS_REGISTRY_ADDRESS: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x2e, 0x07, 0x4e, 0xc6, 0x9a, 0x0d, 0xfb, 0x29, 0x97, 0xba,
+    0x6c, 0x7d, 0x2e, 0x1e,
+]);
+
+/// The addresses of the ENS contracts deployed to a chain.
+///
+/// See <https://docs.ens.domains/learn/deployments>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnsDeployment {
+    /// The ENS registry.
+    pub registry: Address,
+    /// The `ETHRegistrarController` registering `.eth` names.
+    pub controller: Address,
+    /// The `PublicResolver`, used as the resolver of new names.
+    pub public_resolver: Address,
+    /// The `NameWrapper`.
+    pub name_wrapper: Address,
+    /// The `ReverseRegistrar`.
+    pub reverse_registrar: Address,
+}
+
+impl EnsDeployment {
+    /// The deployment on the Ethereum mainnet.
+    pub const MAINNET: Self = Self {
+        registry: ENS_REGISTRY_ADDRESS,
+        controller: H160([
+            0x25, 0x35, 0x53, 0x36, 0x6d, 0xa8, 0x54, 0x6f, 0xc2, 0x50, 0xf2, 0x25, 0xfe, 0x3d,
+            0x25, 0xd0, 0xc7, 0x82, 0x30, 0x3b,
+        ]),
+        public_resolver: H160([
+            0x23, 0x1b, 0x0e, 0xe1, 0x40, 0x48, 0xe9, 0xdc, 0xcd, 0x1d, 0x24, 0x77, 0x44, 0xd1,
+            0x14, 0xa4, 0xeb, 0x5e, 0x8e, 0x63,
+        ]),
+        name_wrapper: H160([
+            0xd4, 0x41, 0x6b, 0x13, 0xd2, 0xb3, 0xa9, 0xab, 0xae, 0x7a, 0xcd, 0x5d, 0x6c, 0x2b,
+            0xbd, 0xbe, 0x25, 0x68, 0x64, 0x01,
+        ]),
+        reverse_registrar: H160([
+            0xa5, 0x8e, 0x81, 0xfe, 0x9b, 0x61, 0xb5, 0xc3, 0xfe, 0x2a, 0xfd, 0x33, 0xcf, 0x30,
+            0x4c, 0x45, 0x4a, 0xbf, 0xc7, 0xcb,
+        ]),
+    };
+
+    /// The deployment on the Sepolia testnet.
+    pub const SEPOLIA: Self = Self {
+        registry: ENS_REGISTRY_ADDRESS,
+        controller: H160([
+            0xfe, 0xd6, 0xa9, 0x69, 0xaa, 0xa6, 0x0e, 0x49, 0x61, 0xfc, 0xd3, 0xeb, 0xf1, 0xa2,
+            0xe8, 0x91, 0x3a, 0xc6, 0x5b, 0x72,
+        ]),
+        public_resolver: H160([
+            0x8f, 0xad, 0xe6, 0x6b, 0x79, 0xcc, 0x9f, 0x70, 0x7a, 0xb2, 0x67, 0x99, 0x35, 0x44,
+            0x82, 0xeb, 0x93, 0xa5, 0xb7, 0xdd,
+        ]),
+        name_wrapper: H160([
+            0x06, 0x35, 0x51, 0x3f, 0x17, 0x9d, 0x50, 0xa2, 0x07, 0x75, 0x7e, 0x05, 0x75, 0x9c,
+            0xbd, 0x10, 0x6d, 0x7d, 0xfc, 0xe8,
+        ]),
+        reverse_registrar: H160([
+            0xa0, 0xa1, 0xab, 0xcd, 0xae, 0x1a, 0x2a, 0x4a, 0x2e, 0xf8, 0xe9, 0x11, 0x3f, 0xf0,
+            0xe0, 0x2d, 0xd8, 0x1d, 0xc0, 0xc6,
+        ]),
+    };
+
+    /// Returns the deployment on the chain with the given id, if known.
+    pub fn for_chain(chain_id: u64) -> Option<Self> {
+        match Chain::try_from(chain_id).ok()? {
+            Chain::Mainnet => Some(Self::MAINNET),
+            Chain::Sepolia => Some(Self::SEPOLIA),
+            _ => None,
+        }
+    }
+}
+
+/// The fuses of wrapped names, which permanently revoke permissions once burned.
+///
+/// The owner of a name controls the fuses in the lower 16 bits, which can only be burned once
+/// [`PARENT_CANNOT_CONTROL`] is burned. The parent of a name controls the other fuses.
+///
+/// See <https://docs.ens.domains/wrapper/fuses>.
+pub mod fuses {
+    /// The name cannot be unwrapped. Must be burned before any other owner-controlled fuse.
+    pub const CANNOT_UNWRAP: u32 = 1;
+    /// No further fuses can be burned.
+    pub const CANNOT_BURN_FUSES: u32 = 1 << 1;
+    /// The name cannot be transferred.
+    pub const CANNOT_TRANSFER: u32 = 1 << 2;
+    /// The resolver of the name cannot be changed.
+    pub const CANNOT_SET_RESOLVER: u32 = 1 << 3;
+    /// The TTL of the name cannot be changed.
+    pub const CANNOT_SET_TTL: u32 = 1 << 4;
+    /// No new subnames can be created.
+    pub const CANNOT_CREATE_SUBDOMAIN: u32 = 1 << 5;
+    /// The approved address of the name cannot be changed.
+    pub const CANNOT_APPROVE: u32 = 1 << 6;
+    /// The parent can no longer control the name. Burned by the parent.
+    pub const PARENT_CANNOT_CONTROL: u32 = 1 << 16;
+    /// Set for `.eth` second-level names.
+    pub const IS_DOT_ETH: u32 = 1 << 17;
+    /// The owner can extend the expiry of the name. Burned by the parent.
+    pub const CAN_EXTEND_EXPIRY: u32 = 1 << 18;
+
+    /// The mask of the fuses controlled by the owner of a name.
+    pub const OWNER_CONTROLLED: u32 = 0xffff;
+}
+
+/// Returns the hash of a single label of a name, e.g. of `vitalik` for `vitalik.eth`.
+pub fn labelhash(label: &str) -> H256 {
+    keccak256(label).into()
+}
@@ -0,0 +1,30 @@
+//! Bindings for the [`NameWrapper`](https://docs.ens.domains/wrapper/overview), which wraps names
+//! into ERC-1155 tokens whose permissions are restricted by [`fuses`](super::fuses).
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        NameWrapper,
+        r#"[
+            function ownerOf(uint256 id) external view returns (address)
+            function getData(uint256 id) external view returns (address owner, uint32 fuses, uint64 expiry)
+            function allFusesBurned(bytes32 node, uint32 fuseMask) external view returns (bool)
+            function canModifyName(bytes32 node, address addr) external view returns (bool)
+            function wrapETH2LD(string label, address wrappedOwner, uint16 ownerControlledFuses, address resolver) external returns (uint64 expiry)
+            function unwrapETH2LD(bytes32 labelhash, address registrant, address controller) external
+            function setFuses(bytes32 node, uint16 ownerControlledFuses) external returns (uint32 newFuses)
+            function setChildFuses(bytes32 parentNode, bytes32 labelhash, uint32 fuses, uint64 expiry) external
+            function setSubnodeOwner(bytes32 parentNode, string label, address owner, uint32 fuses, uint64 expiry) external returns (bytes32 node)
+            function setSubnodeRecord(bytes32 parentNode, string label, address owner, address resolver, uint64 ttl, uint32 fuses, uint64 expiry) external returns (bytes32 node)
+            function setResolver(bytes32 node, address resolver) external
+            function extendExpiry(bytes32 parentNode, bytes32 labelhash, uint64 expiry) external returns (uint64)
+            event NameWrapped(bytes32 indexed node, bytes name, address owner, uint32 fuses, uint64 expiry)
+            event NameUnwrapped(bytes32 indexed node, address owner)
+            event FusesSet(bytes32 indexed node, uint32 fuses)
+            event ExpiryExtended(bytes32 indexed node, uint64 expiry)
+        ]"#
+    );
+}
+pub use generated::*;
@@ -0,0 +1,28 @@
+//! Bindings for the [ENS registry](https://docs.ens.domains/registry/ens), which records the
+//! owner and resolver of every name.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        ENSRegistry,
+        r#"[
+            function owner(bytes32 node) external view returns (address)
+            function resolver(bytes32 node) external view returns (address)
+            function ttl(bytes32 node) external view returns (uint64)
+            function recordExists(bytes32 node) external view returns (bool)
+            function isApprovedForAll(address owner, address operator) external view returns (bool)
+            function setOwner(bytes32 node, address owner) external
+            function setResolver(bytes32 node, address resolver) external
+            function setTTL(bytes32 node, uint64 ttl) external
+            function setSubnodeOwner(bytes32 node, bytes32 label, address owner) external returns (bytes32)
+            function setSubnodeRecord(bytes32 node, bytes32 label, address owner, address resolver, uint64 ttl) external
+            function setApprovalForAll(address operator, bool approved) external
+            event Transfer(bytes32 indexed node, address owner)
+            event NewOwner(bytes32 indexed node, bytes32 indexed label, address owner)
+            event NewResolver(bytes32 indexed node, address resolver)
+        ]"#
+    );
+}
+pub use generated::*;
@@ -0,0 +1,112 @@
+//! Bindings for the [`PublicResolver`](https://docs.ens.domains/resolvers/public), which stores
+//! the records of names.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        PublicResolver,
+        r#"[
+            function supportsInterface(bytes4 interfaceID) external view returns (bool)
+            function addr(bytes32 node) external view returns (address)
+            function addr(bytes32 node, uint256 coinType) external view returns (bytes)
+            function text(bytes32 node, string key) external view returns (string)
+            function contenthash(bytes32 node) external view returns (bytes)
+            function name(bytes32 node) external view returns (string)
+            function setAddr(bytes32 node, address a) external
+            function setAddr(bytes32 node, uint256 coinType, bytes a) external
+            function setText(bytes32 node, string key, string value) external
+            function setContenthash(bytes32 node, bytes hash) external
+            function setName(bytes32 node, string newName) external
+            function multicall(bytes[] data) external returns (bytes[] results)
+            function multicallWithNodeCheck(bytes32 nodehash, bytes[] data) external returns (bytes[] results)
+            event AddrChanged(bytes32 indexed node, address a)
+            event AddressChanged(bytes32 indexed node, uint256 coinType, bytes newAddress)
+            event TextChanged(bytes32 indexed node, string indexed indexedKey, string key, string value)
+            event ContenthashChanged(bytes32 indexed node, bytes hash)
+        ]"#,
+        methods {
+            addr(bytes32) as addr;
+            addr(bytes32,uint256) as coin_addr;
+            setAddr(bytes32,address) as set_addr;
+            setAddr(bytes32,uint256,bytes) as set_coin_addr;
+        },
+    );
+}
+pub use generated::*;
+
+use super::coin_type;
+use ethers_core::{
+    abi::AbiEncode,
+    types::{Address, Bytes, H256, U256},
+};
+
+/// A record of a name that can be set on its resolver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Record {
+    /// The Ethereum address of the name.
+    Addr(Address),
+    /// The address of the name on another blockchain, in the binary format of its
+    /// [ENSIP-9](https://docs.ens.domains/ensip/9) coin type.
+    CoinAddr {
+        /// The SLIP-44 coin type, or the [ENSIP-11](https://docs.ens.domains/ensip/11) coin type
+        /// of an EVM chain.
+        coin_type: u64,
+        /// The address, e.g. the 20 address bytes for EVM chains.
+        address: Bytes,
+    },
+    /// A text record, e.g. `avatar`, `url` or `com.twitter`.
+    Text {
+        /// The key of the record.
+        key: String,
+        /// The value of the record, or an empty string to clear it.
+        value: String,
+    },
+    /// The [ENSIP-7](https://docs.ens.domains/ensip/7) content hash of the name, e.g. an IPFS
+    /// CID with its multicodec prefix.
+    Contenthash(Bytes),
+}
+
+impl Record {
+    /// Returns a text record.
+    pub fn text(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Text { key: key.into(), value: value.into() }
+    }
+
+    /// Returns the address record of `address` on the EVM chain with the given id.
+    ///
+    /// The address of the Ethereum mainnet is stored with the coin type `60`, the address of
+    /// other chains with the coin type `0x80000000 | chain_id` as specified in
+    /// [ENSIP-11](https://docs.ens.domains/ensip/11).
+    pub fn evm_addr(chain_id: u64, address: Address) -> Self {
+        Self::CoinAddr {
+            coin_type: coin_type::evm(chain_id),
+            address: address.as_bytes().to_vec().into(),
+        }
+    }
+
+    /// Returns the calldata setting this record of `node` on a resolver.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_contract::ens::resolver::Record;
+    /// use ethers_core::types::H256;
+    ///
+    /// let calldata = Record::text("url", "https://example.com").encode(H256::zero());
+    /// assert_eq!(&calldata[..4], &[0x10, 0xf1, 0x3a, 0x8c]); // setText(bytes32,string,string)
+    /// ```
+    pub fn encode(&self, node: H256) -> Bytes {
+        let node = node.0;
+        match self.clone() {
+            Self::Addr(a) => SetAddrCall { node, a }.encode(),
+            Self::CoinAddr { coin_type, address } => {
+                SetCoinAddrCall { node, coin_type: U256::from(coin_type), a: address }.encode()
+            }
+            Self::Text { key, value } => SetTextCall { node, key, value }.encode(),
+            Self::Contenthash(hash) => SetContenthashCall { node, hash }.encode(),
+        }
+        .into()
+    }
+}
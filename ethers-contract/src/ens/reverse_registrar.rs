@@ -0,0 +1,20 @@
+//! Bindings for the [`ReverseRegistrar`](https://docs.ens.domains/registry/reverse), which sets
+//! the primary name of an address.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        ReverseRegistrar,
+        r#"[
+            function node(address addr) external pure returns (bytes32)
+            function defaultResolver() external view returns (address)
+            function claim(address owner) external returns (bytes32)
+            function setName(string name) external returns (bytes32)
+            function setNameForAddr(address addr, address owner, address resolver, string name) external returns (bytes32)
+            event ReverseClaimed(address indexed addr, bytes32 indexed node)
+        ]"#
+    );
+}
+pub use generated::*;
@@ -28,6 +28,8 @@ pub use log::{decode_logs, EthLogDecode, LogMeta};
 
 pub mod stream;
 
+pub mod call_trace;
+
 #[cfg(feature = "abigen")]
 #[cfg_attr(docsrs, doc(cfg(feature = "abigen")))]
 mod multicall;
@@ -38,6 +40,14 @@ pub use multicall::{
     contract as multicall_contract, MulticallVersion,
 };
 
+#[cfg(feature = "abigen")]
+#[cfg_attr(docsrs, doc(cfg(feature = "abigen")))]
+pub mod tokens;
+
+#[cfg(all(feature = "abigen", feature = "providers"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "abigen", feature = "providers"))))]
+pub mod ens;
+
 #[cfg(feature = "abigen")]
 #[cfg_attr(docsrs, doc(cfg(feature = "abigen")))]
 pub use ethers_contract_abigen::{
@@ -84,6 +94,8 @@ if_providers! {
     mod call;
     pub use call::{ContractCall, ContractError, FunctionCall};
 
+    pub mod signature;
+
     mod factory;
     pub use factory::{ContractDeployer, ContractDeploymentTx, ContractFactory, DeploymentTxFactory};
 
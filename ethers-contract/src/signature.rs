@@ -0,0 +1,295 @@
+//! Verification of signatures of EOAs and smart contract accounts.
+//!
+//! [`verify_signature`] accepts:
+//! - ECDSA signatures of EOAs
+//! - [EIP-1271](https://eips.ethereum.org/EIPS/eip-1271) signatures of deployed smart contract
+//!   accounts, which are checked with `isValidSignature`
+//! - [ERC-6492](https://eips.ethereum.org/EIPS/eip-6492) signatures of smart contract accounts that
+//!   are not deployed yet, which wrap the EIP-1271 signature together with the factory call that
+//!   deploys the account
+
+use crate::ContractError;
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::{Address, BlockId, Bytes, Signature, TransactionRequest, H256},
+};
+use ethers_providers::Middleware;
+
+/// The magic value that `isValidSignature` returns for valid signatures, which is its selector.
+pub const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// The suffix of ERC-6492 signatures.
+pub const ERC6492_MAGIC_SUFFIX: [u8; 32] = [
+    0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92,
+    0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92,
+];
+
+/// Init code of a contract that is never deployed, but run with `eth_call` to verify ERC-6492
+/// signatures of undeployed accounts.
+///
+/// It calls the factory to deploy the account, ignoring failures, then calls `isValidSignature` on
+/// the account and returns its result, or reverts with it. The two calls are appended to the
+/// code, each encoded as the address and the length of the calldata as 32 byte words followed by
+/// the calldata.
+const DEPLOYLESS_VALIDATOR: [u8; 69] = [
+    0x60, 0x45, 0x38, 0x03, 0x60, 0x45, 0x60, 0x00, 0x39, 0x60, 0x00, 0x60, 0x00, 0x60, 0x20, 0x51,
+    0x60, 0x40, 0x60, 0x00, 0x60, 0x00, 0x51, 0x5a, 0xf1, 0x50, 0x60, 0x20, 0x51, 0x60, 0x40, 0x01,
+    0x60, 0x00, 0x60, 0x00, 0x82, 0x60, 0x20, 0x01, 0x51, 0x83, 0x60, 0x40, 0x01, 0x60, 0x00, 0x85,
+    0x51, 0x5a, 0xf1, 0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x60, 0x40, 0x57, 0x3d, 0x60, 0x00, 0xfd,
+    0x5b, 0x3d, 0x60, 0x00, 0xf3,
+];
+
+/// An [ERC-6492](https://eips.ethereum.org/EIPS/eip-6492) signature of an account that may not be
+/// deployed yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Erc6492Signature {
+    /// The factory that deploys the account.
+    pub factory: Address,
+    /// The calldata of the factory call that deploys the account.
+    pub factory_calldata: Bytes,
+    /// The EIP-1271 signature of the account.
+    pub signature: Bytes,
+}
+
+impl Erc6492Signature {
+    /// Decodes an ERC-6492 signature, returns `None` if `signature` is not wrapped.
+    pub fn decode(signature: &[u8]) -> Option<Self> {
+        let wrapped = signature.strip_suffix(&ERC6492_MAGIC_SUFFIX)?;
+        let mut tokens =
+            abi::decode(&[ParamType::Address, ParamType::Bytes, ParamType::Bytes], wrapped)
+                .ok()?
+                .into_iter();
+        match (tokens.next()?, tokens.next()?, tokens.next()?) {
+            (Token::Address(factory), Token::Bytes(calldata), Token::Bytes(signature)) => {
+                Some(Self {
+                    factory,
+                    factory_calldata: calldata.into(),
+                    signature: signature.into(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Encodes the signature as `abi.encode(factory, factoryCalldata, signature) ++ magicSuffix`.
+    pub fn encode(&self) -> Bytes {
+        let mut encoded = abi::encode(&[
+            Token::Address(self.factory),
+            Token::Bytes(self.factory_calldata.to_vec()),
+            Token::Bytes(self.signature.to_vec()),
+        ]);
+        encoded.extend_from_slice(&ERC6492_MAGIC_SUFFIX);
+        encoded.into()
+    }
+}
+
+/// Verifies that `signature` is a valid signature of `hash` by `signer` at `block`.
+///
+/// 65 byte signatures are first checked as ECDSA signatures. Otherwise, if `signer` is a contract,
+/// the signature is checked with its EIP-1271 `isValidSignature` function. ERC-6492 signatures of
+/// accounts that are not deployed yet are checked with an `eth_call` that deploys the account and
+/// then calls `isValidSignature`, without sending a transaction.
+///
+/// Returns `false` if the signature is invalid, or if `isValidSignature` reverts, and an error if
+/// a request to the node fails.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_contract::signature::verify_signature;
+/// use ethers_core::{types::Address, utils::hash_message};
+/// use ethers_providers::{Http, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo(signer: Address, signature: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let hash = hash_message("Sign in");
+/// let valid = verify_signature(&provider, signer, hash, &signature, None).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn verify_signature<M: Middleware>(
+    client: &M,
+    signer: Address,
+    hash: H256,
+    signature: &[u8],
+    block: Option<BlockId>,
+) -> Result<bool, ContractError<M>> {
+    if let Some(wrapped) = Erc6492Signature::decode(signature) {
+        let code =
+            client.get_code(signer, block).await.map_err(ContractError::from_middleware_error)?;
+        return if code.is_empty() {
+            verify_undeployed(client, signer, hash, &wrapped, block).await
+        } else {
+            verify_eip1271(client, signer, hash, &wrapped.signature, block).await
+        }
+    }
+
+    if let Ok(ecdsa) = Signature::try_from(signature) {
+        if ecdsa.verify(hash, signer).is_ok() {
+            return Ok(true)
+        }
+    }
+
+    let code =
+        client.get_code(signer, block).await.map_err(ContractError::from_middleware_error)?;
+    if code.is_empty() {
+        return Ok(false)
+    }
+    verify_eip1271(client, signer, hash, signature, block).await
+}
+
+/// Calls `isValidSignature(hash, signature)` on `signer`
+async fn verify_eip1271<M: Middleware>(
+    client: &M,
+    signer: Address,
+    hash: H256,
+    signature: &[u8],
+    block: Option<BlockId>,
+) -> Result<bool, ContractError<M>> {
+    let tx =
+        TransactionRequest::new().to(signer).data(is_valid_signature_calldata(hash, signature));
+    match client.call(&tx.into(), block).await.map_err(ContractError::from_middleware_error) {
+        Ok(output) => Ok(output.starts_with(&EIP1271_MAGIC_VALUE)),
+        Err(ContractError::Revert(_)) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Runs the [`DEPLOYLESS_VALIDATOR`] with `eth_call`
+async fn verify_undeployed<M: Middleware>(
+    client: &M,
+    signer: Address,
+    hash: H256,
+    signature: &Erc6492Signature,
+    block: Option<BlockId>,
+) -> Result<bool, ContractError<M>> {
+    let mut code = DEPLOYLESS_VALIDATOR.to_vec();
+    append_call(&mut code, signature.factory, &signature.factory_calldata);
+    append_call(&mut code, signer, &is_valid_signature_calldata(hash, &signature.signature));
+
+    let tx = TransactionRequest::new().data(code);
+    match client.call(&tx.into(), block).await.map_err(ContractError::from_middleware_error) {
+        Ok(output) => Ok(output.starts_with(&EIP1271_MAGIC_VALUE)),
+        Err(ContractError::Revert(_)) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn is_valid_signature_calldata(hash: H256, signature: &[u8]) -> Vec<u8> {
+    let args = abi::encode(&[Token::FixedBytes(hash.0.to_vec()), Token::Bytes(signature.to_vec())]);
+    [&EIP1271_MAGIC_VALUE[..], &args].concat()
+}
+
+/// Appends a call in the format that the [`DEPLOYLESS_VALIDATOR`] expects
+fn append_call(code: &mut Vec<u8>, to: Address, calldata: &[u8]) {
+    code.extend(abi::encode(&[Token::Address(to), Token::Uint(calldata.len().into())]));
+    code.extend_from_slice(calldata);
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use ethers_core::{
+        k256::ecdsa::SigningKey,
+        types::{transaction::eip2718::TypedTransaction, BlockNumber},
+        utils::{hash_message, secret_key_to_address},
+    };
+    use ethers_providers::Provider;
+
+    #[test]
+    fn can_encode_erc6492_signatures() {
+        let signature = Erc6492Signature {
+            factory: Address::repeat_byte(1),
+            factory_calldata: vec![1, 2, 3].into(),
+            signature: vec![4; 65].into(),
+        };
+        let encoded = signature.encode();
+        assert!(encoded.ends_with(&ERC6492_MAGIC_SUFFIX));
+        assert_eq!(Erc6492Signature::decode(&encoded), Some(signature));
+        assert_eq!(Erc6492Signature::decode(&[4; 65]), None);
+    }
+
+    #[tokio::test]
+    async fn verifies_ecdsa_signatures() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let hash = hash_message("hello");
+        let (signature, recovery_id) = key.sign_prehash_recoverable(hash.as_bytes()).unwrap();
+        let mut signature = signature.to_bytes().to_vec();
+        signature.push(27 + recovery_id.to_byte());
+
+        // no requests are sent for valid ECDSA signatures
+        let (provider, _) = Provider::mocked();
+        let signer = secret_key_to_address(&key);
+        assert!(verify_signature(&provider, signer, hash, &signature, None).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn verifies_eip1271_signatures() {
+        let (provider, mock) = Provider::mocked();
+        let account = Address::repeat_byte(0xaa);
+        let hash = H256::repeat_byte(1);
+        let signature = vec![7; 100];
+        let latest = BlockId::from(BlockNumber::Latest);
+
+        // responses are popped from the back
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::FixedBytes(
+            EIP1271_MAGIC_VALUE.to_vec(),
+        )])))
+        .unwrap();
+        mock.push::<Bytes, _>(Bytes::from(vec![0x60])).unwrap();
+        assert!(verify_signature(&provider, account, hash, &signature, None).await.unwrap());
+        mock.assert_request("eth_getCode", (account, latest)).unwrap();
+        mock.assert_request(
+            "eth_call",
+            (
+                TypedTransaction::Legacy(
+                    TransactionRequest::new()
+                        .to(account)
+                        .data(is_valid_signature_calldata(hash, &signature)),
+                ),
+                latest,
+            ),
+        )
+        .unwrap();
+
+        mock.push::<Bytes, _>(Bytes::from(vec![0u8; 32])).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(vec![0x60])).unwrap();
+        assert!(!verify_signature(&provider, account, hash, &signature, None).await.unwrap());
+
+        // accounts without code
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        assert!(!verify_signature(&provider, account, hash, &signature, None).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn verifies_undeployed_erc6492_signatures() {
+        let (provider, mock) = Provider::mocked();
+        let account = Address::repeat_byte(0xaa);
+        let hash = H256::repeat_byte(1);
+        let signature = Erc6492Signature {
+            factory: Address::repeat_byte(0xfa),
+            factory_calldata: vec![1, 2, 3].into(),
+            signature: vec![7; 100].into(),
+        };
+
+        mock.push::<Bytes, _>(Bytes::from(EIP1271_MAGIC_VALUE.to_vec())).unwrap();
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        assert!(verify_signature(&provider, account, hash, &signature.encode(), None)
+            .await
+            .unwrap());
+
+        let mut code = DEPLOYLESS_VALIDATOR.to_vec();
+        code.extend(abi::encode(&[Token::Address(signature.factory), Token::Uint(3.into())]));
+        code.extend([1, 2, 3]);
+        code.extend(abi::encode(&[Token::Address(account), Token::Uint(228.into())]));
+        code.extend(is_valid_signature_calldata(hash, &signature.signature));
+        let latest = BlockId::from(BlockNumber::Latest);
+        mock.assert_request("eth_getCode", (account, latest)).unwrap();
+        mock.assert_request(
+            "eth_call",
+            (TypedTransaction::Legacy(TransactionRequest::new().data(code)), latest),
+        )
+        .unwrap();
+    }
+}
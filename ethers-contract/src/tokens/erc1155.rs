@@ -0,0 +1,126 @@
+//! [ERC-1155](https://eips.ethereum.org/EIPS/eip-1155) bindings, including the metadata URI
+//! extension.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        IERC1155,
+        r#"[
+            function uri(uint256 id) external view returns (string)
+            function balanceOf(address account, uint256 id) external view returns (uint256)
+            function balanceOfBatch(address[] accounts, uint256[] ids) external view returns (uint256[])
+            function isApprovedForAll(address account, address operator) external view returns (bool)
+            function setApprovalForAll(address operator, bool approved) external
+            function safeTransferFrom(address from, address to, uint256 id, uint256 amount, bytes data) external
+            function safeBatchTransferFrom(address from, address to, uint256[] ids, uint256[] amounts, bytes data) external
+            function supportsInterface(bytes4 interfaceId) external view returns (bool)
+            event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)
+            event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)
+            event ApprovalForAll(address indexed account, address indexed operator, bool approved)
+            event URI(string value, uint256 indexed id)
+        ]"#
+    );
+}
+pub use generated::*;
+
+use ethers_core::types::{Address, U256};
+
+/// The [ERC-165](https://eips.ethereum.org/EIPS/eip-165) interface id of ERC-1155.
+pub const ERC1155_INTERFACE_ID: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+
+/// Replaces the `{id}` placeholder of an ERC-1155 metadata URI with the hex encoded token id, as
+/// defined by the standard.
+///
+/// # Example
+///
+/// ```
+/// use ethers_contract::tokens::erc1155::expand_uri;
+///
+/// let uri = expand_uri("https://token-cdn-domain/{id}.json", 314592u64.into());
+/// assert_eq!(
+///     uri,
+///     "https://token-cdn-domain/000000000000000000000000000000000000000000000000000000000004cce0.json"
+/// );
+/// ```
+pub fn expand_uri(uri: &str, id: U256) -> String {
+    uri.replace("{id}", &format!("{id:064x}"))
+}
+
+impl IERC1155Events {
+    /// Returns the `(from, to, id, value)` transfers of a `TransferSingle` or `TransferBatch`
+    /// event, or nothing for other events.
+    pub fn transfers(&self) -> Vec<(Address, Address, U256, U256)> {
+        match self {
+            IERC1155Events::TransferSingleFilter(e) => vec![(e.from, e.to, e.id, e.value)],
+            IERC1155Events::TransferBatchFilter(e) => {
+                e.ids.iter().zip(&e.values).map(|(id, value)| (e.from, e.to, *id, *value)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+if_providers! {
+    use crate::{ContractError, EthEvent, Event};
+    use ethers_providers::Middleware;
+    use std::sync::Arc;
+
+    impl<M: Middleware> IERC1155<M> {
+        /// Returns the metadata URI of the token `id` with the `{id}` placeholder replaced.
+        pub async fn token_uri(&self, id: U256) -> Result<String, ContractError<M>> {
+            let uri = self.uri(id).call().await?;
+            Ok(expand_uri(&uri, id))
+        }
+
+        /// Returns the balances of `owner` for all `ids` in a single call.
+        pub async fn balances_of(
+            &self,
+            owner: Address,
+            ids: Vec<U256>,
+        ) -> Result<Vec<U256>, ContractError<M>> {
+            self.balance_of_batch(vec![owner; ids.len()], ids).call().await
+        }
+
+        /// Returns an event filter for the `TransferSingle` and `TransferBatch` events of
+        /// transfers sent by `from`.
+        pub fn transfers_from(&self, from: Address) -> Event<Arc<M>, M, IERC1155Events> {
+            self.transfer_events().topic2(from)
+        }
+
+        /// Returns an event filter for the `TransferSingle` and `TransferBatch` events of
+        /// transfers received by `to`.
+        pub fn transfers_to(&self, to: Address) -> Event<Arc<M>, M, IERC1155Events> {
+            self.transfer_events().topic3(to)
+        }
+
+        fn transfer_events(&self) -> Event<Arc<M>, M, IERC1155Events> {
+            self.events()
+                .topic0(vec![TransferSingleFilter::signature(), TransferBatchFilter::signature()])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_batch_transfers() {
+        let event = IERC1155Events::TransferBatchFilter(TransferBatchFilter {
+            operator: Address::zero(),
+            from: Address::repeat_byte(1),
+            to: Address::repeat_byte(2),
+            ids: vec![1u64.into(), 2u64.into()],
+            values: vec![10u64.into(), 20u64.into()],
+        });
+        assert_eq!(
+            event.transfers(),
+            vec![
+                (Address::repeat_byte(1), Address::repeat_byte(2), 1u64.into(), 10u64.into()),
+                (Address::repeat_byte(1), Address::repeat_byte(2), 2u64.into(), 20u64.into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! [ERC-20](https://eips.ethereum.org/EIPS/eip-20) bindings, including the
+//! [EIP-2612](https://eips.ethereum.org/EIPS/eip-2612) `permit` extension.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        IERC20,
+        r#"[
+            function name() external view returns (string)
+            function symbol() external view returns (string)
+            function decimals() external view returns (uint8)
+            function totalSupply() external view returns (uint256)
+            function balanceOf(address owner) external view returns (uint256)
+            function allowance(address owner, address spender) external view returns (uint256)
+            function transfer(address to, uint256 amount) external returns (bool)
+            function approve(address spender, uint256 amount) external returns (bool)
+            function transferFrom(address from, address to, uint256 amount) external returns (bool)
+            function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external
+            function nonces(address owner) external view returns (uint256)
+            function DOMAIN_SEPARATOR() external view returns (bytes32)
+            event Transfer(address indexed from, address indexed to, uint256 value)
+            event Approval(address indexed owner, address indexed spender, uint256 value)
+        ]"#
+    );
+}
+pub use generated::*;
+
+if_providers! {
+    use super::{permit::Permit, TokenAmount};
+    use crate::{ContractCall, ContractError, Event};
+    use ethers_core::types::{transaction::eip712::EIP712Domain, Address, Signature, U256};
+    use ethers_providers::Middleware;
+    use std::sync::Arc;
+
+    impl<M: Middleware> IERC20<M> {
+        /// Returns the token balance of `owner` with the token's decimals.
+        pub async fn balance_of_amount(&self, owner: Address) -> Result<TokenAmount, ContractError<M>> {
+            let decimals = self.decimals().call().await?;
+            let balance = self.balance_of(owner).call().await?;
+            Ok(TokenAmount::new(balance, decimals))
+        }
+
+        /// Returns the amount of tokens of `owner` that `spender` is allowed to spend, with the
+        /// token's decimals.
+        pub async fn allowance_amount(
+            &self,
+            owner: Address,
+            spender: Address,
+        ) -> Result<TokenAmount, ContractError<M>> {
+            let decimals = self.decimals().call().await?;
+            let allowance = self.allowance(owner, spender).call().await?;
+            Ok(TokenAmount::new(allowance, decimals))
+        }
+
+        /// Returns an `approve` call that allows `spender` to spend `amount` tokens of `owner`, or
+        /// `None` if the current allowance is already sufficient.
+        pub async fn approve_if_needed(
+            &self,
+            owner: Address,
+            spender: Address,
+            amount: impl Into<U256>,
+        ) -> Result<Option<ContractCall<M, bool>>, ContractError<M>> {
+            let amount = amount.into();
+            let allowance = self.allowance(owner, spender).call().await?;
+            if allowance >= amount {
+                return Ok(None)
+            }
+            Ok(Some(self.approve(spender, amount).from(owner)))
+        }
+
+        /// Returns an `approve` call that allows `spender` to spend all tokens.
+        pub fn approve_max(&self, spender: Address) -> ContractCall<M, bool> {
+            self.approve(spender, U256::MAX)
+        }
+
+        /// Returns a `Transfer` event filter for transfers sent by `from`.
+        pub fn transfers_from(&self, from: Address) -> Event<Arc<M>, M, TransferFilter> {
+            self.transfer_filter().topic1(from)
+        }
+
+        /// Returns a `Transfer` event filter for transfers received by `to`.
+        pub fn transfers_to(&self, to: Address) -> Event<Arc<M>, M, TransferFilter> {
+            self.transfer_filter().topic2(to)
+        }
+
+        /// Returns an `Approval` event filter for approvals given by `owner`.
+        pub fn approvals_of(&self, owner: Address) -> Event<Arc<M>, M, ApprovalFilter> {
+            self.approval_filter().topic1(owner)
+        }
+
+        /// Fetches the EIP-2612 [`Permit`] that allows `spender` to spend `value` tokens of
+        /// `owner` until `deadline`, ready to be signed with `Signer::sign_typed_data`.
+        ///
+        /// The token's `name` and the current nonce of `owner` are fetched from the token. The
+        /// domain version is chosen by comparing the token's `DOMAIN_SEPARATOR` for the versions
+        /// `"1"` and `"2"`, and defaults to `"1"`.
+        pub async fn permit_data(
+            &self,
+            owner: Address,
+            spender: Address,
+            value: impl Into<U256>,
+            deadline: impl Into<U256>,
+        ) -> Result<Permit, ContractError<M>> {
+            let name = self.name().call().await?;
+            let nonce = self.nonces(owner).call().await?;
+            let chain_id =
+                self.client_ref().get_chainid().await.map_err(ContractError::from_middleware_error)?;
+
+            let domain = |version: &str| EIP712Domain {
+                name: Some(name.clone()),
+                version: Some(version.to_string()),
+                chain_id: Some(chain_id),
+                verifying_contract: Some(self.address()),
+                salt: None,
+            };
+            let separator = self.domain_separator().call().await.ok();
+            let domain = ["1", "2"]
+                .into_iter()
+                .map(domain)
+                .find(|domain| Some(domain.separator()) == separator)
+                .unwrap_or_else(|| domain("1"));
+
+            Ok(Permit {
+                domain,
+                owner,
+                spender,
+                value: value.into(),
+                nonce,
+                deadline: deadline.into(),
+            })
+        }
+
+        /// Returns the `permit` call for a signed [`Permit`].
+        ///
+        /// Fails if the recovery id of the signature does not fit in a byte, e.g. for an EIP-155
+        /// signature.
+        pub fn permit_with_signature(
+            &self,
+            permit: &Permit,
+            signature: &Signature,
+        ) -> Result<ContractCall<M, ()>, ContractError<M>> {
+            let v = u8::try_from(signature.v).map_err(|_| {
+                ethers_core::abi::Error::Other(
+                    format!("signature recovery id {} does not fit in a byte", signature.v).into(),
+                )
+            })?;
+            let mut r = [0u8; 32];
+            let mut s = [0u8; 32];
+            signature.r.to_big_endian(&mut r);
+            signature.s.to_big_endian(&mut s);
+            Ok(self.permit(permit.owner, permit.spender, permit.value, permit.deadline, v, r, s))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "providers", not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use ethers_core::{
+        abi::AbiEncode,
+        types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, U256},
+    };
+    use ethers_providers::Provider;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn can_fetch_balance_amount() {
+        let (provider, mock) = Provider::mocked();
+        let token = IERC20::new(Address::repeat_byte(1), Arc::new(provider));
+        let owner = Address::repeat_byte(2);
+
+        // responses are popped in reverse order
+        mock.push::<String, _>(U256::from(1_500_000u64).encode_hex()).unwrap();
+        mock.push::<String, _>(U256::from(6u64).encode_hex()).unwrap();
+
+        let balance = token.balance_of_amount(owner).await.unwrap();
+        assert_eq!(balance.to_string(), "1.500000");
+
+        let tx: TypedTransaction = token.decimals().tx;
+        mock.assert_request("eth_call", (tx, BlockNumber::Latest)).unwrap();
+        let tx: TypedTransaction = token.balance_of(owner).tx;
+        mock.assert_request("eth_call", (tx, BlockNumber::Latest)).unwrap();
+    }
+
+    #[test]
+    fn rejects_wide_recovery_id() {
+        let (provider, _mock) = Provider::mocked();
+        let token = IERC20::new(Address::repeat_byte(1), Arc::new(provider));
+        let permit = Permit {
+            domain: Default::default(),
+            owner: Address::repeat_byte(2),
+            spender: Address::repeat_byte(3),
+            value: 1.into(),
+            nonce: 0.into(),
+            deadline: U256::MAX,
+        };
+
+        let mut signature = Signature { r: 1.into(), s: 2.into(), v: 27 };
+        assert!(token.permit_with_signature(&permit, &signature).is_ok());
+        // EIP-155 recovery ids are not valid for permits
+        signature.v = 37 + 2 * 1_000_000;
+        assert!(token.permit_with_signature(&permit, &signature).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! [ERC-4626](https://eips.ethereum.org/EIPS/eip-4626) tokenized vault bindings.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        IERC4626,
+        r#"[
+            function name() external view returns (string)
+            function symbol() external view returns (string)
+            function decimals() external view returns (uint8)
+            function totalSupply() external view returns (uint256)
+            function balanceOf(address owner) external view returns (uint256)
+            function allowance(address owner, address spender) external view returns (uint256)
+            function transfer(address to, uint256 amount) external returns (bool)
+            function approve(address spender, uint256 amount) external returns (bool)
+            function transferFrom(address from, address to, uint256 amount) external returns (bool)
+            function asset() external view returns (address)
+            function totalAssets() external view returns (uint256)
+            function convertToShares(uint256 assets) external view returns (uint256)
+            function convertToAssets(uint256 shares) external view returns (uint256)
+            function maxDeposit(address receiver) external view returns (uint256)
+            function previewDeposit(uint256 assets) external view returns (uint256)
+            function deposit(uint256 assets, address receiver) external returns (uint256)
+            function maxMint(address receiver) external view returns (uint256)
+            function previewMint(uint256 shares) external view returns (uint256)
+            function mint(uint256 shares, address receiver) external returns (uint256)
+            function maxWithdraw(address owner) external view returns (uint256)
+            function previewWithdraw(uint256 assets) external view returns (uint256)
+            function withdraw(uint256 assets, address receiver, address owner) external returns (uint256)
+            function maxRedeem(address owner) external view returns (uint256)
+            function previewRedeem(uint256 shares) external view returns (uint256)
+            function redeem(uint256 shares, address receiver, address owner) external returns (uint256)
+            event Transfer(address indexed from, address indexed to, uint256 value)
+            event Approval(address indexed owner, address indexed spender, uint256 value)
+            event Deposit(address indexed sender, address indexed owner, uint256 assets, uint256 shares)
+            event Withdraw(address indexed sender, address indexed receiver, address indexed owner, uint256 assets, uint256 shares)
+        ]"#
+    );
+}
+pub use generated::*;
+
+if_providers! {
+    use super::{erc20::IERC20, TokenAmount};
+    use crate::ContractError;
+    use ethers_core::types::{Address, U256};
+    use ethers_providers::Middleware;
+
+    impl<M: Middleware> IERC4626<M> {
+        /// Returns the bindings of the vault's underlying asset token.
+        pub async fn asset_token(&self) -> Result<IERC20<M>, ContractError<M>> {
+            let asset = self.asset().call().await?;
+            Ok(IERC20::new(asset, self.client()))
+        }
+
+        /// Returns the bindings of the vault's share token, which is the vault itself.
+        pub fn share_token(&self) -> IERC20<M> {
+            IERC20::new(self.address(), self.client())
+        }
+
+        /// Converts `shares` to the amount of underlying assets, with the asset's decimals.
+        pub async fn shares_to_assets(&self, shares: U256) -> Result<TokenAmount, ContractError<M>> {
+            let decimals = self.asset_token().await?.decimals().call().await?;
+            let assets = self.convert_to_assets(shares).call().await?;
+            Ok(TokenAmount::new(assets, decimals))
+        }
+
+        /// Converts an amount of underlying `assets` to shares, with the vault's decimals.
+        pub async fn assets_to_shares(&self, assets: U256) -> Result<TokenAmount, ContractError<M>> {
+            let decimals = self.decimals().call().await?;
+            let shares = self.convert_to_shares(assets).call().await?;
+            Ok(TokenAmount::new(shares, decimals))
+        }
+
+        /// Returns the amount of underlying assets that the shares of `owner` are worth, with the
+        /// asset's decimals.
+        pub async fn assets_of(&self, owner: Address) -> Result<TokenAmount, ContractError<M>> {
+            let shares = self.balance_of(owner).call().await?;
+            self.shares_to_assets(shares).await
+        }
+
+        /// Returns the amount of underlying assets that one whole share is worth, with the asset's
+        /// decimals.
+        pub async fn share_price(&self) -> Result<TokenAmount, ContractError<M>> {
+            let decimals = self.decimals().call().await?;
+            let one = U256::from(10).checked_pow(decimals.into()).ok_or_else(|| {
+                ethers_core::abi::Error::Other(
+                    format!("share decimals {decimals} do not fit in a uint256").into(),
+                )
+            })?;
+            self.shares_to_assets(one).await
+        }
+    }
+}
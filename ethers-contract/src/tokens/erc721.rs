@@ -0,0 +1,109 @@
+//! [ERC-721](https://eips.ethereum.org/EIPS/eip-721) bindings, including the metadata extension.
+
+#[allow(missing_docs)]
+mod generated {
+    use ethers_contract_derive::abigen;
+
+    abigen!(
+        IERC721,
+        r#"[
+            function name() external view returns (string)
+            function symbol() external view returns (string)
+            function tokenURI(uint256 tokenId) external view returns (string)
+            function balanceOf(address owner) external view returns (uint256)
+            function ownerOf(uint256 tokenId) external view returns (address)
+            function getApproved(uint256 tokenId) external view returns (address)
+            function isApprovedForAll(address owner, address operator) external view returns (bool)
+            function approve(address to, uint256 tokenId) external
+            function setApprovalForAll(address operator, bool approved) external
+            function transferFrom(address from, address to, uint256 tokenId) external
+            function safeTransferFrom(address from, address to, uint256 tokenId) external
+            function safeTransferFrom(address from, address to, uint256 tokenId, bytes data) external
+            function supportsInterface(bytes4 interfaceId) external view returns (bool)
+            event Transfer(address indexed from, address indexed to, uint256 indexed tokenId)
+            event Approval(address indexed owner, address indexed approved, uint256 indexed tokenId)
+            event ApprovalForAll(address indexed owner, address indexed operator, bool approved)
+        ]"#
+    );
+}
+pub use generated::*;
+
+/// The [ERC-165](https://eips.ethereum.org/EIPS/eip-165) interface id of ERC-721.
+pub const ERC721_INTERFACE_ID: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+
+/// The ERC-165 interface id of the ERC-721 metadata extension.
+pub const ERC721_METADATA_INTERFACE_ID: [u8; 4] = [0x5b, 0x5e, 0x13, 0x9f];
+
+if_providers! {
+    use crate::{ContractError, Event};
+    use ethers_core::types::{Address, U256};
+    use ethers_providers::Middleware;
+    use std::sync::Arc;
+
+    impl<M: Middleware> IERC721<M> {
+        /// Returns whether the contract reports support for ERC-721 through ERC-165.
+        pub async fn supports_erc721(&self) -> Result<bool, ContractError<M>> {
+            self.supports_interface(ERC721_INTERFACE_ID).call().await
+        }
+
+        /// Returns whether `operator` may transfer the token `token_id`, either as its owner, as
+        /// the approved address of the token or as an approved operator of the owner.
+        pub async fn is_approved_or_owner(
+            &self,
+            operator: Address,
+            token_id: U256,
+        ) -> Result<bool, ContractError<M>> {
+            let owner = self.owner_of(token_id).call().await?;
+            if owner == operator || self.get_approved(token_id).call().await? == operator {
+                return Ok(true)
+            }
+            self.is_approved_for_all(owner, operator).call().await
+        }
+
+        /// Returns a `Transfer` event filter for transfers sent by `from`.
+        pub fn transfers_from(&self, from: Address) -> Event<Arc<M>, M, TransferFilter> {
+            self.transfer_filter().topic1(from)
+        }
+
+        /// Returns a `Transfer` event filter for transfers received by `to`.
+        pub fn transfers_to(&self, to: Address) -> Event<Arc<M>, M, TransferFilter> {
+            self.transfer_filter().topic2(to)
+        }
+
+        /// Returns a `Transfer` event filter for transfers of the token `token_id`.
+        pub fn transfers_of(&self, token_id: U256) -> Event<Arc<M>, M, TransferFilter> {
+            self.transfer_filter().topic3(token_id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::utils::id;
+
+    #[test]
+    fn interface_ids() {
+        let xor = |sigs: &[&str]| {
+            sigs.iter().map(id).fold([0u8; 4], |mut acc, sel| {
+                acc.iter_mut().zip(sel).for_each(|(a, b)| *a ^= b);
+                acc
+            })
+        };
+        assert_eq!(
+            xor(&[
+                "balanceOf(address)",
+                "ownerOf(uint256)",
+                "safeTransferFrom(address,address,uint256,bytes)",
+                "safeTransferFrom(address,address,uint256)",
+                "transferFrom(address,address,uint256)",
+                "approve(address,uint256)",
+                "setApprovalForAll(address,bool)",
+                "getApproved(uint256)",
+                "isApprovedForAll(address,address)",
+            ]),
+            ERC721_INTERFACE_ID
+        );
+        assert_eq!(xor(&["name()", "symbol()", "tokenURI(uint256)"]), ERC721_METADATA_INTERFACE_ID);
+    }
+}
@@ -0,0 +1,123 @@
+//! Typed bindings and helpers for the common token standards.
+//!
+//! The bindings are generated with [`abigen`](crate::abigen) from the interfaces of the standards,
+//! so they can be used with any compliant token without regenerating them:
+//!
+//! - [`erc20`]: fungible tokens, including [EIP-2612](https://eips.ethereum.org/EIPS/eip-2612)
+//!   `permit`
+//! - [`erc721`]: non-fungible tokens
+//! - [`erc1155`]: multi tokens
+//! - [`erc4626`]: tokenized vaults
+//! - [`permit`]: EIP-712 messages for EIP-2612 permits and Uniswap's [Permit2](https://github.com/Uniswap/permit2)
+//!   signature transfers, which can be signed with any `Signer`
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ethers_contract::tokens::erc20::IERC20;
+//! use ethers_core::types::Address;
+//! use ethers_providers::{Http, Provider};
+//! use std::{convert::TryFrom, sync::Arc};
+//!
+//! # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+//! let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545")?);
+//! let token = IERC20::new(Address::random(), provider);
+//!
+//! let balance = token.balance_of_amount(Address::random()).await?;
+//! println!("balance: {balance}");
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod erc1155;
+pub mod erc20;
+pub mod erc4626;
+pub mod erc721;
+pub mod permit;
+
+use ethers_core::{
+    abi::ethereum_types::FromDecStrErr,
+    types::U256,
+    utils::{format_units, parse_units, ConversionError, ParseUnits},
+};
+use std::fmt;
+
+/// An amount of a token together with the token's decimals.
+///
+/// The [`Display`](fmt::Display) implementation formats the amount in whole tokens, e.g. `1.5`
+/// for `1500000` with 6 decimals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TokenAmount {
+    /// The raw amount in the token's smallest unit.
+    pub amount: U256,
+    /// The decimals of the token.
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    /// Creates a new amount from the raw `amount` in the token's smallest unit.
+    pub fn new(amount: impl Into<U256>, decimals: u8) -> Self {
+        Self { amount: amount.into(), decimals }
+    }
+
+    /// Parses an amount in whole tokens, e.g. `"1.5"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_contract::tokens::TokenAmount;
+    /// use ethers_core::types::U256;
+    ///
+    /// let amount = TokenAmount::parse("1.5", 6).unwrap();
+    /// assert_eq!(amount.amount, U256::from(1_500_000u64));
+    /// assert_eq!(amount.to_string(), "1.500000");
+    /// ```
+    pub fn parse(amount: &str, decimals: u8) -> Result<Self, ConversionError> {
+        match parse_units(amount, decimals as u32)? {
+            ParseUnits::U256(amount) => Ok(Self { amount, decimals }),
+            ParseUnits::I256(_) => {
+                Err(ConversionError::FromDecStrError(FromDecStrErr::InvalidCharacter))
+            }
+        }
+    }
+
+    /// Formats the amount in whole tokens.
+    pub fn format(&self) -> Result<String, ConversionError> {
+        if self.decimals == 0 {
+            return Ok(self.amount.to_string())
+        }
+        format_units(self.amount, self.decimals as u32)
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format() {
+            Ok(amount) => f.write_str(&amount),
+            Err(_) => write!(f, "{}e-{}", self.amount, self.decimals),
+        }
+    }
+}
+
+impl From<TokenAmount> for U256 {
+    fn from(amount: TokenAmount) -> Self {
+        amount.amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_and_format_amounts() {
+        let amount = TokenAmount::parse("1234.5678", 18).unwrap();
+        assert_eq!(amount.amount, U256::from(1_234_567_800_000_000_000_000u128));
+        assert_eq!(amount.to_string(), "1234.567800000000000000");
+
+        let amount = TokenAmount::new(42u64, 0);
+        assert_eq!(amount.to_string(), "42");
+
+        assert!(TokenAmount::parse("-1", 18).is_err());
+    }
+}
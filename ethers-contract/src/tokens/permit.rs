@@ -0,0 +1,294 @@
+//! EIP-712 messages for gasless token approvals.
+//!
+//! Both [`Permit`] and [`PermitTransferFrom`] implement [`Eip712`], so they can be signed with
+//! `Signer::sign_typed_data`.
+
+use ethers_core::{
+    abi::{encode, Token},
+    types::{
+        transaction::eip712::{EIP712Domain, Eip712, Eip712Error},
+        Address, H160, U256,
+    },
+    utils::keccak256,
+};
+
+/// The address of Uniswap's Permit2 contract, which is the same on all chains.
+pub const PERMIT2_ADDRESS: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x22, 0xd4, 0x73, 0x03, 0x0f, 0x11, 0x6d, 0xde, 0xe9, 0xf6, 0xb4,
+    0x3a, 0xc7, 0x8b, 0xa3,
+]);
+
+const PERMIT_TYPE: &str =
+    "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+
+const TOKEN_PERMISSIONS_TYPE: &str = "TokenPermissions(address token,uint256 amount)";
+
+const PERMIT_TRANSFER_FROM_TYPE: &str = "PermitTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline)TokenPermissions(address token,uint256 amount)";
+
+/// An [EIP-2612](https://eips.ethereum.org/EIPS/eip-2612) permit, which allows `spender` to spend
+/// `value` tokens of `owner`.
+///
+/// The permit data of a token can be fetched with
+/// [`IERC20::permit_data`](super::erc20::IERC20::permit_data), and a signed permit is submitted
+/// with [`IERC20::permit_with_signature`](super::erc20::IERC20::permit_with_signature).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permit {
+    /// The EIP-712 domain of the token.
+    pub domain: EIP712Domain,
+    /// The owner of the tokens.
+    pub owner: Address,
+    /// The spender that is approved.
+    pub spender: Address,
+    /// The approved amount.
+    pub value: U256,
+    /// The current permit nonce of `owner`.
+    pub nonce: U256,
+    /// The timestamp after which the permit is invalid.
+    pub deadline: U256,
+}
+
+impl Eip712 for Permit {
+    type Error = Eip712Error;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(self.domain.clone())
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(PERMIT_TYPE))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(encode(&[
+            Token::FixedBytes(Self::type_hash()?.to_vec()),
+            Token::Address(self.owner),
+            Token::Address(self.spender),
+            Token::Uint(self.value),
+            Token::Uint(self.nonce),
+            Token::Uint(self.deadline),
+        ])))
+    }
+}
+
+/// The token and amount of a Permit2 signature transfer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenPermissions {
+    /// The token to transfer.
+    pub token: Address,
+    /// The maximum amount that can be transferred.
+    pub amount: U256,
+}
+
+impl TokenPermissions {
+    fn struct_hash(&self) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::FixedBytes(keccak256(TOKEN_PERMISSIONS_TYPE).to_vec()),
+            Token::Address(self.token),
+            Token::Uint(self.amount),
+        ]))
+    }
+}
+
+/// A Permit2 `PermitTransferFrom` message, which allows `spender` to transfer tokens of the signer
+/// once through the Permit2 contract's `permitTransferFrom`.
+///
+/// # Example
+///
+/// ```
+/// use ethers_contract::tokens::permit::PermitTransferFrom;
+/// use ethers_core::types::{transaction::eip712::Eip712, Address, U256};
+///
+/// let permit = PermitTransferFrom::new(
+///     1,
+///     Address::random(),
+///     U256::exp10(18),
+///     Address::random(),
+///     U256::zero(),
+///     U256::MAX,
+/// );
+/// let digest = permit.encode_eip712().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermitTransferFrom {
+    /// The token and amount that can be transferred.
+    pub permitted: TokenPermissions,
+    /// The address that can execute the transfer.
+    pub spender: Address,
+    /// The unordered nonce of the signer.
+    pub nonce: U256,
+    /// The timestamp after which the permit is invalid.
+    pub deadline: U256,
+    /// The chain id of the EIP-712 domain.
+    pub chain_id: U256,
+    /// The address of the Permit2 contract, [`PERMIT2_ADDRESS`] by default.
+    pub permit2: Address,
+}
+
+impl PermitTransferFrom {
+    /// Creates a new message for the canonical Permit2 deployment on the given chain.
+    pub fn new(
+        chain_id: impl Into<U256>,
+        token: Address,
+        amount: U256,
+        spender: Address,
+        nonce: U256,
+        deadline: U256,
+    ) -> Self {
+        Self {
+            permitted: TokenPermissions { token, amount },
+            spender,
+            nonce,
+            deadline,
+            chain_id: chain_id.into(),
+            permit2: PERMIT2_ADDRESS,
+        }
+    }
+
+    /// Sets the address of the Permit2 contract.
+    #[must_use]
+    pub fn permit2(mut self, permit2: Address) -> Self {
+        self.permit2 = permit2;
+        self
+    }
+}
+
+impl Eip712 for PermitTransferFrom {
+    type Error = Eip712Error;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(EIP712Domain {
+            name: Some("Permit2".to_string()),
+            version: None,
+            chain_id: Some(self.chain_id),
+            verifying_contract: Some(self.permit2),
+            salt: None,
+        })
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(PERMIT_TRANSFER_FROM_TYPE))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(encode(&[
+            Token::FixedBytes(Self::type_hash()?.to_vec()),
+            Token::FixedBytes(self.permitted.struct_hash().to_vec()),
+            Token::Address(self.spender),
+            Token::Uint(self.nonce),
+            Token::Uint(self.deadline),
+        ])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::transaction::eip712::TypedData;
+
+    #[test]
+    fn permit_matches_typed_data() {
+        let owner: Address = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf".parse().unwrap();
+        let spender: Address = "0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF".parse().unwrap();
+        let token: Address = "0x6B175474E89094C44Da98b954EedeAC495271d0F".parse().unwrap();
+        let permit = Permit {
+            domain: EIP712Domain {
+                name: Some("Dai Stablecoin".to_string()),
+                version: Some("1".to_string()),
+                chain_id: Some(1u64.into()),
+                verifying_contract: Some(token),
+                salt: None,
+            },
+            owner,
+            spender,
+            value: U256::exp10(18),
+            nonce: 3u64.into(),
+            deadline: 1_700_000_000u64.into(),
+        };
+
+        let json = serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Permit": [
+                    { "name": "owner", "type": "address" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" }
+                ]
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": "Dai Stablecoin",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": token
+            },
+            "message": {
+                "owner": owner,
+                "spender": spender,
+                "value": "1000000000000000000",
+                "nonce": 3,
+                "deadline": 1700000000
+            }
+        });
+        let typed_data: TypedData = serde_json::from_value(json).unwrap();
+
+        assert_eq!(permit.encode_eip712().unwrap(), typed_data.encode_eip712().unwrap());
+    }
+
+    #[test]
+    fn permit_transfer_from_matches_typed_data() {
+        let spender: Address = "0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF".parse().unwrap();
+        let token: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse().unwrap();
+        let permit =
+            PermitTransferFrom::new(1, token, 1_000_000u64.into(), spender, 7u64.into(), U256::MAX);
+
+        let json = serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "PermitTransferFrom": [
+                    { "name": "permitted", "type": "TokenPermissions" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" }
+                ],
+                "TokenPermissions": [
+                    { "name": "token", "type": "address" },
+                    { "name": "amount", "type": "uint256" }
+                ]
+            },
+            "primaryType": "PermitTransferFrom",
+            "domain": {
+                "name": "Permit2",
+                "chainId": 1,
+                "verifyingContract": PERMIT2_ADDRESS
+            },
+            "message": {
+                "permitted": { "token": token, "amount": 1000000 },
+                "spender": spender,
+                "nonce": 7,
+                "deadline": U256::MAX
+            }
+        });
+        let typed_data: TypedData = serde_json::from_value(json).unwrap();
+
+        assert_eq!(permit.encode_eip712().unwrap(), typed_data.encode_eip712().unwrap());
+    }
+
+    #[test]
+    fn permit2_address() {
+        assert_eq!(
+            PERMIT2_ADDRESS,
+            "0x000000000022D473030F116dDEE9F6B43aC78BA3".parse::<Address>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,526 @@
+//! Encoding of function calls from string and JSON values, and decoding of calls and return
+//! values to JSON, when only the ABI of a function is known at runtime.
+//!
+//! Values are parsed according to the [`ParamType`] of the parameter:
+//! - `address`: a hex encoded address
+//! - `uintN` and `intN`: decimal or `0x` prefixed hex numbers. Decimal numbers can have a fraction,
+//!   an exponent or a unit suffix, e.g. `1.5e18`, `1.5 ether` or `20gwei`, as long as the result is
+//!   an integer
+//! - `bool`: `true` or `false`
+//! - `string`: any value, surrounding double quotes are removed
+//! - `bytes` and `bytesN`: hex encoded bytes, `bytesN` values must have exactly `N` bytes
+//! - arrays and tuples: comma separated values in `[...]` or `(...)`, e.g. `[(1,"a"),(2,"b")]`
+//!
+//! Numbers are decoded to JSON strings since they can exceed the range of JSON numbers.
+
+use crate::{
+    abi::{
+        error::{bail, format_err},
+        token::{LenientTokenizer, Tokenizer},
+        Function, HumanReadableParser, Param, ParamType, ParseError, Token,
+    },
+    types::{Address, Bytes, I256, U256},
+    utils::Units,
+};
+use serde_json::{Map, Value};
+
+/// Parses a human-readable function signature and encodes a call with the given string
+/// arguments.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::abi::encode_call;
+///
+/// let calldata = encode_call(
+///     "transfer(address,uint256)",
+///     &["0xdAC17F958D2ee523a2206206994597C13D831ec7", "1.5e18"],
+/// )
+/// .unwrap();
+/// assert_eq!(&calldata[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+/// ```
+pub fn encode_call<S: AsRef<str>>(signature: &str, args: &[S]) -> Result<Bytes, ParseError> {
+    let function = HumanReadableParser::parse_function(signature)?;
+    encode_function_args(&function, args)
+}
+
+/// Encodes a call of `function` with string arguments, see the [module docs](self) for the
+/// supported formats.
+pub fn encode_function_args<S: AsRef<str>>(
+    function: &Function,
+    args: &[S],
+) -> Result<Bytes, ParseError> {
+    if args.len() != function.inputs.len() {
+        bail!("`{}` expects {} arguments, got {}", function.name, function.inputs.len(), args.len())
+    }
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(args)
+        .enumerate()
+        .map(|(i, (param, arg))| {
+            tokenize_str(&param.kind, arg.as_ref()).map_err(|err| param_err(param, i, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(function.encode_input(&tokens)?.into())
+}
+
+/// Encodes a call of `function` with JSON arguments, which are either an array of the arguments
+/// or an object that maps the names of the parameters to the arguments.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::abi::{encode_function_json, HumanReadableParser};
+/// use serde_json::json;
+///
+/// let function =
+///     HumanReadableParser::parse_function("function approve(address spender, uint256 amount)")
+///         .unwrap();
+/// let args = json!({ "spender": "0xdAC17F958D2ee523a2206206994597C13D831ec7", "amount": 100 });
+/// let calldata = encode_function_json(&function, &args).unwrap();
+/// ```
+pub fn encode_function_json(function: &Function, args: &Value) -> Result<Bytes, ParseError> {
+    let args = match args {
+        Value::Array(args) => {
+            if args.len() != function.inputs.len() {
+                bail!(
+                    "`{}` expects {} arguments, got {}",
+                    function.name,
+                    function.inputs.len(),
+                    args.len()
+                )
+            }
+            args.iter().collect::<Vec<_>>()
+        }
+        Value::Object(args) => function
+            .inputs
+            .iter()
+            .map(|param| {
+                args.get(&param.name)
+                    .ok_or_else(|| format_err!("missing argument `{}`", param.name))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        arg if function.inputs.len() == 1 => vec![arg],
+        _ => bail!("expected an array or object of arguments for `{}`", function.name),
+    };
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(args)
+        .enumerate()
+        .map(|(i, (param, arg))| {
+            tokenize_json(&param.kind, arg).map_err(|err| param_err(param, i, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(function.encode_input(&tokens)?.into())
+}
+
+/// Decodes the arguments of a call of `function` to JSON, see [`tokens_to_json`].
+pub fn decode_function_input_json(
+    function: &Function,
+    calldata: &[u8],
+) -> Result<Value, ParseError> {
+    if calldata.get(..4) != Some(&function.short_signature()[..]) {
+        bail!("calldata does not start with the selector of `{}`", function.name)
+    }
+    let tokens = function.decode_input(&calldata[4..])?;
+    Ok(tokens_to_json(&function.inputs, &tokens))
+}
+
+/// Decodes the return values of `function` to JSON, see [`tokens_to_json`].
+pub fn decode_function_output_json(function: &Function, data: &[u8]) -> Result<Value, ParseError> {
+    let tokens = function.decode_output(data)?;
+    Ok(tokens_to_json(&function.outputs, &tokens))
+}
+
+/// Converts the tokens of `params` to a JSON object keyed by the parameter names, or to an array
+/// if any parameter is unnamed.
+pub fn tokens_to_json(params: &[Param], tokens: &[Token]) -> Value {
+    if params.iter().any(|param| param.name.is_empty()) {
+        return Value::Array(tokens.iter().map(token_to_json).collect())
+    }
+    let values =
+        params.iter().zip(tokens).map(|(param, token)| (param.name.clone(), token_to_json(token)));
+    Value::Object(values.collect::<Map<_, _>>())
+}
+
+/// Converts a token to JSON, numbers are converted to decimal strings, bytes to hex strings and
+/// tuples to arrays.
+pub fn token_to_json(token: &Token) -> Value {
+    match token {
+        Token::Address(address) => Value::String(format!("{address:?}")),
+        Token::Uint(value) => Value::String(value.to_string()),
+        Token::Int(value) => Value::String(I256::from_raw(*value).to_string()),
+        Token::Bool(value) => Value::Bool(*value),
+        Token::String(value) => Value::String(value.clone()),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+            Value::String(format!("0x{}", hex::encode(bytes)))
+        }
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            Value::Array(tokens.iter().map(token_to_json).collect())
+        }
+    }
+}
+
+/// Parses a string value of type `kind`, see the [module docs](self) for the supported formats.
+pub fn tokenize_str(kind: &ParamType, value: &str) -> Result<Token, ParseError> {
+    let value = value.trim();
+    Ok(match kind {
+        ParamType::Address => Token::Address(
+            unquote(value)?
+                .parse::<Address>()
+                .map_err(|_| format_err!("invalid address `{value}`"))?,
+        ),
+        ParamType::Uint(bits) => Token::Uint(parse_uint(unquote(value)?, *bits)?),
+        ParamType::Int(bits) => Token::Int(parse_int(unquote(value)?, *bits)?),
+        ParamType::Bool => match unquote(value)? {
+            "true" => Token::Bool(true),
+            "false" => Token::Bool(false),
+            _ => bail!("invalid bool `{value}`"),
+        },
+        ParamType::String => Token::String(if value.len() >= 2 && value.starts_with('"') {
+            serde_json::from_str(value).map_err(|_| format_err!("invalid string `{value}`"))?
+        } else {
+            value.to_string()
+        }),
+        ParamType::Bytes => Token::Bytes(parse_hex(unquote(value)?)?),
+        ParamType::FixedBytes(len) => {
+            let bytes = parse_hex(unquote(value)?)?;
+            if bytes.len() != *len {
+                bail!("expected {len} bytes, got {} in `{value}`", bytes.len())
+            }
+            Token::FixedBytes(bytes)
+        }
+        ParamType::Array(kind) => {
+            let items = split_list(value, '[', ']')?;
+            Token::Array(tokenize_items(kind, items.iter().copied(), tokenize_str)?)
+        }
+        ParamType::FixedArray(kind, len) => {
+            let items = split_list(value, '[', ']')?;
+            if items.len() != *len {
+                bail!("expected {len} items, got {} in `{value}`", items.len())
+            }
+            Token::FixedArray(tokenize_items(kind, items.iter().copied(), tokenize_str)?)
+        }
+        ParamType::Tuple(kinds) => {
+            let items = split_list(value, '(', ')')?;
+            if items.len() != kinds.len() {
+                bail!("expected {} tuple fields, got {} in `{value}`", kinds.len(), items.len())
+            }
+            Token::Tuple(tokenize_fields(kinds, items.iter().copied(), tokenize_str)?)
+        }
+    })
+}
+
+/// Parses a JSON value of type `kind`.
+///
+/// Strings are parsed like [`tokenize_str`], arrays and tuples are JSON arrays, and numbers,
+/// booleans and nested values can also be native JSON values.
+pub fn tokenize_json(kind: &ParamType, value: &Value) -> Result<Token, ParseError> {
+    Ok(match (kind, value) {
+        (ParamType::String, Value::String(value)) => Token::String(value.clone()),
+        (_, Value::String(value)) => tokenize_str(kind, value)?,
+        (ParamType::Uint(_) | ParamType::Int(_), Value::Number(number)) => {
+            tokenize_str(kind, &number.to_string())?
+        }
+        (ParamType::Bool, Value::Bool(value)) => Token::Bool(*value),
+        (ParamType::Array(kind), Value::Array(items)) => {
+            Token::Array(tokenize_items(kind, items.iter(), tokenize_json)?)
+        }
+        (ParamType::FixedArray(kind, len), Value::Array(items)) => {
+            if items.len() != *len {
+                bail!("expected {len} items, got {}", items.len())
+            }
+            Token::FixedArray(tokenize_items(kind, items.iter(), tokenize_json)?)
+        }
+        (ParamType::Tuple(kinds), Value::Array(items)) => {
+            if items.len() != kinds.len() {
+                bail!("expected {} tuple fields, got {}", kinds.len(), items.len())
+            }
+            Token::Tuple(tokenize_fields(kinds, items.iter(), tokenize_json)?)
+        }
+        (kind, value) => bail!("invalid {kind} value `{value}`"),
+    })
+}
+
+/// Tokenizes array items, errors are prefixed with the index of the item
+fn tokenize_items<T>(
+    kind: &ParamType,
+    items: impl Iterator<Item = T>,
+    tokenize: impl Fn(&ParamType, T) -> Result<Token, ParseError>,
+) -> Result<Vec<Token>, ParseError> {
+    items
+        .enumerate()
+        .map(|(i, item)| tokenize(kind, item).map_err(|err| format_err!("[{i}]: {err}")))
+        .collect()
+}
+
+/// Tokenizes tuple fields, errors are prefixed with the index of the field
+fn tokenize_fields<T>(
+    kinds: &[ParamType],
+    fields: impl Iterator<Item = T>,
+    tokenize: impl Fn(&ParamType, T) -> Result<Token, ParseError>,
+) -> Result<Vec<Token>, ParseError> {
+    kinds
+        .iter()
+        .zip(fields)
+        .enumerate()
+        .map(|(i, (kind, field))| tokenize(kind, field).map_err(|err| format_err!(".{i}: {err}")))
+        .collect()
+}
+
+/// Prefixes an error with the name, or the position, of the parameter
+fn param_err(param: &Param, index: usize, err: ParseError) -> ParseError {
+    if param.name.is_empty() {
+        format_err!("argument {index}: {err}")
+    } else {
+        format_err!("argument `{}`: {err}", param.name)
+    }
+}
+
+/// Removes the double quotes around a value, if any
+fn unquote(value: &str) -> Result<&str, ParseError> {
+    match value.strip_prefix('"') {
+        Some(inner) => {
+            inner.strip_suffix('"').ok_or_else(|| format_err!("unterminated string `{value}`"))
+        }
+        None => Ok(value),
+    }
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, ParseError> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(hex).map_err(|_| format_err!("invalid hex `{value}`"))
+}
+
+/// Parses a `uintN`
+fn parse_uint(value: &str, bits: usize) -> Result<U256, ParseError> {
+    let number = parse_number(value)?;
+    if number.bits() > bits {
+        bail!("`{value}` does not fit into uint{bits}")
+    }
+    Ok(number)
+}
+
+/// Parses an `intN` and returns its two's complement
+fn parse_int(value: &str, bits: usize) -> Result<U256, ParseError> {
+    if let Some(int) = tokenize_lenient(LenientTokenizer::tokenize_int, value) {
+        let magnitude = if int.bit(255) { !int } else { int };
+        if !(magnitude >> (bits - 1)).is_zero() {
+            bail!("`{value}` does not fit into int{bits}")
+        }
+        return Ok(int)
+    }
+
+    let (negative, magnitude) = match value.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, value),
+    };
+    let magnitude = parse_number(magnitude)?;
+    let limit = U256::one() << (bits - 1);
+    if (negative && magnitude > limit) || (!negative && magnitude >= limit) {
+        bail!("`{value}` does not fit into int{bits}")
+    }
+    Ok(if negative { (!magnitude).overflowing_add(U256::one()).0 } else { magnitude })
+}
+
+/// Parses a number with the `LenientTokenizer`, i.e. a decimal with an optional `ether`, `gwei`
+/// or `wei` unit
+fn tokenize_lenient(
+    tokenize: fn(&str) -> Result<[u8; 32], crate::abi::Error>,
+    value: &str,
+) -> Option<U256> {
+    let value = value.replace('_', "");
+    // the tokenizer reads 64 digits as unprefixed hex
+    if value.len() == 64 {
+        return None
+    }
+    tokenize(&value).ok().map(U256::from)
+}
+
+/// Parses a non-negative number: hex, or decimal with an optional fraction, exponent and unit
+fn parse_number(value: &str) -> Result<U256, ParseError> {
+    if let Some(number) = tokenize_lenient(LenientTokenizer::tokenize_uint, value) {
+        return Ok(number)
+    }
+
+    let invalid = || format_err!("invalid number `{value}`");
+    let number = value.replace('_', "");
+    if let Some(hex) = number.strip_prefix("0x") {
+        return U256::from_str_radix(hex, 16).map_err(|_| invalid())
+    }
+
+    // split off the unit, e.g. `1.5 kwei` or `20szabo`
+    let unit = number
+        .char_indices()
+        .filter(|(_, c)| c.is_ascii_alphabetic())
+        .find_map(|(i, _)| Some((i, number[i..].trim().parse::<Units>().ok()?)));
+    let (number, exponent) = match unit {
+        Some((i, unit)) => (number[..i].trim(), unit.as_num()),
+        None => (number.as_str(), 0),
+    };
+    let (number, exponent) = match number.split_once(['e', 'E']) {
+        Some((mantissa, e)) => {
+            let e = e.parse::<u32>().map_err(|_| invalid())?;
+            let exponent =
+                exponent.checked_add(e).ok_or_else(|| format_err!("`{value}` is too large"))?;
+            (mantissa, exponent)
+        }
+        None => (number, exponent),
+    };
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if integer.is_empty() && fraction.is_empty() ||
+        !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid())
+    }
+
+    // a fraction is only allowed if the exponent shifts it into the integer part
+    let fraction = fraction.trim_end_matches('0');
+    let Some(exponent) = exponent.checked_sub(fraction.len() as u32) else {
+        bail!("`{value}` is not an integer")
+    };
+    let digits = format!("{integer}{fraction}");
+    let digits = if digits.is_empty() { "0" } else { digits.as_str() };
+    U256::from_dec_str(digits)
+        .ok()
+        .and_then(|number| number.checked_mul(U256::from(10).checked_pow(exponent.into())?))
+        .ok_or_else(|| format_err!("`{value}` is too large"))
+}
+
+/// Splits a `[...]` or `(...)` list into its top-level items, brackets and double quoted strings
+/// are kept intact
+fn split_list(value: &str, open: char, close: char) -> Result<Vec<&str>, ParseError> {
+    let inner = value
+        .strip_prefix(open)
+        .and_then(|value| value.strip_suffix(close))
+        .ok_or_else(|| format_err!("expected `{open}...{close}`, got `{value}`"))?;
+    if inner.trim().is_empty() {
+        return Ok(Vec::new())
+    }
+
+    let mut items = Vec::new();
+    let (mut depth, mut in_string, mut escaped, mut start) = (0usize, false, false, 0);
+    for (i, c) in inner.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue
+        }
+        match c {
+            '"' => in_string = true,
+            '[' | '(' => depth += 1,
+            ']' | ')' => {
+                depth = depth.checked_sub(1).ok_or_else(|| format_err!("unbalanced `{value}`"))?
+            }
+            ',' if depth == 0 => {
+                items.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 || in_string {
+        bail!("unbalanced `{value}`")
+    }
+    items.push(inner[start..].trim());
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_parse_numbers() {
+        let wei = |value| parse_uint(value, 256).unwrap();
+        assert_eq!(wei("1.5e18"), U256::from(1_500_000_000_000_000_000u128));
+        assert_eq!(wei("1.5 ether"), wei("1500000000000000000"));
+        assert_eq!(wei("20gwei"), U256::from(20_000_000_000u64));
+        assert_eq!(wei("0x10"), U256::from(16));
+        assert_eq!(wei("1_000"), U256::from(1000));
+        assert_eq!(wei("1.0"), U256::one());
+        assert!(parse_uint("1.5", 256).is_err());
+        assert!(parse_uint("256", 8).is_err());
+        assert!(parse_uint("abc", 256).is_err());
+
+        assert_eq!(parse_int("-1", 8).unwrap(), U256::MAX);
+        assert_eq!(I256::from_raw(parse_int("-128", 8).unwrap()), I256::from(-128));
+        assert!(parse_int("128", 8).is_err());
+        assert!(parse_int("-129", 8).is_err());
+        assert_eq!(parse_int("-1e3", 16).unwrap(), (!U256::from(999)));
+        assert!(parse_int("-32769", 16).is_err());
+
+        // the exponent of the unit and the exponent of the number overflow together
+        assert!(parse_uint("1e4294967295 ether", 256).is_err());
+        // 64 digits are decimal, not unprefixed hex
+        let digits = "1".repeat(64);
+        assert_eq!(wei(&digits), U256::from_dec_str(&digits).unwrap());
+    }
+
+    #[test]
+    fn can_tokenize_nested_values() {
+        let kind = ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Uint(256),
+            ParamType::String,
+            ParamType::FixedBytes(2),
+        ])));
+        let token = tokenize_str(&kind, r#"[(1, "a, (b)", 0xabcd), (2gwei, c, 0x0000)]"#).unwrap();
+        assert_eq!(
+            token,
+            Token::Array(vec![
+                Token::Tuple(vec![
+                    Token::Uint(1.into()),
+                    Token::String("a, (b)".to_string()),
+                    Token::FixedBytes(vec![0xab, 0xcd]),
+                ]),
+                Token::Tuple(vec![
+                    Token::Uint(2_000_000_000u64.into()),
+                    Token::String("c".to_string()),
+                    Token::FixedBytes(vec![0, 0]),
+                ]),
+            ])
+        );
+        assert_eq!(
+            tokenize_json(&kind, &json!([[1, "a, (b)", "0xabcd"], ["2gwei", "c", "0x0000"]]))
+                .unwrap(),
+            token
+        );
+
+        let err = tokenize_str(&kind, "[(1, a, 0xab)]").unwrap_err();
+        assert_eq!(err.to_string(), "[0]: .2: expected 2 bytes, got 1 in `0xab`");
+    }
+
+    #[test]
+    fn can_encode_and_decode_calls() {
+        let function = HumanReadableParser::parse_function(
+            "function swap(address to, uint256[] amounts, bool flag) returns (uint256 out)",
+        )
+        .unwrap();
+        let to = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+        let calldata = encode_function_args(&function, &[to, "[1, 2.5e3]", "true"]).unwrap();
+        let from_json = encode_function_json(
+            &function,
+            &json!({"to": to, "amounts": [1, "2500"], "flag": true}),
+        )
+        .unwrap();
+        assert_eq!(calldata, from_json);
+
+        assert_eq!(
+            decode_function_input_json(&function, &calldata).unwrap(),
+            json!({"to": to, "amounts": ["1", "2500"], "flag": true})
+        );
+        let output = crate::abi::encode(&[Token::Uint(7.into())]);
+        assert_eq!(decode_function_output_json(&function, &output).unwrap(), json!({"out": "7"}));
+
+        let err = encode_function_args(&function, &[to, "[1]", "yes"]).unwrap_err();
+        assert_eq!(err.to_string(), "argument `flag`: invalid bool `yes`");
+        assert!(encode_function_json(&function, &json!({"to": to})).is_err());
+    }
+}
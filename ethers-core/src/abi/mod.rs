@@ -25,12 +25,21 @@ pub use human_readable::{
     lexer::HumanReadableParser, parse as parse_abi, parse_str as parse_abi_str, AbiParser,
 };
 
+mod dynamic;
+pub use dynamic::{
+    decode_function_input_json, decode_function_output_json, encode_call, encode_function_args,
+    encode_function_json, token_to_json, tokenize_json, tokenize_str, tokens_to_json,
+};
+
 mod raw;
 pub use raw::{AbiObject, Component, Item, JsonAbi, RawAbi};
 
 mod packed;
 pub use packed::{encode_packed, EncodePackedError};
 
+mod signatures;
+pub use signatures::{FetchFuture, SignatureDb, SignatureDbError, SignatureFetcher};
+
 mod sealed {
     use ethabi::{Event, Function};
 
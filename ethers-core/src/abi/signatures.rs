@@ -0,0 +1,601 @@
+//! An offline database of function, event and error signatures.
+
+use crate::{
+    abi::{
+        ethabi::AbiError, Abi, Event, Function, HumanReadableParser, Log, Param, ParseError,
+        RawLog, Token,
+    },
+    types::{FourByteFrame, Selector, H256},
+    utils::id,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    future::Future,
+    path::Path,
+    pin::Pin,
+};
+use thiserror::Error;
+
+/// The future returned by a [`SignatureFetcher`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type FetchFuture<'a, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
+
+/// The future returned by a [`SignatureFetcher`].
+#[cfg(target_arch = "wasm32")]
+pub type FetchFuture<'a, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + 'a>>;
+
+/// A remote source of signatures, like the [4byte directory](https://www.4byte.directory) or
+/// [openchain](https://openchain.xyz/signatures), that is used to resolve selectors which are not
+/// in a [`SignatureDb`].
+pub trait SignatureFetcher {
+    /// The error of a lookup.
+    type Error;
+
+    /// Looks up the candidate signatures of function or error selectors, e.g.
+    /// `transfer(address,uint256)`.
+    fn fetch_functions<'a>(
+        &'a self,
+        selectors: &'a [Selector],
+    ) -> FetchFuture<'a, HashMap<Selector, Vec<String>>, Self::Error>;
+
+    /// Looks up the candidate signatures of event topics, e.g.
+    /// `Transfer(address,address,uint256)`.
+    fn fetch_events<'a>(
+        &'a self,
+        topics: &'a [H256],
+    ) -> FetchFuture<'a, HashMap<H256, Vec<String>>, Self::Error>;
+}
+
+/// An error that occurred while loading or saving a [`SignatureDb`].
+#[derive(Debug, Error)]
+pub enum SignatureDbError {
+    /// Reading or writing a file failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A JSON file could not be (de)serialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// A signature could not be parsed.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// A registry that maps function and error selectors and event topics to candidate signatures,
+/// which is used to decode calldata, revert data and logs without the ABI of the contract.
+///
+/// Several signatures can share a selector, all of them are tried when decoding.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::abi::{encode, SignatureDb, Token};
+/// use ethers_core::types::Address;
+///
+/// let mut db = SignatureDb::new();
+/// db.insert("transfer(address,uint256)").unwrap();
+/// db.insert("event Transfer(address indexed,address indexed,uint256)").unwrap();
+///
+/// let calldata = db.functions().next().unwrap().encode_input(&[
+///     Token::Address(Address::zero()),
+///     Token::Uint(100u64.into()),
+/// ]).unwrap();
+/// let candidates = db.decode_calldata(&calldata);
+/// assert_eq!(candidates[0].0.name, "transfer");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignatureDb {
+    functions: BTreeMap<Selector, Vec<Function>>,
+    events: BTreeMap<H256, Vec<Event>>,
+    errors: BTreeMap<Selector, Vec<AbiError>>,
+}
+
+impl SignatureDb {
+    /// Creates an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a database from a file.
+    ///
+    /// JSON files in the format written by [`Self::save`] are supported, as well as text files
+    /// with one signature per line in the format of [`Self::insert`]. Empty lines and lines
+    /// starting with `#` are skipped, and so are lines that can not be parsed, since public
+    /// signature dumps contain many invalid entries.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SignatureDbError> {
+        let content = fs::read_to_string(path)?;
+        if content.trim_start().starts_with('{') {
+            return Ok(serde_json::from_str(&content)?)
+        }
+
+        let mut db = Self::new();
+        for line in content.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') {
+                let _ = db.insert(line);
+            }
+        }
+        Ok(db)
+    }
+
+    /// Saves the database as JSON, which maps the hex encoded selectors and topics to their
+    /// signatures.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SignatureDbError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds the functions, events and errors of the ABIs of all compiled artifacts in `dir` and
+    /// its subdirectories, e.g. the `out` directory of Foundry or the `artifacts` directory of
+    /// Hardhat.
+    ///
+    /// Every JSON file with an `abi` field is treated as an artifact, other files are skipped.
+    pub fn load_artifacts(&mut self, dir: impl AsRef<Path>) -> Result<(), SignatureDbError> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.load_artifacts(&path)?;
+            } else if path.extension().map_or(false, |ext| ext == "json") {
+                #[derive(Deserialize)]
+                struct Artifact {
+                    abi: Abi,
+                }
+                if let Ok(artifact) = serde_json::from_str::<Artifact>(&fs::read_to_string(&path)?)
+                {
+                    self.extend_abi(&artifact.abi);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds all functions, events and errors of `abi`.
+    pub fn extend_abi(&mut self, abi: &Abi) {
+        abi.functions().cloned().for_each(|function| self.insert_function(function));
+        abi.events().cloned().for_each(|event| self.insert_event(event));
+        abi.errors().cloned().for_each(|error| self.insert_error(error));
+    }
+
+    /// Parses and adds a human-readable signature.
+    ///
+    /// Signatures prefixed with `event` or `error` are added as events or errors, all other
+    /// signatures as functions, with or without the `function` prefix. Since signatures from
+    /// databases usually do not mark indexed parameters, the leading parameters of events without
+    /// indexed parameters are treated as indexed when decoding a log with more topics.
+    pub fn insert(&mut self, signature: &str) -> Result<(), ParseError> {
+        let signature = signature.trim();
+        if signature.starts_with("event ") {
+            self.insert_event(HumanReadableParser::parse_event(signature)?);
+        } else if signature.starts_with("error ") {
+            self.insert_error(HumanReadableParser::parse_error(signature)?);
+        } else {
+            self.insert_function(HumanReadableParser::parse_function(signature)?);
+        }
+        Ok(())
+    }
+
+    /// Adds a function, which is ignored if it is already known.
+    pub fn insert_function(&mut self, function: Function) {
+        insert_unique(self.functions.entry(function.short_signature()).or_default(), function);
+    }
+
+    /// Adds an event, which is ignored if it is already known.
+    pub fn insert_event(&mut self, event: Event) {
+        insert_unique(self.events.entry(event.signature()).or_default(), event);
+    }
+
+    /// Adds an error, which is ignored if it is already known.
+    pub fn insert_error(&mut self, error: AbiError) {
+        insert_unique(self.errors.entry(error_selector(&error)).or_default(), error);
+    }
+
+    /// Adds all signatures of `other`.
+    pub fn merge(&mut self, other: SignatureDb) {
+        other.functions.into_values().flatten().for_each(|function| self.insert_function(function));
+        other.events.into_values().flatten().for_each(|event| self.insert_event(event));
+        other.errors.into_values().flatten().for_each(|error| self.insert_error(error));
+    }
+
+    /// Returns all functions.
+    pub fn functions(&self) -> impl Iterator<Item = &Function> {
+        self.functions.values().flatten()
+    }
+
+    /// Returns all events.
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.events.values().flatten()
+    }
+
+    /// Returns all errors.
+    pub fn errors(&self) -> impl Iterator<Item = &AbiError> {
+        self.errors.values().flatten()
+    }
+
+    /// Returns the functions with the given selector.
+    pub fn functions_by_selector(&self, selector: Selector) -> &[Function] {
+        self.functions.get(&selector).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the events with the given topic0.
+    pub fn events_by_topic(&self, topic: H256) -> &[Event] {
+        self.events.get(&topic).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the errors with the given selector.
+    pub fn errors_by_selector(&self, selector: Selector) -> &[AbiError] {
+        self.errors.get(&selector).map_or(&[], Vec::as_slice)
+    }
+
+    /// Decodes calldata with all functions that match its selector.
+    ///
+    /// Only candidates that decode the calldata and encode the decoded arguments to the same
+    /// calldata are returned, which rules out most signatures that collide by chance.
+    pub fn decode_calldata(&self, data: &[u8]) -> Vec<(&Function, Vec<Token>)> {
+        let Some(selector) = selector(data) else { return Vec::new() };
+        self.functions_by_selector(selector)
+            .iter()
+            .filter_map(|function| {
+                let tokens = function.decode_input(&data[4..]).ok()?;
+                let encoded = function.encode_input(&tokens).ok()?;
+                (encoded == data).then_some((function, tokens))
+            })
+            .collect()
+    }
+
+    /// Decodes revert data with all errors that match its selector, see
+    /// [`Self::decode_calldata`].
+    pub fn decode_error(&self, data: &[u8]) -> Vec<(&AbiError, Vec<Token>)> {
+        let Some(selector) = selector(data) else { return Vec::new() };
+        self.errors_by_selector(selector)
+            .iter()
+            .filter_map(|error| {
+                let tokens = error.decode(&data[4..]).ok()?;
+                let encoded = [&selector[..], &crate::abi::encode(&tokens)].concat();
+                (encoded == data).then_some((error, tokens))
+            })
+            .collect()
+    }
+
+    /// Decodes a log with all events that match its topic0.
+    pub fn decode_log(&self, log: &RawLog) -> Vec<(&Event, Log)> {
+        let Some(topic) = log.topics.first() else { return Vec::new() };
+        self.events_by_topic(*topic)
+            .iter()
+            .filter_map(|event| Some((event, parse_log(event, log)?)))
+            .collect()
+    }
+
+    /// Returns the selectors of a [`FourByteFrame`] that are not in the database.
+    pub fn unknown_selectors(&self, frame: &FourByteFrame) -> Vec<Selector> {
+        frame
+            .0
+            .keys()
+            .filter_map(|key| {
+                let selector = key.split('-').next()?.strip_prefix("0x")?;
+                hex::decode(selector).ok()?.try_into().ok()
+            })
+            .filter(|selector| !self.functions.contains_key(selector))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Looks up the function selectors that are not in the database with `fetcher` and adds the
+    /// returned signatures.
+    ///
+    /// Signatures that can not be parsed or that do not match the selector they were returned for
+    /// are ignored.
+    pub async fn resolve_functions<F: SignatureFetcher>(
+        &mut self,
+        fetcher: &F,
+        selectors: impl IntoIterator<Item = Selector>,
+    ) -> Result<(), F::Error> {
+        let unknown = selectors
+            .into_iter()
+            .filter(|selector| !self.functions.contains_key(selector))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            return Ok(())
+        }
+        for (selector, signatures) in fetcher.fetch_functions(&unknown).await? {
+            for signature in signatures {
+                match HumanReadableParser::parse_function(&signature) {
+                    Ok(function) if function.short_signature() == selector => {
+                        self.insert_function(function)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up the event topics that are not in the database with `fetcher` and adds the
+    /// returned signatures, see [`Self::resolve_functions`].
+    pub async fn resolve_events<F: SignatureFetcher>(
+        &mut self,
+        fetcher: &F,
+        topics: impl IntoIterator<Item = H256>,
+    ) -> Result<(), F::Error> {
+        let unknown = topics
+            .into_iter()
+            .filter(|topic| !self.events.contains_key(topic))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            return Ok(())
+        }
+        for (topic, signatures) in fetcher.fetch_events(&unknown).await? {
+            for signature in signatures {
+                match HumanReadableParser::parse_event(&signature) {
+                    Ok(event) if event.signature() == topic => self.insert_event(event),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Looks up the unknown selectors of a [`FourByteFrame`], as returned by the geth
+    /// `4byteTracer`, with `fetcher` and adds the returned signatures.
+    pub async fn resolve_four_byte<F: SignatureFetcher>(
+        &mut self,
+        fetcher: &F,
+        frame: &FourByteFrame,
+    ) -> Result<(), F::Error> {
+        let selectors = self.unknown_selectors(frame);
+        self.resolve_functions(fetcher, selectors).await
+    }
+}
+
+fn insert_unique<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    if !items.contains(&item) {
+        items.push(item);
+    }
+}
+
+fn selector(data: &[u8]) -> Option<Selector> {
+    data.get(..4)?.try_into().ok()
+}
+
+/// Returns the signature without return types, e.g. `transfer(address,uint256)`
+fn signature(name: &str, params: &[Param]) -> String {
+    let types = params.iter().map(|param| param.kind.to_string()).collect::<Vec<_>>();
+    format!("{name}({})", types.join(","))
+}
+
+fn error_selector(error: &AbiError) -> Selector {
+    id(signature(&error.name, &error.inputs))
+}
+
+/// Parses a log, the leading parameters of events without indexed parameters are assumed to be
+/// indexed if the log has more topics
+fn parse_log(event: &Event, log: &RawLog) -> Option<Log> {
+    // ethabi matches the decoded values to the parameters by name, so unnamed parameters are
+    // named by their position while parsing
+    let mut event = event.clone();
+    let unnamed = event.inputs.iter().map(|param| param.name.is_empty()).collect::<Vec<_>>();
+    for (i, param) in event.inputs.iter_mut().enumerate().filter(|(_, p)| p.name.is_empty()) {
+        param.name = format!("_{i}");
+    }
+
+    let mut parsed = event.parse_log(log.clone());
+    if parsed.is_err() && !event.anonymous && event.inputs.iter().all(|param| !param.indexed) {
+        let indexed = log.topics.len().saturating_sub(1);
+        event.inputs.iter_mut().take(indexed).for_each(|param| param.indexed = true);
+        parsed = event.parse_log(log.clone());
+    }
+
+    let mut parsed = parsed.ok()?;
+    for (param, unnamed) in parsed.params.iter_mut().zip(unnamed) {
+        if unnamed {
+            param.name.clear();
+        }
+    }
+    Some(parsed)
+}
+
+/// The JSON format of a [`SignatureDb`]
+#[derive(Default, Serialize, Deserialize)]
+struct SignatureDbJson {
+    #[serde(default)]
+    functions: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    events: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    errors: BTreeMap<String, Vec<String>>,
+}
+
+impl Serialize for SignatureDb {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let event_signature = |event: &Event| {
+            let params = event
+                .inputs
+                .iter()
+                .map(|param| {
+                    if param.indexed {
+                        format!("{} indexed", param.kind)
+                    } else {
+                        param.kind.to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+            format!("{}({})", event.name, params.join(","))
+        };
+        let json = SignatureDbJson {
+            functions: self
+                .functions
+                .iter()
+                .map(|(selector, functions)| {
+                    let signatures =
+                        functions.iter().map(|f| signature(&f.name, &f.inputs)).collect();
+                    (format!("0x{}", hex::encode(selector)), signatures)
+                })
+                .collect(),
+            events: self
+                .events
+                .iter()
+                .map(|(topic, events)| {
+                    (format!("{topic:?}"), events.iter().map(event_signature).collect())
+                })
+                .collect(),
+            errors: self
+                .errors
+                .iter()
+                .map(|(selector, errors)| {
+                    let signatures =
+                        errors.iter().map(|e| format!("error {}", signature(&e.name, &e.inputs)));
+                    (format!("0x{}", hex::encode(selector)), signatures.collect())
+                })
+                .collect(),
+        };
+        json.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SignatureDb {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let json = SignatureDbJson::deserialize(deserializer)?;
+        let mut db = SignatureDb::new();
+        for signature in json.functions.values().flatten() {
+            db.insert_function(
+                HumanReadableParser::parse_function(signature).map_err(D::Error::custom)?,
+            );
+        }
+        for signature in json.events.values().flatten() {
+            db.insert_event(HumanReadableParser::parse_event(signature).map_err(D::Error::custom)?);
+        }
+        for signature in json.errors.values().flatten() {
+            db.insert_error(HumanReadableParser::parse_error(signature).map_err(D::Error::custom)?);
+        }
+        Ok(db)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::{abi::encode, types::Address, utils::keccak256};
+
+    struct StaticFetcher;
+
+    impl SignatureFetcher for StaticFetcher {
+        type Error = std::convert::Infallible;
+
+        fn fetch_functions<'a>(
+            &'a self,
+            selectors: &'a [Selector],
+        ) -> FetchFuture<'a, HashMap<Selector, Vec<String>>, Self::Error> {
+            Box::pin(async move {
+                Ok(selectors
+                    .iter()
+                    .map(|selector| {
+                        // the first signature does not match the selector
+                        (
+                            *selector,
+                            vec!["foo()".to_string(), "approve(address,uint256)".to_string()],
+                        )
+                    })
+                    .collect())
+            })
+        }
+
+        fn fetch_events<'a>(
+            &'a self,
+            _topics: &'a [H256],
+        ) -> FetchFuture<'a, HashMap<H256, Vec<String>>, Self::Error> {
+            Box::pin(async move { Ok(HashMap::new()) })
+        }
+    }
+
+    #[test]
+    fn can_decode_with_candidates() {
+        let mut db = SignatureDb::new();
+        db.insert("function transfer(address,uint256)").unwrap();
+        db.insert("event Transfer(address,address,uint256)").unwrap();
+        db.insert("error InsufficientBalance(uint256,uint256)").unwrap();
+
+        let args = [Token::Address(Address::repeat_byte(1)), Token::Uint(100u64.into())];
+        let calldata = [&id("transfer(address,uint256)")[..], &encode(&args)].concat();
+        let decoded = db.decode_calldata(&calldata);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].1, args);
+        assert!(db.decode_calldata(&calldata[..20]).is_empty());
+
+        let revert = [
+            &id("InsufficientBalance(uint256,uint256)")[..],
+            &encode(&[Token::Uint(1u64.into()), Token::Uint(2u64.into())]),
+        ]
+        .concat();
+        assert_eq!(db.decode_error(&revert)[0].0.name, "InsufficientBalance");
+
+        // the indexed parameters of the event are unknown
+        let log = RawLog {
+            topics: vec![
+                H256(keccak256("Transfer(address,address,uint256)")),
+                Address::repeat_byte(1).into(),
+                Address::repeat_byte(2).into(),
+            ],
+            data: encode(&[Token::Uint(100u64.into())]),
+        };
+        let decoded = db.decode_log(&log);
+        assert_eq!(decoded[0].1.params[1].value, Token::Address(Address::repeat_byte(2)));
+        assert_eq!(decoded[0].1.params[2].value, Token::Uint(100u64.into()));
+    }
+
+    #[test]
+    fn can_roundtrip_json() {
+        let mut db = SignatureDb::new();
+        db.insert("transfer(address,uint256)").unwrap();
+        db.insert("event Transfer(address indexed from,address indexed to,uint256)").unwrap();
+        db.insert("error Unauthorized()").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signatures.json");
+        db.save(&path).unwrap();
+        let loaded = SignatureDb::load(&path).unwrap();
+        assert_eq!(loaded.functions().count(), 1);
+        assert_eq!(loaded.errors().count(), 1);
+        assert!(loaded.events().next().unwrap().inputs[1].indexed);
+
+        let path = dir.path().join("signatures.txt");
+        std::fs::write(&path, "# functions\ntransfer(address,uint256)\nnot a signature\n\n")
+            .unwrap();
+        assert_eq!(SignatureDb::load(&path).unwrap().functions().count(), 1);
+    }
+
+    #[test]
+    fn can_load_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("Token.sol")).unwrap();
+        std::fs::write(
+            dir.path().join("Token.sol/Token.json"),
+            r#"{"abi":[{"type":"function","name":"decimals","inputs":[],"outputs":[{"name":"","type":"uint8"}],"stateMutability":"view"}],"bytecode":"0x"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("other.json"), r#"{"foo":1}"#).unwrap();
+
+        let mut db = SignatureDb::new();
+        db.load_artifacts(dir.path()).unwrap();
+        assert_eq!(db.functions_by_selector(id("decimals()"))[0].name, "decimals");
+    }
+
+    #[test]
+    fn can_resolve_four_byte_selectors() {
+        let mut db = SignatureDb::new();
+        db.insert("transfer(address,uint256)").unwrap();
+
+        let frame: FourByteFrame =
+            serde_json::from_str(r#"{"0xa9059cbb-64": 1, "0x095ea7b3-64": 2}"#).unwrap();
+        assert_eq!(db.unknown_selectors(&frame), vec![id("approve(address,uint256)")]);
+
+        futures_executor::block_on(db.resolve_four_byte(&StaticFetcher, &frame)).unwrap();
+        assert_eq!(db.functions().count(), 2);
+        assert!(db.unknown_selectors(&frame).is_empty());
+    }
+}
@@ -280,6 +280,42 @@ impl SolStruct {
         }
     }
 
+    /// Parses all struct definitions in a snippet of Solidity source, e.g. the struct definitions
+    /// of a contract.
+    ///
+    /// Definitions can span multiple lines and `//` comments are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ethers_core::abi::SolStruct;
+    /// let structs = SolStruct::parse_all(
+    ///     r#"
+    ///     struct Person {
+    ///         string name; // the name
+    ///         address wallet;
+    ///     }
+    ///     struct Mail { Person from; Person to; string contents; }
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(structs.len(), 2);
+    /// ```
+    pub fn parse_all(s: &str) -> Result<Vec<Self>> {
+        let source = strip_comments(s).split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let mut input = source.as_str();
+        let mut structs = Vec::new();
+        while let Some(start) = find_keyword(input, "struct") {
+            let end = input[start..]
+                .find('}')
+                .ok_or_else(|| format_err!("Expected closing `}}` in `{}`", &input[start..]))?;
+            structs.push(Self::parse(&input[start..=start + end])?);
+            input = &input[start + end + 1..];
+        }
+        Ok(structs)
+    }
+
     /// Name of this struct
     pub fn name(&self) -> &str {
         &self.name
@@ -310,6 +346,40 @@ impl SolStruct {
     }
 }
 
+/// Removes all `//` and `/* */` comments from the input
+fn strip_comments(input: &str) -> String {
+    let mut stripped = String::with_capacity(input.len());
+    let mut rest = input;
+    loop {
+        let line = rest.find("//");
+        let block = rest.find("/*");
+        match (line, block) {
+            (Some(line), block) if block.map_or(true, |block| line < block) => {
+                stripped.push_str(&rest[..line]);
+                rest = rest[line..].find('\n').map_or("", |end| &rest[line + end..]);
+            }
+            (_, Some(block)) => {
+                stripped.push_str(&rest[..block]);
+                stripped.push(' ');
+                rest = rest[block + 2..].find("*/").map_or("", |end| &rest[block + end + 4..]);
+            }
+            _ => {
+                stripped.push_str(rest);
+                return stripped
+            }
+        }
+    }
+}
+
+/// Returns the position of the first occurrence of `keyword` that is not part of an identifier
+fn find_keyword(input: &str, keyword: &str) -> Option<usize> {
+    let is_identifier = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    input.match_indices(keyword).map(|(start, _)| start).find(|&start| {
+        !input[..start].chars().next_back().map_or(false, is_identifier) &&
+            !input[start + keyword.len()..].chars().next().map_or(false, is_identifier)
+    })
+}
+
 /// Strips the identifier of field declaration from the input and returns it
 fn strip_field_identifier(input: &mut &str) -> Result<String> {
     let mut iter = input.trim_end().rsplitn(2, is_whitespace);
@@ -548,4 +618,20 @@ mod tests {
         assert_eq!("_myvar", name);
         assert_eq!("uint256", s);
     }
+
+    #[test]
+    fn can_parse_all_structs() {
+        let structs = SolStruct::parse_all(
+            "
+            /* struct Commented { uint256 a; } */
+            struct Point { uint256 x; /* the y coordinate */ uint256 y; }
+            // struct Skipped { uint256 a; }
+            struct Line { Point from; Point to; Mystruct item; }
+            ",
+        )
+        .unwrap();
+        assert_eq!(structs.iter().map(SolStruct::name).collect::<Vec<_>>(), ["Point", "Line"]);
+        assert_eq!(structs[0].fields().len(), 2);
+        assert_eq!(structs[1].fields()[2].name, "item");
+    }
 }
@@ -3,7 +3,14 @@
 #[cfg(not(feature = "celo"))]
 use crate::types::Withdrawal;
 use crate::types::{Address, Bloom, Bytes, Transaction, TxHash, H256, U256, U64};
+#[cfg(not(feature = "celo"))]
+use crate::{
+    types::{trie::ordered_trie_root, TransactionReceipt},
+    utils::keccak256,
+};
 use chrono::{DateTime, TimeZone, Utc};
+#[cfg(not(feature = "celo"))]
+use rlp::RlpStream;
 use serde::{
     de::{MapAccess, Visitor},
     ser::SerializeStruct,
@@ -213,6 +220,109 @@ impl<TX> Block<TX> {
     }
 }
 
+#[cfg(not(feature = "celo"))]
+impl<TX> Block<TX> {
+    /// Returns the RLP encoding of the block header, or `None` if a header field is missing, e.g.
+    /// for pending blocks.
+    ///
+    /// The fields that were added by later forks (base fee, withdrawals root, blob gas and parent
+    /// beacon block root) are only encoded if they are present.
+    pub fn header_rlp(&self) -> Option<Bytes> {
+        let mut rlp = RlpStream::new();
+        rlp.begin_unbounded_list();
+        rlp.append(&self.parent_hash);
+        rlp.append(&self.uncles_hash);
+        rlp.append(&self.author?);
+        rlp.append(&self.state_root);
+        rlp.append(&self.transactions_root);
+        rlp.append(&self.receipts_root);
+        rlp.append(&self.logs_bloom?);
+        rlp.append(&self.difficulty);
+        rlp.append(&self.number?);
+        rlp.append(&self.gas_limit);
+        rlp.append(&self.gas_used);
+        rlp.append(&self.timestamp);
+        rlp.append(&self.extra_data.as_ref());
+        rlp.append(&self.mix_hash?);
+        rlp.append(&self.nonce?);
+
+        // fork specific fields, a field can only be present if all previous fields are
+        let optional = [
+            self.base_fee_per_gas.map(|fee| rlp::encode(&fee)),
+            self.withdrawals_root.map(|root| rlp::encode(&root)),
+            self.blob_gas_used.map(|gas| rlp::encode(&gas)),
+            self.excess_blob_gas.map(|gas| rlp::encode(&gas)),
+            self.parent_beacon_block_root.map(|root| rlp::encode(&root)),
+        ];
+        let present = optional.iter().take_while(|field| field.is_some()).count();
+        if optional[present..].iter().any(Option::is_some) {
+            return None
+        }
+        for field in optional.iter().flatten() {
+            rlp.append_raw(field, 1);
+        }
+
+        rlp.finalize_unbounded_list();
+        Some(rlp.out().freeze().into())
+    }
+
+    /// Computes the hash of the block header, see [`Self::header_rlp`].
+    pub fn header_hash(&self) -> Option<H256> {
+        self.header_rlp().map(|rlp| H256(keccak256(rlp)))
+    }
+
+    /// Returns `true` if the `hash` of the block matches the hash of its header.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_core::types::{Block, TxHash};
+    ///
+    /// # fn foo(block: Block<TxHash>) {
+    /// assert!(block.is_hash_valid(), "provider returned a forged block");
+    /// # }
+    /// ```
+    pub fn is_hash_valid(&self) -> bool {
+        self.hash.is_some() && self.hash == self.header_hash()
+    }
+
+    /// Computes the withdrawals root from the withdrawals of the block, or `None` if the block has
+    /// no withdrawals.
+    pub fn compute_withdrawals_root(&self) -> Option<H256> {
+        let withdrawals = self.withdrawals.as_ref()?;
+        Some(ordered_trie_root(withdrawals.iter().map(rlp::encode)))
+    }
+
+    /// Computes the receipts root from the receipts of the block's transactions, in the order of
+    /// the transactions.
+    ///
+    /// The receipts of a block can be fetched with `Middleware::get_block_receipts`. If the root
+    /// matches [`Self::receipts_root`] the receipts are the ones committed to by the block header.
+    pub fn compute_receipts_root(receipts: &[TransactionReceipt]) -> H256 {
+        ordered_trie_root(receipts.iter().map(TransactionReceipt::rlp))
+    }
+}
+
+#[cfg(not(feature = "celo"))]
+impl Block<Transaction> {
+    /// Computes the transactions root from the transactions of the block.
+    ///
+    /// Returns `None` if the block contains a transaction type that can not be encoded.
+    pub fn compute_transactions_root(&self) -> Option<H256> {
+        let encoded = self
+            .transactions
+            .iter()
+            .map(|tx| match tx.transaction_type.map(|ty| ty.as_u64()) {
+                None | Some(0..=2) => Some(tx.rlp()),
+                #[cfg(feature = "optimism")]
+                Some(0x7E) => Some(tx.rlp()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(ordered_trie_root(encoded))
+    }
+}
+
 impl Block<TxHash> {
     /// Converts this block that only holds transaction hashes into a full block with `Transaction`
     pub fn into_full_block(self, transactions: Vec<Transaction>) -> Block<Transaction> {
@@ -952,6 +1062,61 @@ mod tests {
               );
         let _block: Block<TxHash> = serde_json::from_value(json).unwrap();
     }
+
+    #[test]
+    fn can_compute_genesis_hash() {
+        let json = serde_json::json!({
+            "number": "0x0",
+            "hash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+            "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "nonce": "0x0000000000000042",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "stateRoot": "0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "miner": "0x0000000000000000000000000000000000000000",
+            "difficulty": "0x400000000",
+            "extraData": "0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+            "gasLimit": "0x1388",
+            "gasUsed": "0x0",
+            "timestamp": "0x0",
+            "transactions": [],
+            "uncles": []
+        });
+        let mut block: Block<TxHash> = serde_json::from_value(json).unwrap();
+        assert!(block.is_hash_valid());
+
+        block.gas_used = 1u64.into();
+        assert!(!block.is_hash_valid());
+
+        // fork fields must be contiguous
+        block.withdrawals_root = Some(H256::zero());
+        assert_eq!(block.header_rlp(), None);
+        block.base_fee_per_gas = Some(7u64.into());
+        assert!(block.header_rlp().is_some());
+
+        block.number = None;
+        assert_eq!(block.header_hash(), None);
+    }
+
+    #[test]
+    fn can_compute_roots() {
+        let block = r#"{"number":"0x3","hash":"0xda53da08ef6a3cbde84c33e51c04f68c3853b6a3731f10baa2324968eee63972","parentHash":"0x689c70c080ca22bc0e681694fa803c1aba16a69c8b6368fed5311d279eb9de90","mixHash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0000000000000000","sha3Uncles":"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","transactionsRoot":"0x7270c1c4440180f2bd5215809ee3d545df042b67329499e1ab97eb759d31610d","stateRoot":"0x29f32984517a7d25607da485b23cefabfd443751422ca7e603395e1de9bc8a4b","receiptsRoot":"0x056b23fbba480696b65fe5a59b8f2148a1299103c4f57df839233af2cf4ca2d2","miner":"0x0000000000000000000000000000000000000000","difficulty":"0x0","totalDifficulty":"0x0","extraData":"0x","size":"0x3e8","gasLimit":"0x6691b7","gasUsed":"0x5208","timestamp":"0x5ecedbb9","transactions":[{"hash":"0xc3c5f700243de37ae986082fd2af88d2a7c2752a0c0f7b9d6ac47c729d45e067","nonce":"0x2","blockHash":"0xda53da08ef6a3cbde84c33e51c04f68c3853b6a3731f10baa2324968eee63972","blockNumber":"0x3","transactionIndex":"0x0","from":"0xfdcedc3bfca10ecb0890337fbdd1977aba84807a","to":"0xdca8ce283150ab773bcbeb8d38289bdb5661de1e","value":"0x0","gas":"0x15f90","gasPrice":"0x4a817c800","input":"0x","v":"0x25","r":"0x19f2694eb9113656dbea0b925e2e7ceb43df83e601c4116aee9c0dd99130be88","s":"0x73e5764b324a4f7679d890a198ba658ba1c8cd36983ff9797e10b1b89dbb448e"}],"uncles":[]}"#;
+        let block: Block<Transaction> = serde_json::from_str(block).unwrap();
+        assert!(block.is_hash_valid());
+        assert_eq!(block.compute_transactions_root(), Some(block.transactions_root));
+
+        let receipt = TransactionReceipt {
+            transaction_hash: block.transactions[0].hash,
+            status: Some(1u64.into()),
+            cumulative_gas_used: 0x5208.into(),
+            ..Default::default()
+        };
+        assert_eq!(Block::<Transaction>::compute_receipts_root(&[receipt]), block.receipts_root);
+        assert_eq!(block.compute_withdrawals_root(), None);
+    }
 }
 
 #[cfg(test)]
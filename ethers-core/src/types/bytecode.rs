@@ -0,0 +1,665 @@
+//! EVM bytecode disassembly and analysis.
+//!
+//! The entry point is [`Disassembly`], which decodes raw bytecode into a list of
+//! [`Instruction`]s and provides some basic static analysis on top of that: jump destinations,
+//! basic blocks, dispatcher function selectors and immutable placeholders.
+//!
+//! Compiler metadata appended to the bytecode by solc and vyper can be separated with
+//! [`split_metadata`] and decoded with [`BytecodeMetadata::decode`].
+
+use crate::types::{Bytes, Opcode, Selector, H256};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fmt, ops::Range};
+use thiserror::Error;
+
+/// A single decoded EVM instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    /// The offset of this instruction in the bytecode.
+    pub pc: usize,
+    /// The raw opcode byte.
+    pub byte: u8,
+    /// The decoded opcode, or `None` if `byte` is not a known opcode.
+    pub opcode: Option<Opcode>,
+    /// The immediate data of a `PUSH` instruction.
+    ///
+    /// If the bytecode ends before all immediate bytes are available, this only contains the
+    /// bytes that are present, see [`Instruction::is_truncated`].
+    pub push_data: Option<Bytes>,
+}
+
+impl Instruction {
+    /// Returns the number of bytes this instruction occupies in the bytecode.
+    pub fn size(&self) -> usize {
+        1 + self.push_data.as_ref().map(|data| data.len()).unwrap_or_default()
+    }
+
+    /// Returns true if this is a `PUSH` instruction whose immediate data runs past the end of the
+    /// bytecode.
+    pub fn is_truncated(&self) -> bool {
+        match (self.opcode, &self.push_data) {
+            (Some(op), Some(data)) => data.len() < op.immediate_size(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if this instruction is a `JUMPDEST`.
+    pub fn is_jumpdest(&self) -> bool {
+        self.opcode == Some(Opcode::JUMPDEST)
+    }
+
+    /// Returns true if execution does not continue with the next instruction.
+    ///
+    /// Unknown opcodes are treated like `INVALID`.
+    pub fn is_terminating(&self) -> bool {
+        self.opcode.map(|op| op.is_terminating()).unwrap_or(true)
+    }
+
+    /// Returns true if this instruction ends a basic block, which is the case for terminating
+    /// instructions and `JUMPI`.
+    pub fn ends_block(&self) -> bool {
+        self.is_terminating() || self.opcode == Some(Opcode::JUMPI)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06x}: ", self.pc)?;
+        match self.opcode {
+            Some(op) => write!(f, "{op}")?,
+            None => write!(f, "UNKNOWN({:#04x})", self.byte)?,
+        }
+        if let Some(data) = &self.push_data {
+            if !data.is_empty() {
+                write!(f, " {data}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sequence of instructions that is only entered at the start and only left at the end.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BasicBlock {
+    /// The offset of the first instruction of the block.
+    pub start_pc: usize,
+    /// The offset of the last instruction of the block.
+    pub end_pc: usize,
+    /// The range of indices into [`Disassembly::instructions`] covered by this block.
+    pub instructions: Range<usize>,
+}
+
+/// Disassembled EVM bytecode.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::{bytecode::Disassembly, Opcode};
+///
+/// // PUSH1 0x80 PUSH1 0x40 MSTORE
+/// let disassembly = Disassembly::new([0x60, 0x80, 0x60, 0x40, 0x52]);
+/// let ops = disassembly.iter().map(|i| i.opcode.unwrap()).collect::<Vec<_>>();
+/// assert_eq!(ops, vec![Opcode::PUSH1, Opcode::PUSH1, Opcode::MSTORE]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Disassembly {
+    instructions: Vec<Instruction>,
+}
+
+impl Disassembly {
+    /// Disassembles the given bytecode.
+    ///
+    /// Disassembly never fails: unknown opcodes are kept as instructions without an
+    /// [`Opcode`], and a `PUSH` at the end of the code keeps whatever immediate bytes are left.
+    ///
+    /// Note that any compiler metadata at the end of the bytecode will be disassembled as well,
+    /// use [`split_metadata`] to strip it first.
+    pub fn new(code: impl AsRef<[u8]>) -> Self {
+        let code = code.as_ref();
+        let mut instructions = Vec::new();
+        let mut pc = 0;
+        while pc < code.len() {
+            let byte = code[pc];
+            let opcode = Opcode::try_from(byte).ok();
+            let push_data = opcode.filter(Opcode::is_push).map(|op| {
+                let start = pc + 1;
+                let end = (start + op.immediate_size()).min(code.len());
+                Bytes::from(code[start..end].to_vec())
+            });
+            let instruction = Instruction { pc, byte, opcode, push_data };
+            pc += instruction.size();
+            instructions.push(instruction);
+        }
+        Self { instructions }
+    }
+
+    /// Returns all instructions.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Returns an iterator over all instructions.
+    pub fn iter(&self) -> std::slice::Iter<'_, Instruction> {
+        self.instructions.iter()
+    }
+
+    /// Returns the number of instructions.
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Returns true if there are no instructions.
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Returns the index of the instruction starting at `pc`.
+    ///
+    /// Source maps produced by solc are indexed by instruction rather than by bytecode offset, so
+    /// this can be used to find the source map entry of a program counter.
+    pub fn instruction_index(&self, pc: usize) -> Option<usize> {
+        self.instructions.binary_search_by_key(&pc, |i| i.pc).ok()
+    }
+
+    /// Returns the instruction starting at `pc`.
+    pub fn instruction_at(&self, pc: usize) -> Option<&Instruction> {
+        self.instruction_index(pc).map(|idx| &self.instructions[idx])
+    }
+
+    /// Returns the offsets of all valid jump destinations.
+    pub fn jumpdests(&self) -> BTreeSet<usize> {
+        self.iter().filter(|i| i.is_jumpdest()).map(|i| i.pc).collect()
+    }
+
+    /// Splits the instructions into basic blocks.
+    ///
+    /// A new block starts at every `JUMPDEST` and after every instruction that
+    /// [ends a block](Instruction::ends_block).
+    pub fn basic_blocks(&self) -> Vec<BasicBlock> {
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            if instruction.is_jumpdest() && idx > start {
+                blocks.push(self.block(start..idx));
+                start = idx;
+            }
+            if instruction.ends_block() {
+                blocks.push(self.block(start..idx + 1));
+                start = idx + 1;
+            }
+        }
+        if start < self.instructions.len() {
+            blocks.push(self.block(start..self.instructions.len()));
+        }
+        blocks
+    }
+
+    fn block(&self, instructions: Range<usize>) -> BasicBlock {
+        BasicBlock {
+            start_pc: self.instructions[instructions.start].pc,
+            end_pc: self.instructions[instructions.end - 1].pc,
+            instructions,
+        }
+    }
+
+    /// Extracts the function selectors from the function dispatcher, in order of appearance.
+    ///
+    /// This recognizes the comparison sequences emitted by solc (`PUSH4 <selector> [DUPn] EQ
+    /// PUSHn <dest> JUMPI`) and vyper (`PUSH4 <selector> [DUPn] XOR PUSHn <dest> JUMPI`).
+    pub fn selectors(&self) -> Vec<Selector> {
+        let mut selectors = Vec::<Selector>::new();
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            if instruction.opcode != Some(Opcode::PUSH4) || instruction.is_truncated() {
+                continue
+            }
+            let mut rest = self.instructions[idx + 1..].iter().filter_map(|i| i.opcode).peekable();
+            if rest.peek().map(is_dup).unwrap_or_default() {
+                rest.next();
+            }
+            let is_dispatch = matches!(rest.next(), Some(Opcode::EQ | Opcode::XOR)) &&
+                rest.next().map(|op| op.is_push() && op != Opcode::PUSH0).unwrap_or_default() &&
+                rest.next() == Some(Opcode::JUMPI);
+            if !is_dispatch {
+                continue
+            }
+            let selector: Selector = instruction.push_data.as_deref().unwrap().try_into().unwrap();
+            if !selectors.contains(&selector) {
+                selectors.push(selector);
+            }
+        }
+        selectors
+    }
+
+    /// Returns the bytecode offsets of immutable placeholders.
+    ///
+    /// Solc reads immutable variables with `PUSH32` instructions whose immediate data is zeroed in
+    /// the compiler output and filled in at deployment time. The returned offsets point to the
+    /// first byte of the 32 byte immediate, which matches the `start` of the
+    /// `immutableReferences` reported by solc.
+    ///
+    /// Note that a literal `PUSH32 0x00..00` cannot be distinguished from a placeholder, though
+    /// solc never emits one.
+    pub fn immutable_placeholders(&self) -> Vec<usize> {
+        self.iter()
+            .filter(|i| i.opcode == Some(Opcode::PUSH32) && !i.is_truncated())
+            .filter(|i| i.push_data.as_ref().unwrap().iter().all(|b| *b == 0))
+            .map(|i| i.pc + 1)
+            .collect()
+    }
+}
+
+fn is_dup(op: &Opcode) -> bool {
+    (Opcode::DUP1..=Opcode::DUP16).contains(op)
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in self.iter() {
+            writeln!(f, "{instruction}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a Disassembly {
+    type Item = &'a Instruction;
+    type IntoIter = std::slice::Iter<'a, Instruction>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<&Bytes> for Disassembly {
+    fn from(code: &Bytes) -> Self {
+        Self::new(code)
+    }
+}
+
+/// Splits bytecode into the executable code and the CBOR encoded compiler metadata.
+///
+/// Solc and vyper append the CBOR encoded metadata to the bytecode, followed by its length as a
+/// 2 byte big endian integer. If no well-formed metadata is found, the entire bytecode is returned
+/// as code.
+///
+/// The returned metadata does not include the 2 length bytes.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::bytecode::{split_metadata, BytecodeMetadata};
+///
+/// let code = hex::decode("6080604052a264697066735822122084f2e6b8c1ad3d34e5ad7e0e2e4a02ff05b3a1da5468e44b1b44bdb2e6a8bec364736f6c63430008110033").unwrap();
+/// let (code, metadata) = split_metadata(&code);
+/// assert_eq!(code, &[0x60, 0x80, 0x60, 0x40, 0x52]);
+///
+/// let metadata = BytecodeMetadata::decode(metadata.unwrap()).unwrap();
+/// assert_eq!(metadata.solc.as_deref(), Some("0.8.17"));
+/// ```
+pub fn split_metadata(code: &[u8]) -> (&[u8], Option<&[u8]>) {
+    if code.len() < 2 {
+        return (code, None)
+    }
+    let len = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize;
+    if len == 0 || len + 2 > code.len() {
+        return (code, None)
+    }
+    let start = code.len() - 2 - len;
+    let metadata = &code[start..code.len() - 2];
+    // only accept the trailer if it is a single, complete CBOR item
+    let mut buf = metadata;
+    match cbor::decode(&mut buf) {
+        Ok(cbor::Value::Map(_) | cbor::Value::Array(_)) if buf.is_empty() => {
+            (&code[..start], Some(metadata))
+        }
+        _ => (code, None),
+    }
+}
+
+/// Returns the bytecode with the compiler metadata removed, see [`split_metadata`].
+pub fn strip_metadata(code: &[u8]) -> &[u8] {
+    split_metadata(code).0
+}
+
+/// An error that occurred while decoding [`BytecodeMetadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MetadataError {
+    /// The metadata is not valid CBOR.
+    #[error("invalid CBOR: {0}")]
+    Cbor(&'static str),
+    /// The metadata has unexpected trailing bytes.
+    #[error("unexpected trailing bytes after CBOR item")]
+    TrailingBytes,
+    /// The CBOR item is not a map, or an array ending in a map.
+    #[error("expected a CBOR map")]
+    NotAMap,
+    /// A known key has a value of the wrong type.
+    #[error("invalid value for metadata key `{0}`")]
+    InvalidValue(String),
+}
+
+/// The compiler metadata appended to the bytecode by solc or vyper.
+///
+/// See also <https://docs.soliditylang.org/en/latest/metadata.html#encoding-of-the-metadata-hash-in-the-bytecode>
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BytecodeMetadata {
+    /// The IPFS multihash of the metadata file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipfs: Option<Bytes>,
+    /// The legacy Swarm hash of the metadata file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bzzr0: Option<H256>,
+    /// The Swarm hash of the metadata file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bzzr1: Option<H256>,
+    /// The solc version, e.g. `0.8.17` or `0.8.18-ci.2022.11.11+commit.a5ff5fa2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solc: Option<String>,
+    /// The vyper version, e.g. `0.3.10`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vyper: Option<String>,
+    /// Whether experimental compiler features were used.
+    #[serde(default)]
+    pub experimental: bool,
+}
+
+impl BytecodeMetadata {
+    /// Decodes the CBOR encoded metadata, as returned by [`split_metadata`].
+    ///
+    /// Besides the solc format, this also accepts the vyper formats, where the metadata is either
+    /// a map or an array whose last element is a map.
+    pub fn decode(metadata: &[u8]) -> Result<Self, MetadataError> {
+        let mut buf = metadata;
+        let value = cbor::decode(&mut buf).map_err(MetadataError::Cbor)?;
+        if !buf.is_empty() {
+            return Err(MetadataError::TrailingBytes)
+        }
+        let entries = match value {
+            cbor::Value::Map(entries) => entries,
+            cbor::Value::Array(mut items) => match items.pop() {
+                Some(cbor::Value::Map(entries)) => entries,
+                _ => return Err(MetadataError::NotAMap),
+            },
+            _ => return Err(MetadataError::NotAMap),
+        };
+
+        let mut decoded = Self::default();
+        for (key, value) in entries {
+            let cbor::Value::Text(key) = key else { continue };
+            let invalid = || MetadataError::InvalidValue(key.clone());
+            match key.as_str() {
+                "ipfs" => match value {
+                    cbor::Value::Bytes(hash) => decoded.ipfs = Some(hash.into()),
+                    _ => return Err(invalid()),
+                },
+                "bzzr0" | "bzzr1" => {
+                    let hash = match value {
+                        cbor::Value::Bytes(hash) if hash.len() == 32 => H256::from_slice(&hash),
+                        _ => return Err(invalid()),
+                    };
+                    if key == "bzzr0" {
+                        decoded.bzzr0 = Some(hash)
+                    } else {
+                        decoded.bzzr1 = Some(hash)
+                    }
+                }
+                "solc" => decoded.solc = Some(decode_version(value).ok_or_else(invalid)?),
+                "vyper" => decoded.vyper = Some(decode_version(value).ok_or_else(invalid)?),
+                "experimental" => match value {
+                    cbor::Value::Bool(experimental) => decoded.experimental = experimental,
+                    _ => return Err(invalid()),
+                },
+                _ => {}
+            }
+        }
+        Ok(decoded)
+    }
+
+    /// Returns the IPFS hash as a base58 encoded CIDv0 (`Qm...`), if present.
+    pub fn ipfs_cid(&self) -> Option<String> {
+        self.ipfs.as_ref().map(|hash| base58_encode(hash))
+    }
+}
+
+/// Decodes a compiler version that is either encoded as `[major, minor, patch]` (bytes or array)
+/// or as a full version string.
+fn decode_version(value: cbor::Value) -> Option<String> {
+    let parts = match value {
+        cbor::Value::Text(version) => return Some(version),
+        cbor::Value::Bytes(bytes) => bytes.into_iter().map(u64::from).collect::<Vec<_>>(),
+        cbor::Value::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                cbor::Value::Uint(n) => Some(n),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    match parts[..] {
+        [major, minor, patch] => Some(format!("{major}.{minor}.{patch}")),
+        _ => None,
+    }
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    // little endian base58 digits
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for byte in &bytes[zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    std::iter::repeat('1')
+        .take(zeros)
+        .chain(digits.iter().rev().map(|d| ALPHABET[*d as usize] as char))
+        .collect()
+}
+
+/// A minimal CBOR decoder that supports the subset used by compiler metadata.
+mod cbor {
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(super) enum Value {
+        Uint(u64),
+        Bytes(Vec<u8>),
+        Text(String),
+        Array(Vec<Value>),
+        Map(Vec<(Value, Value)>),
+        Bool(bool),
+        Null,
+    }
+
+    /// Nesting limit, compiler metadata is at most 2 levels deep.
+    const MAX_DEPTH: usize = 8;
+
+    pub(super) fn decode(buf: &mut &[u8]) -> Result<Value, &'static str> {
+        decode_value(buf, 0)
+    }
+
+    fn decode_value(buf: &mut &[u8], depth: usize) -> Result<Value, &'static str> {
+        if depth > MAX_DEPTH {
+            return Err("nesting too deep")
+        }
+        let initial = take(buf, 1)?[0];
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+
+        if major == 7 {
+            return match info {
+                20 => Ok(Value::Bool(false)),
+                21 => Ok(Value::Bool(true)),
+                22 => Ok(Value::Null),
+                _ => Err("unsupported simple value"),
+            }
+        }
+
+        let arg = match info {
+            0..=23 => info as u64,
+            24 => take(buf, 1)?[0] as u64,
+            25 => u16::from_be_bytes(take(buf, 2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(take(buf, 4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(take(buf, 8)?.try_into().unwrap()),
+            _ => return Err("indefinite lengths are not supported"),
+        };
+        // bound lengths by the remaining input so malformed lengths can't trigger huge allocations
+        let len = || {
+            usize::try_from(arg).ok().filter(|len| *len <= buf.len()).ok_or("length out of bounds")
+        };
+
+        match major {
+            0 => Ok(Value::Uint(arg)),
+            2 => Ok(Value::Bytes(take(buf, len()?)?.to_vec())),
+            3 => {
+                let text = take(buf, len()?)?;
+                String::from_utf8(text.to_vec()).map(Value::Text).map_err(|_| "invalid utf-8")
+            }
+            4 => {
+                let len = len()?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(decode_value(buf, depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+            5 => {
+                let len = len()?;
+                let mut entries = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = decode_value(buf, depth + 1)?;
+                    let value = decode_value(buf, depth + 1)?;
+                    entries.push((key, value));
+                }
+                Ok(Value::Map(entries))
+            }
+            _ => Err("unsupported major type"),
+        }
+    }
+
+    fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], &'static str> {
+        if buf.len() < len {
+            return Err("unexpected end of input")
+        }
+        let (head, tail) = buf.split_at(len);
+        *buf = tail;
+        Ok(head)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // solc style runtime code with a two function dispatcher, followed by solc 0.8.17 metadata
+    const RUNTIME: &str = "6080604052348015600f57600080fd5b506004361060325760003560e01c8063c2985578146037578063febb0f7e146037575b600080fd5b00fea2646970667358221220d9ab2ea8e8ec98c05d3b5c6a3d6a8b0fc6a3b80a6a4bb4ad2df6eb1dc4e0a58a64736f6c63430008110033";
+
+    fn runtime() -> Vec<u8> {
+        hex::decode(RUNTIME).unwrap()
+    }
+
+    #[test]
+    fn disassembles() {
+        let disassembly = Disassembly::new([0x60, 0x80, 0x60, 0x40, 0x52, 0x0c, 0x61, 0x01]);
+        let instructions = disassembly.instructions();
+        assert_eq!(instructions.len(), 5);
+        assert_eq!(instructions[0].pc, 0);
+        assert_eq!(instructions[0].opcode, Some(Opcode::PUSH1));
+        assert_eq!(instructions[0].push_data, Some(vec![0x80].into()));
+        assert_eq!(instructions[2].pc, 4);
+        assert_eq!(instructions[2].opcode, Some(Opcode::MSTORE));
+        assert_eq!(instructions[3].opcode, None);
+        assert_eq!(instructions[3].byte, 0x0c);
+        assert!(instructions[4].is_truncated());
+        assert_eq!(instructions[4].push_data, Some(vec![0x01].into()));
+
+        assert_eq!(disassembly.instruction_index(4), Some(2));
+        assert_eq!(disassembly.instruction_index(1), None);
+        assert_eq!(
+            disassembly.to_string(),
+            "0x0000: PUSH1 0x80\n0x0002: PUSH1 0x40\n0x0004: MSTORE\n0x0005: UNKNOWN(0x0c)\n0x0006: PUSH2 0x01\n"
+        );
+    }
+
+    #[test]
+    fn can_split_metadata() {
+        let code = runtime();
+        let (stripped, metadata) = split_metadata(&code);
+        assert_eq!(stripped.len(), code.len() - 0x33 - 2);
+        assert_eq!(*stripped.last().unwrap(), Opcode::INVALID as u8);
+
+        let metadata = BytecodeMetadata::decode(metadata.unwrap()).unwrap();
+        assert_eq!(metadata.solc.as_deref(), Some("0.8.17"));
+        assert_eq!(metadata.ipfs.as_ref().unwrap().len(), 34);
+        assert!(metadata.ipfs_cid().unwrap().starts_with("Qm"));
+        assert!(!metadata.experimental);
+
+        // no metadata
+        let code = [0x60, 0x80, 0x60, 0x40, 0x52];
+        assert_eq!(split_metadata(&code), (&code[..], None));
+    }
+
+    #[test]
+    fn can_decode_vyper_metadata() {
+        // legacy vyper: {"vyper": [0, 3, 1]}
+        let metadata = hex::decode("a165767970657283000301").unwrap();
+        let decoded = BytecodeMetadata::decode(&metadata).unwrap();
+        assert_eq!(decoded.vyper.as_deref(), Some("0.3.1"));
+
+        // vyper >= 0.3.10: [runtime size, data sizes, immutables size, {"vyper": [0, 3, 10]}]
+        let metadata = hex::decode("84190123800aa16576797065728300030a").unwrap();
+        let decoded = BytecodeMetadata::decode(&metadata).unwrap();
+        assert_eq!(decoded.vyper.as_deref(), Some("0.3.10"));
+    }
+
+    #[test]
+    fn can_find_selectors_and_blocks() {
+        let code = runtime();
+        let disassembly = Disassembly::new(strip_metadata(&code));
+        assert_eq!(
+            disassembly.selectors(),
+            vec![[0xc2, 0x98, 0x55, 0x78], [0xfe, 0xbb, 0x0f, 0x7e]]
+        );
+
+        let jumpdests = disassembly.jumpdests();
+        assert_eq!(jumpdests.iter().copied().collect::<Vec<_>>(), vec![0x0f, 0x32, 0x37]);
+
+        let blocks = disassembly.basic_blocks();
+        // every block ends with a block ending instruction or precedes a jumpdest
+        for window in blocks.windows(2) {
+            let last = &disassembly.instructions()[window[0].instructions.end - 1];
+            let next = &disassembly.instructions()[window[1].instructions.start];
+            assert!(last.ends_block() || next.is_jumpdest());
+            assert_eq!(window[0].instructions.end, window[1].instructions.start);
+        }
+        assert!(blocks.iter().any(|b| b.start_pc == 0x37));
+    }
+
+    #[test]
+    fn can_find_immutable_placeholders() {
+        let mut code = vec![0x7f];
+        code.extend([0u8; 32]);
+        code.extend([0x60, 0x00, 0x52, 0x7f]);
+        code.extend([1u8; 32]);
+        let disassembly = Disassembly::new(&code);
+        assert_eq!(disassembly.immutable_placeholders(), vec![1]);
+    }
+
+    #[test]
+    fn rejects_malformed_metadata() {
+        assert!(BytecodeMetadata::decode(&[0xa1]).is_err());
+        assert!(BytecodeMetadata::decode(&[0x01]).is_err());
+        assert!(BytecodeMetadata::decode(&[0xa0, 0x00]).is_err());
+        // huge declared length must not allocate
+        assert!(BytecodeMetadata::decode(&[0x5b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])
+            .is_err());
+    }
+}
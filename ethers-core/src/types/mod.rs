@@ -82,5 +82,11 @@ pub use syncing::{SyncProgress, SyncingStatus};
 mod opcode;
 pub use opcode::Opcode;
 
+pub mod bytecode;
+
+pub mod trie;
+
+pub mod siwe;
+
 mod withdrawal;
 pub use withdrawal::Withdrawal;
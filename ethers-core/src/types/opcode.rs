@@ -379,6 +379,41 @@ impl From<Opcode> for u8 {
     }
 }
 
+impl Opcode {
+    /// Returns the number of immediate bytes that follow this opcode in bytecode.
+    ///
+    /// This is only non-zero for `PUSH1` through `PUSH32`.
+    pub const fn immediate_size(&self) -> usize {
+        let byte = *self as u8;
+        if byte >= Opcode::PUSH1 as u8 && byte <= Opcode::PUSH32 as u8 {
+            (byte - Opcode::PUSH0 as u8) as usize
+        } else {
+            0
+        }
+    }
+
+    /// Returns true if this is one of the `PUSH0` through `PUSH32` opcodes.
+    pub const fn is_push(&self) -> bool {
+        let byte = *self as u8;
+        byte >= Opcode::PUSH0 as u8 && byte <= Opcode::PUSH32 as u8
+    }
+
+    /// Returns true if execution never continues with the next instruction after this opcode.
+    ///
+    /// Note that `JUMPI` is not included since it may fall through.
+    pub const fn is_terminating(&self) -> bool {
+        matches!(
+            self,
+            Opcode::STOP |
+                Opcode::JUMP |
+                Opcode::RETURN |
+                Opcode::REVERT |
+                Opcode::INVALID |
+                Opcode::SELFDESTRUCT
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
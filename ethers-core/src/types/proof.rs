@@ -1,6 +1,12 @@
-use crate::types::{
-    serde_helpers::deserialize_stringified_numeric, Address, Bytes, H256, U256, U64,
+use crate::{
+    types::{
+        serde_helpers::deserialize_stringified_numeric,
+        trie::{ordered_trie_proof, verify_proof, ProofError, EMPTY_ROOT, KECCAK_EMPTY},
+        Address, Bytes, Log, TransactionReceipt, H256, U256, U64,
+    },
+    utils::keccak256,
 };
+use rlp::{Rlp, RlpStream};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -11,6 +17,23 @@ pub struct StorageProof {
     pub value: U256,
 }
 
+impl StorageProof {
+    /// Verifies the proof against the `storage_hash` of the account.
+    ///
+    /// A zero value is proven by showing that the slot is not in the storage trie.
+    pub fn verify(&self, storage_hash: H256) -> Result<(), ProofError> {
+        let mut slot = [0u8; 32];
+        self.key.to_big_endian(&mut slot);
+        let value = verify_proof(storage_hash, &keccak256(slot), &self.proof)?;
+
+        let expected = (!self.value.is_zero()).then(|| rlp::encode(&self.value).to_vec());
+        if value != expected {
+            return Err(ProofError::ValueMismatch)
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EIP1186ProofResponse {
@@ -23,6 +46,147 @@ pub struct EIP1186ProofResponse {
     pub storage_proof: Vec<StorageProof>,
 }
 
+impl EIP1186ProofResponse {
+    /// Verifies the account proof against the `state_root` of a block.
+    ///
+    /// Accounts that do not exist are proven by showing that the address is not in the state
+    /// trie, in which case the response must contain the values of an empty account.
+    pub fn verify_account(&self, state_root: H256) -> Result<(), ProofError> {
+        let value = verify_proof(state_root, &keccak256(self.address), &self.account_proof)?;
+
+        let expected = if self.is_empty_account() {
+            None
+        } else {
+            let mut stream = RlpStream::new_list(4);
+            stream
+                .append(&self.nonce)
+                .append(&self.balance)
+                .append(&self.storage_hash)
+                .append(&self.code_hash);
+            Some(stream.out().to_vec())
+        };
+        if value != expected {
+            return Err(ProofError::ValueMismatch)
+        }
+        Ok(())
+    }
+
+    /// Verifies the account proof against the `state_root` of a block and all storage proofs
+    /// against the proven storage hash of the account.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_core::types::{EIP1186ProofResponse, H256};
+    ///
+    /// # fn foo(proof: EIP1186ProofResponse, state_root: H256) {
+    /// proof.verify(state_root).expect("invalid proof");
+    /// # }
+    /// ```
+    pub fn verify(&self, state_root: H256) -> Result<(), ProofError> {
+        self.verify_account(state_root)?;
+        for proof in &self.storage_proof {
+            proof.verify(self.storage_hash)?;
+        }
+        Ok(())
+    }
+
+    fn is_empty_account(&self) -> bool {
+        self.nonce.is_zero() &&
+            self.balance.is_zero() &&
+            self.storage_hash == EMPTY_ROOT &&
+            self.code_hash == KECCAK_EMPTY
+    }
+}
+
+/// A proof that a receipt, and therefore its logs, is included in the receipts trie of a block.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{ReceiptProof, TransactionReceipt, H256};
+///
+/// # fn foo(receipts: Vec<TransactionReceipt>, receipts_root: H256) {
+/// // `receipts` are all receipts of the block, e.g. from `Middleware::get_block_receipts`
+/// let proof = ReceiptProof::new(&receipts, 3).unwrap();
+/// proof.verify_receipt(receipts_root, &receipts[3]).expect("invalid proof");
+/// proof.verify_log(receipts_root, &receipts[3].logs[0]).expect("invalid proof");
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptProof {
+    /// The index of the transaction in the block.
+    pub transaction_index: U64,
+    /// The consensus encoding of the receipt, see [`TransactionReceipt::rlp`].
+    pub receipt: Bytes,
+    /// The trie nodes on the path from the receipts root to the receipt.
+    pub proof: Vec<Bytes>,
+}
+
+impl ReceiptProof {
+    /// Generates the proof for the receipt at `index` from all receipts of a block, ordered by
+    /// their transaction index.
+    ///
+    /// Returns `None` if `index` is out of bounds.
+    pub fn new(receipts: &[TransactionReceipt], index: usize) -> Option<Self> {
+        let encoded = receipts.iter().map(TransactionReceipt::rlp).collect::<Vec<_>>();
+        let receipt = encoded.get(index)?.clone();
+        let proof = ordered_trie_proof(&encoded, index);
+        Some(Self { transaction_index: (index as u64).into(), receipt, proof })
+    }
+
+    /// Verifies that the encoded receipt is stored at the transaction index in the trie with the
+    /// given `receipts_root`.
+    pub fn verify(&self, receipts_root: H256) -> Result<(), ProofError> {
+        let key = rlp::encode(&self.transaction_index.as_u64());
+        let value = verify_proof(receipts_root, &key, &self.proof)?;
+        if value.as_deref() != Some(self.receipt.as_ref()) {
+            return Err(ProofError::ValueMismatch)
+        }
+        Ok(())
+    }
+
+    /// Verifies the proof and that it is a proof for `receipt`.
+    pub fn verify_receipt(
+        &self,
+        receipts_root: H256,
+        receipt: &TransactionReceipt,
+    ) -> Result<(), ProofError> {
+        if receipt.transaction_index != self.transaction_index {
+            return Err(ProofError::KeyMismatch)
+        }
+        if receipt.rlp() != self.receipt {
+            return Err(ProofError::ValueMismatch)
+        }
+        self.verify(receipts_root)
+    }
+
+    /// Verifies the proof and that `log` was emitted by the transaction of the proven receipt.
+    ///
+    /// Only the address, topics and data of the log are proven.
+    pub fn verify_log(&self, receipts_root: H256, log: &Log) -> Result<(), ProofError> {
+        if log.transaction_index.map_or(false, |index| index != self.transaction_index) {
+            return Err(ProofError::KeyMismatch)
+        }
+        let encoded = rlp::encode(log);
+        if !self.logs()?.iter().any(|item| item.as_raw() == encoded.as_ref()) {
+            return Err(ProofError::ValueMismatch)
+        }
+        self.verify(receipts_root)
+    }
+
+    /// Returns the RLP encoded logs of the receipt
+    fn logs(&self) -> Result<Vec<Rlp<'_>>, ProofError> {
+        // typed receipts are prefixed with the transaction type
+        let receipt = match self.receipt.first() {
+            Some(ty) if *ty < 0x80 => &self.receipt[1..],
+            _ => &self.receipt[..],
+        };
+        Ok(Rlp::new(receipt).at(3)?.iter().collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,6 +205,67 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn can_verify_proofs() {
+        for proof in [
+            include_str!("../../testdata/proof.json"),
+            include_str!("../../testdata/proof_uint_key.json"),
+        ] {
+            let proof: EIP1186ProofResponse = serde_json::from_str(proof).unwrap();
+            let state_root = H256(keccak256(&proof.account_proof[0]));
+            proof.verify(state_root).unwrap();
+
+            let mut wrong = proof.clone();
+            wrong.balance += U256::one();
+            assert_eq!(wrong.verify(state_root), Err(ProofError::ValueMismatch));
+
+            let mut wrong = proof.clone();
+            wrong.address = Address::zero();
+            assert!(wrong.verify(state_root).is_err());
+        }
+    }
+
+    #[test]
+    fn can_verify_receipt_proofs() {
+        let log = Log {
+            address: Address::repeat_byte(1),
+            topics: vec![H256::repeat_byte(2)],
+            data: vec![3u8; 64].into(),
+            transaction_index: Some(5u64.into()),
+            ..Default::default()
+        };
+        let receipts = (0u64..20)
+            .map(|i| TransactionReceipt {
+                transaction_index: i.into(),
+                transaction_type: Some((i % 3).into()),
+                status: Some(1u64.into()),
+                cumulative_gas_used: (21_000 * (i + 1)).into(),
+                logs: if i == 5 { vec![log.clone()] } else { vec![] },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let root = crate::types::trie::ordered_trie_root(receipts.iter().map(|r| r.rlp()));
+
+        for (index, receipt) in receipts.iter().enumerate() {
+            let proof = ReceiptProof::new(&receipts, index).unwrap();
+            proof.verify_receipt(root, receipt).unwrap();
+        }
+        assert_eq!(ReceiptProof::new(&receipts, 20), None);
+
+        let proof = ReceiptProof::new(&receipts, 5).unwrap();
+        proof.verify_log(root, &log).unwrap();
+
+        let mut wrong = log.clone();
+        wrong.data = vec![4u8; 64].into();
+        assert_eq!(proof.verify_log(root, &wrong), Err(ProofError::ValueMismatch));
+        assert_eq!(
+            ReceiptProof::new(&receipts, 4).unwrap().verify_log(root, &log),
+            Err(ProofError::KeyMismatch)
+        );
+        assert_eq!(proof.verify_receipt(root, &receipts[6]), Err(ProofError::KeyMismatch));
+        assert!(proof.verify(H256::zero()).is_err());
+    }
+
     #[test]
     fn can_deserialize_proof_empty_key() {
         serde_json::from_str::<EIP1186ProofResponse>(include_str!(
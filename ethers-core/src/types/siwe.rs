@@ -0,0 +1,563 @@
+//! Sign-In with Ethereum messages, see [EIP-4361](https://eips.ethereum.org/EIPS/eip-4361).
+//!
+//! # Example
+//!
+//! ```
+//! use ethers_core::types::{
+//!     siwe::{SiweMessage, VerificationOpts},
+//!     Address,
+//! };
+//!
+//! let address: Address = "0x6Da01670d8fc844e736095918bbE11fE8D564163".parse().unwrap();
+//! let message = SiweMessage::new("example.com", address, "https://example.com/login", 1)
+//!     .statement("Sign in to Example");
+//!
+//! // the text that is signed with `Signer::sign_message`
+//! let text = message.to_string();
+//! assert_eq!(text.parse::<SiweMessage>().unwrap(), message);
+//!
+//! // the backend checks the domain and the nonce it issued, then the signature
+//! let opts = VerificationOpts::new().domain("example.com").nonce(&message.nonce);
+//! message.validate(&opts).unwrap();
+//! ```
+
+use crate::{
+    types::{Address, Signature, SignatureError, H256},
+    utils::{hash_message, to_checksum},
+};
+use chrono::{DateTime, FixedOffset, Offset, SecondsFormat, TimeZone, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+const PREAMBLE: &str = " wants you to sign in with your Ethereum account:";
+const URI_TAG: &str = "URI: ";
+const VERSION_TAG: &str = "Version: ";
+const CHAIN_ID_TAG: &str = "Chain ID: ";
+const NONCE_TAG: &str = "Nonce: ";
+const ISSUED_AT_TAG: &str = "Issued At: ";
+const EXPIRATION_TIME_TAG: &str = "Expiration Time: ";
+const NOT_BEFORE_TAG: &str = "Not Before: ";
+const REQUEST_ID_TAG: &str = "Request ID: ";
+const RESOURCES_TAG: &str = "Resources:";
+
+/// An error that occurred while parsing, validating or verifying a [`SiweMessage`].
+#[derive(Debug, Error)]
+pub enum SiweError {
+    /// The message does not follow the EIP-4361 format.
+    #[error("malformed SIWE message: {0}")]
+    Malformed(String),
+    /// The message has expired.
+    #[error("message expired at {0}")]
+    Expired(TimeStamp),
+    /// The message is not valid yet.
+    #[error("message is not valid before {0}")]
+    NotYetValid(TimeStamp),
+    /// The domain of the message is not the expected domain.
+    #[error("domain mismatch: expected `{expected}`, got `{actual}`")]
+    DomainMismatch {
+        /// The expected domain
+        expected: String,
+        /// The domain of the message
+        actual: String,
+    },
+    /// The nonce of the message is not the expected nonce.
+    #[error("nonce mismatch: expected `{expected}`, got `{actual}`")]
+    NonceMismatch {
+        /// The expected nonce
+        expected: String,
+        /// The nonce of the message
+        actual: String,
+    },
+    /// The signature is not a signature of the message by its address.
+    #[error(transparent)]
+    InvalidSignature(#[from] SignatureError),
+}
+
+/// An RFC 3339 timestamp, which keeps its original text so that parsed messages are serialized
+/// exactly as they were signed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeStamp {
+    time: DateTime<FixedOffset>,
+    text: String,
+}
+
+impl TimeStamp {
+    /// Returns the current time, in whole seconds.
+    ///
+    /// On `wasm32`, the time is read from the JS `Date`.
+    pub fn now() -> Self {
+        Utc.timestamp_opt(Utc::now().timestamp(), 0).unwrap().into()
+    }
+
+    /// Returns the time.
+    pub fn as_datetime(&self) -> &DateTime<FixedOffset> {
+        &self.time
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for TimeStamp {
+    fn from(time: DateTime<Tz>) -> Self {
+        let time = time.with_timezone(&time.offset().fix());
+        Self { text: time.to_rfc3339_opts(SecondsFormat::AutoSi, true), time }
+    }
+}
+
+impl FromStr for TimeStamp {
+    type Err = SiweError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let time = DateTime::parse_from_rfc3339(s)
+            .map_err(|err| SiweError::Malformed(format!("invalid timestamp `{s}`: {err}")))?;
+        Ok(Self { time, text: s.to_string() })
+    }
+}
+
+impl fmt::Display for TimeStamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// A Sign-In with Ethereum message.
+///
+/// Messages are parsed with [`FromStr`] and serialized to the signed text with [`fmt::Display`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SiweMessage {
+    /// The URI scheme of the origin of the request, e.g. `https`.
+    pub scheme: Option<String>,
+    /// The RFC 3986 authority that requests the signing, e.g. `example.com`.
+    pub domain: String,
+    /// The address that signs the message.
+    pub address: Address,
+    /// A human-readable assertion that the user signs, which must not contain newlines.
+    pub statement: Option<String>,
+    /// The RFC 3986 URI of the resource that is the subject of the signing.
+    pub uri: String,
+    /// The version of the message, which must be `1`.
+    pub version: String,
+    /// The chain ID of the network where contract accounts are resolved.
+    pub chain_id: u64,
+    /// A random string of at least 8 alphanumeric characters to prevent replay attacks.
+    pub nonce: String,
+    /// The time when the message was generated.
+    pub issued_at: TimeStamp,
+    /// The time when the signed authentication message is no longer valid.
+    pub expiration_time: Option<TimeStamp>,
+    /// The time when the signed authentication message becomes valid.
+    pub not_before: Option<TimeStamp>,
+    /// An identifier that may be used to uniquely refer to the sign-in request.
+    pub request_id: Option<String>,
+    /// URIs the user wishes to have resolved as part of the authentication.
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// Creates a message that is issued now, with a random nonce, see [`TimeStamp::now`].
+    pub fn new(
+        domain: impl Into<String>,
+        address: Address,
+        uri: impl Into<String>,
+        chain_id: u64,
+    ) -> Self {
+        Self {
+            scheme: None,
+            domain: domain.into(),
+            address,
+            statement: None,
+            uri: uri.into(),
+            version: "1".to_string(),
+            chain_id,
+            nonce: generate_nonce(),
+            issued_at: TimeStamp::now(),
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        }
+    }
+
+    /// Sets the URI scheme of the origin of the request.
+    #[must_use]
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(scheme.into());
+        self
+    }
+
+    /// Sets the statement, newlines are replaced with spaces.
+    #[must_use]
+    pub fn statement(mut self, statement: impl Into<String>) -> Self {
+        self.statement = Some(statement.into().replace('\n', " "));
+        self
+    }
+
+    /// Sets the nonce, which is usually issued by the backend that verifies the message.
+    #[must_use]
+    pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = nonce.into();
+        self
+    }
+
+    /// Sets the time when the message was generated.
+    #[must_use]
+    pub fn issued_at(mut self, time: impl Into<TimeStamp>) -> Self {
+        self.issued_at = time.into();
+        self
+    }
+
+    /// Sets the time when the message expires.
+    #[must_use]
+    pub fn expiration_time(mut self, time: impl Into<TimeStamp>) -> Self {
+        self.expiration_time = Some(time.into());
+        self
+    }
+
+    /// Sets the time when the message becomes valid.
+    #[must_use]
+    pub fn not_before(mut self, time: impl Into<TimeStamp>) -> Self {
+        self.not_before = Some(time.into());
+        self
+    }
+
+    /// Sets the request ID.
+    #[must_use]
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// Adds a resource.
+    #[must_use]
+    pub fn resource(mut self, resource: impl Into<String>) -> Self {
+        self.resources.push(resource.into());
+        self
+    }
+
+    /// Returns the EIP-191 hash of the message, which is signed by `Signer::sign_message`.
+    ///
+    /// This is the hash to verify signatures of smart contract accounts with EIP-1271.
+    pub fn eip191_hash(&self) -> H256 {
+        hash_message(self.to_string())
+    }
+
+    /// Checks the domain, the nonce and the validity period of the message.
+    pub fn validate(&self, opts: &VerificationOpts) -> Result<(), SiweError> {
+        if let Some(domain) = opts.domain.as_ref().filter(|domain| **domain != self.domain) {
+            return Err(SiweError::DomainMismatch {
+                expected: domain.clone(),
+                actual: self.domain.clone(),
+            })
+        }
+        if let Some(nonce) = opts.nonce.as_ref().filter(|nonce| **nonce != self.nonce) {
+            return Err(SiweError::NonceMismatch {
+                expected: nonce.clone(),
+                actual: self.nonce.clone(),
+            })
+        }
+
+        let time = opts.time.clone().unwrap_or_else(TimeStamp::now);
+        if let Some(expiration_time) = &self.expiration_time {
+            if time.time >= expiration_time.time {
+                return Err(SiweError::Expired(expiration_time.clone()))
+            }
+        }
+        if let Some(not_before) = &self.not_before {
+            if time.time < not_before.time {
+                return Err(SiweError::NotYetValid(not_before.clone()))
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the message with [`Self::validate`] and checks that `signature` is an ECDSA
+    /// signature of the message by its address.
+    pub fn verify(&self, signature: &Signature, opts: &VerificationOpts) -> Result<(), SiweError> {
+        self.validate(opts)?;
+        signature.verify(self.eip191_hash(), self.address)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for SiweMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{scheme}://")?;
+        }
+        writeln!(f, "{}{PREAMBLE}", self.domain)?;
+        writeln!(f, "{}", to_checksum(&self.address, None))?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{statement}")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{URI_TAG}{}", self.uri)?;
+        writeln!(f, "{VERSION_TAG}{}", self.version)?;
+        writeln!(f, "{CHAIN_ID_TAG}{}", self.chain_id)?;
+        writeln!(f, "{NONCE_TAG}{}", self.nonce)?;
+        write!(f, "{ISSUED_AT_TAG}{}", self.issued_at)?;
+        if let Some(expiration_time) = &self.expiration_time {
+            write!(f, "\n{EXPIRATION_TIME_TAG}{expiration_time}")?;
+        }
+        if let Some(not_before) = &self.not_before {
+            write!(f, "\n{NOT_BEFORE_TAG}{not_before}")?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, "\n{REQUEST_ID_TAG}{request_id}")?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\n{RESOURCES_TAG}")?;
+            for resource in &self.resources {
+                write!(f, "\n- {resource}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SiweMessage {
+    type Err = SiweError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = |message: String| SiweError::Malformed(message);
+        let mut lines = s.split('\n').peekable();
+
+        let origin = lines
+            .next()
+            .and_then(|line| line.strip_suffix(PREAMBLE))
+            .ok_or_else(|| malformed("missing preamble".to_string()))?;
+        let (scheme, domain) = match origin.split_once("://") {
+            Some((scheme, domain)) => (Some(scheme.to_string()), domain),
+            None => (None, origin),
+        };
+        if domain.is_empty() || domain.contains(char::is_whitespace) {
+            return Err(malformed(format!("invalid domain `{domain}`")))
+        }
+
+        let address_line = lines.next().ok_or_else(|| malformed("missing address".to_string()))?;
+        let address = address_line
+            .parse::<Address>()
+            .ok()
+            .filter(|address| to_checksum(address, None) == address_line)
+            .ok_or_else(|| malformed(format!("invalid EIP-55 address `{address_line}`")))?;
+        expect_empty_line(&mut lines)?;
+
+        // the statement is optional, messages without one have two empty lines
+        let statement = lines
+            .next_if(|line| !line.is_empty() && !line.starts_with(URI_TAG))
+            .map(str::to_string);
+        if statement.is_some() || lines.peek() == Some(&"") {
+            expect_empty_line(&mut lines)?;
+        }
+
+        let uri = tagged(&mut lines, URI_TAG)?.to_string();
+        let version = tagged(&mut lines, VERSION_TAG)?;
+        if version != "1" {
+            return Err(malformed(format!("unsupported version `{version}`")))
+        }
+        let chain_id = tagged(&mut lines, CHAIN_ID_TAG)?;
+        let chain_id =
+            chain_id.parse().map_err(|_| malformed(format!("invalid chain ID `{chain_id}`")))?;
+        let nonce = tagged(&mut lines, NONCE_TAG)?;
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(malformed(format!("invalid nonce `{nonce}`")))
+        }
+        let issued_at = tagged(&mut lines, ISSUED_AT_TAG)?.parse()?;
+        let expiration_time = optional_tagged(&mut lines, EXPIRATION_TIME_TAG).map(str::parse);
+        let not_before = optional_tagged(&mut lines, NOT_BEFORE_TAG).map(str::parse);
+        let request_id = optional_tagged(&mut lines, REQUEST_ID_TAG).map(str::to_string);
+
+        let mut resources = Vec::new();
+        if lines.next_if_eq(&RESOURCES_TAG).is_some() {
+            while let Some(resource) = lines.peek().and_then(|line| line.strip_prefix("- ")) {
+                resources.push(resource.to_string());
+                lines.next();
+            }
+        }
+        if let Some(line) = lines.next() {
+            return Err(malformed(format!("unexpected line `{line}`")))
+        }
+
+        Ok(Self {
+            scheme,
+            domain: domain.to_string(),
+            address,
+            statement,
+            uri,
+            version: version.to_string(),
+            chain_id,
+            nonce: nonce.to_string(),
+            issued_at,
+            expiration_time: expiration_time.transpose()?,
+            not_before: not_before.transpose()?,
+            request_id,
+            resources,
+        })
+    }
+}
+
+/// The expected values when validating a [`SiweMessage`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationOpts {
+    /// The expected domain, usually the domain of the backend.
+    pub domain: Option<String>,
+    /// The expected nonce, usually the nonce that the backend issued for the session.
+    pub nonce: Option<String>,
+    /// The time to check the validity period against, defaults to now.
+    pub time: Option<TimeStamp>,
+}
+
+impl VerificationOpts {
+    /// Creates options that only check the validity period against the current time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the expected domain.
+    #[must_use]
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets the expected nonce.
+    #[must_use]
+    pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the time to check the validity period against.
+    #[must_use]
+    pub fn time(mut self, time: impl Into<TimeStamp>) -> Self {
+        self.time = Some(time.into());
+        self
+    }
+}
+
+/// Generates a random alphanumeric nonce of 17 characters.
+pub fn generate_nonce() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(17).map(char::from).collect()
+}
+
+type Lines<'a> = std::iter::Peekable<std::str::Split<'a, char>>;
+
+fn expect_empty_line(lines: &mut Lines<'_>) -> Result<(), SiweError> {
+    match lines.next() {
+        Some("") => Ok(()),
+        line => {
+            Err(SiweError::Malformed(format!("expected empty line, got `{}`", line.unwrap_or(""))))
+        }
+    }
+}
+
+fn tagged<'a>(lines: &mut Lines<'a>, tag: &str) -> Result<&'a str, SiweError> {
+    optional_tagged(lines, tag)
+        .ok_or_else(|| SiweError::Malformed(format!("missing `{}`", tag.trim_end_matches(": "))))
+}
+
+fn optional_tagged<'a>(lines: &mut Lines<'a>, tag: &str) -> Option<&'a str> {
+    let value = lines.peek()?.strip_prefix(tag)?;
+    lines.next();
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    const MESSAGE: &str = r#"service.org wants you to sign in with your Ethereum account:
+0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+I accept the ServiceOrg Terms of Service: https://service.org/tos
+
+URI: https://service.org/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Resources:
+- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/
+- https://example.com/my-web2-claim.json"#;
+
+    #[test]
+    fn can_parse_and_serialize() {
+        let message: SiweMessage = MESSAGE.parse().unwrap();
+        assert_eq!(message.domain, "service.org");
+        assert_eq!(message.chain_id, 1);
+        assert_eq!(message.resources.len(), 2);
+        assert_eq!(message.to_string(), MESSAGE);
+
+        let message = message
+            .scheme("https")
+            .expiration_time("2021-10-01T16:25:24.123+02:00".parse::<TimeStamp>().unwrap());
+        let mut message = SiweMessage { statement: None, ..message };
+        message.resources.clear();
+        let text = message.to_string();
+        assert!(text.starts_with("https://service.org wants you"));
+        assert!(text.contains("Cc2\n\n\nURI: "));
+        assert!(text.ends_with("Expiration Time: 2021-10-01T16:25:24.123+02:00"));
+        assert_eq!(text.parse::<SiweMessage>().unwrap(), message);
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        let lowercase = MESSAGE.replace(
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+            "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+        );
+        let err = lowercase.parse::<SiweMessage>().unwrap_err();
+        assert!(err.to_string().contains("invalid EIP-55 address"), "{err}");
+
+        let err =
+            MESSAGE.replace("Nonce: 32891756", "Nonce: 1").parse::<SiweMessage>().unwrap_err();
+        assert_eq!(err.to_string(), "malformed SIWE message: invalid nonce `1`");
+
+        let err = MESSAGE.replace("Version: 1\n", "").parse::<SiweMessage>().unwrap_err();
+        assert_eq!(err.to_string(), "malformed SIWE message: missing `Version`");
+
+        let err = format!("{MESSAGE}\nfoo").parse::<SiweMessage>().unwrap_err();
+        assert_eq!(err.to_string(), "malformed SIWE message: unexpected line `foo`");
+    }
+
+    #[test]
+    fn can_validate_and_verify() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let address = crate::utils::secret_key_to_address(&key);
+        let issued_at: TimeStamp = "2023-01-01T00:00:00Z".parse().unwrap();
+        let message = SiweMessage::new("example.com", address, "https://example.com", 1)
+            .issued_at(issued_at.clone())
+            .not_before(issued_at.clone())
+            .expiration_time("2023-01-02T00:00:00Z".parse::<TimeStamp>().unwrap());
+
+        let opts = VerificationOpts::new()
+            .domain("example.com")
+            .nonce(&message.nonce)
+            .time("2023-01-01T12:00:00Z".parse::<TimeStamp>().unwrap());
+        message.validate(&opts).unwrap();
+
+        let err = message.validate(&opts.clone().domain("evil.com")).unwrap_err();
+        assert!(matches!(err, SiweError::DomainMismatch { .. }));
+        let err = message.validate(&opts.clone().nonce("00000000")).unwrap_err();
+        assert!(matches!(err, SiweError::NonceMismatch { .. }));
+        let err = message
+            .validate(&opts.clone().time(Utc.timestamp_opt(1700000000, 0).unwrap()))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "message expired at 2023-01-02T00:00:00Z");
+        let err =
+            message.validate(&opts.clone().time(Utc.timestamp_opt(0, 0).unwrap())).unwrap_err();
+        assert!(matches!(err, SiweError::NotYetValid(_)));
+
+        let (signature, recovery_id) =
+            key.sign_prehash_recoverable(message.eip191_hash().as_bytes()).unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        let signature = Signature::try_from(bytes.as_slice()).unwrap();
+        message.verify(&signature, &opts).unwrap();
+
+        let other = message.clone().nonce("abcdefgh12");
+        let err =
+            other.verify(&signature, &VerificationOpts { time: opts.time, ..Default::default() });
+        assert!(matches!(err, Err(SiweError::InvalidSignature(_))));
+    }
+}
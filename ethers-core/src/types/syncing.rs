@@ -36,14 +36,25 @@ impl<'de> Deserialize<'de> for SyncingStatus {
             IsFalse(bool),
             /// When client is still syncing past blocks we get IsSyncing information.
             IsSyncing(Box<SyncProgress>),
+            /// Notifications of the `syncing` subscription wrap the sync information.
+            Notification { syncing: bool, status: Option<Box<SyncProgress>> },
         }
 
         match SyncingStatusIntermediate::deserialize(deserializer)? {
-            SyncingStatusIntermediate::IsFalse(false) => Ok(SyncingStatus::IsFalse),
+            SyncingStatusIntermediate::IsFalse(false) |
+            SyncingStatusIntermediate::Notification { syncing: false, .. } => {
+                Ok(SyncingStatus::IsFalse)
+            }
             SyncingStatusIntermediate::IsFalse(true) => Err(serde::de::Error::custom(
                 "eth_syncing returned `true` that is undefined value.",
             )),
-            SyncingStatusIntermediate::IsSyncing(sync) => Ok(SyncingStatus::IsSyncing(sync)),
+            SyncingStatusIntermediate::IsSyncing(sync) |
+            SyncingStatusIntermediate::Notification { syncing: true, status: Some(sync) } => {
+                Ok(SyncingStatus::IsSyncing(sync))
+            }
+            SyncingStatusIntermediate::Notification { syncing: true, status: None } => {
+                Err(serde::de::Error::custom("syncing notification is missing its status."))
+            }
         }
     }
 }
@@ -149,6 +160,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deserialize_sync_notification() {
+        let s = r#"{
+        "syncing": true,
+        "status": {
+            "currentBlock": "0xeaa2b4",
+            "highestBlock": "0xeaa329",
+            "startingBlock": "0xea97ee"
+        }
+    }"#;
+
+        let sync: SyncingStatus = serde_json::from_str(s).unwrap();
+        match sync {
+            SyncingStatus::IsFalse => {
+                panic!("unexpected variant")
+            }
+            SyncingStatus::IsSyncing(progress) => {
+                assert_eq!(progress.current_block, 0xeaa2b4u64.into())
+            }
+        }
+
+        let sync: SyncingStatus = serde_json::from_str(r#"{"syncing": false}"#).unwrap();
+        assert_eq!(sync, SyncingStatus::IsFalse);
+    }
+
     #[test]
     fn deserialize_sync_false() {
         let s = r"false";
@@ -1,18 +1,22 @@
 mod call;
+mod flat_call;
 mod four_byte;
+mod mux;
 mod noop;
 mod pre_state;
 
 pub use self::{
     call::{CallConfig, CallFrame, CallLogFrame},
+    flat_call::{FlatCallConfig, FlatCallFrame},
     four_byte::FourByteFrame,
+    mux::{MuxConfig, MuxFrame},
     noop::NoopFrame,
     pre_state::{AccountState, DiffMode, PreStateConfig, PreStateFrame, PreStateMode},
 };
 use crate::types::{
     serde_helpers::deserialize_stringified_numeric, Address, Bytes, H256, U256, U64,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
 
@@ -63,6 +67,8 @@ pub enum GethTraceFrame {
     FourByteTracer(FourByteFrame),
     CallTracer(CallFrame),
     PreStateTracer(PreStateFrame),
+    FlatCallTracer(FlatCallFrame),
+    MuxTracer(MuxFrame),
 }
 
 impl From<DefaultFrame> for GethTraceFrame {
@@ -95,6 +101,18 @@ impl From<NoopFrame> for GethTraceFrame {
     }
 }
 
+impl From<FlatCallFrame> for GethTraceFrame {
+    fn from(value: FlatCallFrame) -> Self {
+        GethTraceFrame::FlatCallTracer(value)
+    }
+}
+
+impl From<MuxFrame> for GethTraceFrame {
+    fn from(value: MuxFrame) -> Self {
+        GethTraceFrame::MuxTracer(value)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 enum GethTraceResult {
@@ -138,23 +156,60 @@ impl From<Value> for GethTrace {
 /// Available built-in tracers
 ///
 /// See <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers>
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
 pub enum GethDebugBuiltInTracerType {
     #[serde(rename = "4byteTracer")]
     FourByteTracer,
     #[serde(rename = "callTracer")]
     CallTracer,
+    #[serde(rename = "flatCallTracer")]
+    FlatCallTracer,
     #[serde(rename = "prestateTracer")]
     PreStateTracer,
     #[serde(rename = "noopTracer")]
     NoopTracer,
+    #[serde(rename = "muxTracer")]
+    MuxTracer,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum GethDebugBuiltInTracerConfig {
-    CallTracer(CallConfig),
+    MuxTracer(MuxConfig),
+    FlatCallTracer(FlatCallConfig),
     PreStateTracer(PreStateConfig),
+    CallTracer(CallConfig),
+}
+
+impl GethDebugBuiltInTracerConfig {
+    /// Decodes the config of the given tracer.
+    ///
+    /// Returns `None` if the tracer does not take a config. The untagged `Deserialize` impl can not
+    /// tell the configs of all tracers apart, so this should be preferred whenever the tracer is
+    /// known.
+    pub fn from_value(
+        tracer: &GethDebugBuiltInTracerType,
+        config: Value,
+    ) -> Result<Option<Self>, serde_json::Error> {
+        let config = match tracer {
+            GethDebugBuiltInTracerType::CallTracer => {
+                Self::CallTracer(serde_json::from_value(config)?)
+            }
+            GethDebugBuiltInTracerType::PreStateTracer => {
+                Self::PreStateTracer(serde_json::from_value(config)?)
+            }
+            GethDebugBuiltInTracerType::FlatCallTracer => {
+                Self::FlatCallTracer(serde_json::from_value(config)?)
+            }
+            GethDebugBuiltInTracerType::MuxTracer => {
+                Self::MuxTracer(serde_json::from_value(config)?)
+            }
+            GethDebugBuiltInTracerType::FourByteTracer | GethDebugBuiltInTracerType::NoopTracer => {
+                return Ok(None)
+            }
+        };
+        Ok(Some(config))
+    }
 }
 
 /// Available tracers
@@ -183,7 +238,7 @@ pub enum GethDebugTracerConfig {
 /// Bindings for additional `debug_traceTransaction` options
 ///
 /// See <https://geth.ethereum.org/docs/rpc/ns-debug#debug_tracetransaction>
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct GethDebugTracingOptions {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -204,6 +259,95 @@ pub struct GethDebugTracingOptions {
     pub timeout: Option<String>,
 }
 
+impl<'de> Deserialize<'de> for GethDebugTracingOptions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Options {
+            #[serde(default)]
+            disable_storage: Option<bool>,
+            #[serde(default)]
+            disable_stack: Option<bool>,
+            #[serde(default)]
+            enable_memory: Option<bool>,
+            #[serde(default)]
+            enable_return_data: Option<bool>,
+            #[serde(default)]
+            tracer: Option<GethDebugTracerType>,
+            #[serde(default)]
+            tracer_config: Option<Value>,
+            #[serde(default)]
+            timeout: Option<String>,
+        }
+
+        let opts = Options::deserialize(deserializer)?;
+        // the config is decoded according to the tracer, because the untagged
+        // `GethDebugBuiltInTracerConfig` can not tell the configs of all tracers apart
+        let tracer_config = match (&opts.tracer, opts.tracer_config) {
+            (_, None) => None,
+            (Some(GethDebugTracerType::BuiltInTracer(tracer)), Some(config)) => {
+                match GethDebugBuiltInTracerConfig::from_value(tracer, config.clone())
+                    .map_err(D::Error::custom)?
+                {
+                    Some(config) => Some(GethDebugTracerConfig::BuiltInTracer(config)),
+                    None => Some(GethDebugTracerConfig::JsTracer(config)),
+                }
+            }
+            (Some(GethDebugTracerType::JsTracer(_)), Some(config)) => {
+                Some(GethDebugTracerConfig::JsTracer(config))
+            }
+            (None, Some(config)) => Some(serde_json::from_value(config).map_err(D::Error::custom)?),
+        };
+
+        Ok(Self {
+            disable_storage: opts.disable_storage,
+            disable_stack: opts.disable_stack,
+            enable_memory: opts.enable_memory,
+            enable_return_data: opts.enable_return_data,
+            tracer: opts.tracer,
+            tracer_config,
+            timeout: opts.timeout,
+        })
+    }
+}
+
+impl GethDebugTracingOptions {
+    /// Returns the options to run a custom JS tracer with the given config.
+    ///
+    /// The output of the tracer can be deserialized into a typed result with
+    /// `Provider::debug_trace_transaction_as` or `Provider::debug_trace_call_as`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_core::types::GethDebugTracingOptions;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Config {
+    ///     opcode: String,
+    /// }
+    ///
+    /// let code = "{count: 0, step: function(log) { if (log.op.toString() == this.opcode) this.count++ }, setup: function(cfg) { this.opcode = cfg.opcode }, fault: function() {}, result: function() { return {count: this.count} }}";
+    /// let opts = GethDebugTracingOptions::js_tracer(code, &Config { opcode: "SLOAD".to_string() })
+    ///     .unwrap();
+    ///
+    /// // the config is kept as is, even though it could be mistaken for a built-in config
+    /// let json = serde_json::to_string(&opts).unwrap();
+    /// assert_eq!(serde_json::from_str::<GethDebugTracingOptions>(&json).unwrap(), opts);
+    /// ```
+    pub fn js_tracer<C: Serialize>(
+        code: impl Into<String>,
+        config: &C,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            tracer: Some(GethDebugTracerType::JsTracer(code.into())),
+            tracer_config: Some(GethDebugTracerConfig::JsTracer(serde_json::to_value(config)?)),
+            ..Default::default()
+        })
+    }
+}
+
 /// Bindings for block overrides in `debug_traceCall` options
 ///
 /// See <https://github.com/ethereum/go-ethereum/pull/24871>
@@ -74,10 +74,12 @@ mod tests {
                 CallConfig { only_top_call: Some(true), with_log: Some(true) },
             )));
 
+        let json = serde_json::to_string(&opts).unwrap();
         assert_eq!(
-            serde_json::to_string(&opts).unwrap(),
+            json,
             r#"{"disableStorage":false,"tracer":"callTracer","tracerConfig":{"onlyTopCall":true,"withLog":true}}"#
         );
+        assert_eq!(serde_json::from_str::<GethDebugTracingCallOptions>(&json).unwrap(), opts);
     }
 
     #[test]
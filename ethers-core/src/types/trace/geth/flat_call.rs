@@ -0,0 +1,58 @@
+use crate::types::Trace;
+use serde::{Deserialize, Serialize};
+
+// https://github.com/ethereum/go-ethereum/blob/0a2f33946b95989e8ce36e72a88138adceab6a23/eth/tracers/native/call_flat.go#L118
+/// The output of the `flatCallTracer`, which has the format of the Parity `trace_*` namespace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlatCallFrame(pub Vec<Trace>);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlatCallConfig {
+    /// Convert errors to the Parity format, e.g. `Reverted` instead of `execution reverted`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub convert_parity_errors: Option<bool>,
+    /// Include calls to precompiles in the output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_precompiles: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    const DEFAULT: &str = include_str!("./test_data/flat_call_tracer/default.json");
+
+    #[test]
+    fn test_serialize_flat_call_trace() {
+        let mut opts = GethDebugTracingCallOptions::default();
+        opts.tracing_options.tracer =
+            Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::FlatCallTracer));
+        opts.tracing_options.tracer_config = Some(GethDebugTracerConfig::BuiltInTracer(
+            GethDebugBuiltInTracerConfig::FlatCallTracer(FlatCallConfig {
+                convert_parity_errors: Some(true),
+                include_precompiles: None,
+            }),
+        ));
+
+        let json = serde_json::to_string(&opts).unwrap();
+        assert_eq!(
+            json,
+            r#"{"tracer":"flatCallTracer","tracerConfig":{"convertParityErrors":true}}"#
+        );
+        assert_eq!(serde_json::from_str::<GethDebugTracingCallOptions>(&json).unwrap(), opts);
+    }
+
+    #[test]
+    fn test_deserialize_flat_call_trace() {
+        let trace: FlatCallFrame = serde_json::from_str(DEFAULT).unwrap();
+        assert_eq!(trace.0.len(), 3);
+        assert!(matches!(trace.0[1].action, Action::Create(_)));
+        assert_eq!(trace.0[2].result, None);
+        assert_eq!(trace.0[2].error.as_deref(), Some("Reverted"));
+
+        let trace: GethTrace = serde_json::from_str(DEFAULT).unwrap();
+        assert!(matches!(trace, GethTrace::Known(GethTraceFrame::FlatCallTracer(_))));
+    }
+}
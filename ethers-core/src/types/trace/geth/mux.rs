@@ -0,0 +1,179 @@
+use crate::types::{
+    GethDebugBuiltInTracerConfig, GethDebugBuiltInTracerType, GethTraceFrame, PreStateFrame,
+};
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// https://github.com/ethereum/go-ethereum/blob/0a2f33946b95989e8ce36e72a88138adceab6a23/eth/tracers/native/mux.go#L41
+/// The config of the `muxTracer`, which maps each tracer to run to its config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MuxConfig(pub HashMap<GethDebugBuiltInTracerType, Option<GethDebugBuiltInTracerConfig>>);
+
+impl<'de> Deserialize<'de> for MuxConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // the config of each tracer is decoded according to the tracer, because the untagged
+        // `GethDebugBuiltInTracerConfig` can not tell the configs of all tracers apart
+        let configs =
+            HashMap::<GethDebugBuiltInTracerType, Option<Value>>::deserialize(deserializer)?;
+        configs
+            .into_iter()
+            .map(|(tracer, config)| {
+                let config = match config {
+                    Some(config) => GethDebugBuiltInTracerConfig::from_value(&tracer, config)
+                        .map_err(D::Error::custom)?,
+                    None => None,
+                };
+                Ok((tracer, config))
+            })
+            .collect::<Result<_, _>>()
+            .map(MuxConfig)
+    }
+}
+
+impl MuxConfig {
+    /// Adds a tracer to run with the given config
+    pub fn tracer(
+        mut self,
+        tracer: GethDebugBuiltInTracerType,
+        config: impl Into<Option<GethDebugBuiltInTracerConfig>>,
+    ) -> Self {
+        self.0.insert(tracer, config.into());
+        self
+    }
+}
+
+/// The output of the `muxTracer`, which maps each tracer to its output
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MuxFrame(pub HashMap<GethDebugBuiltInTracerType, GethTraceFrame>);
+
+impl<'de> Deserialize<'de> for MuxFrame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // the output of each tracer is decoded according to the tracer, because the untagged
+        // `GethTraceFrame` can not tell the frames of all tracers apart
+        let frames = HashMap::<GethDebugBuiltInTracerType, Value>::deserialize(deserializer)?;
+        frames
+            .into_iter()
+            .map(|(tracer, frame)| {
+                let frame = match tracer {
+                    GethDebugBuiltInTracerType::FourByteTracer => {
+                        serde_json::from_value(frame).map(GethTraceFrame::FourByteTracer)
+                    }
+                    GethDebugBuiltInTracerType::CallTracer => {
+                        serde_json::from_value(frame).map(GethTraceFrame::CallTracer)
+                    }
+                    GethDebugBuiltInTracerType::FlatCallTracer => {
+                        serde_json::from_value(frame).map(GethTraceFrame::FlatCallTracer)
+                    }
+                    GethDebugBuiltInTracerType::PreStateTracer => {
+                        serde_json::from_value::<PreStateFrame>(frame)
+                            .map(GethTraceFrame::PreStateTracer)
+                    }
+                    GethDebugBuiltInTracerType::NoopTracer => {
+                        serde_json::from_value(frame).map(GethTraceFrame::NoopTracer)
+                    }
+                    GethDebugBuiltInTracerType::MuxTracer => {
+                        serde_json::from_value(frame).map(GethTraceFrame::MuxTracer)
+                    }
+                };
+                Ok((tracer, frame.map_err(D::Error::custom)?))
+            })
+            .collect::<Result<_, _>>()
+            .map(MuxFrame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    const DEFAULT: &str = r#"{
+        "4byteTracer": {
+            "0x27dc297e-128": 1,
+            "0x38cc4831-0": 2
+        },
+        "callTracer": {
+            "type": "CALL",
+            "from": "0xb436ba50d378d4bbc8660d312a13df6af6e89dfb",
+            "to": "0x7ccbc69292c7a6d7b538c91f3b283de97906cf30",
+            "value": "0x0",
+            "gas": "0x10738",
+            "gasUsed": "0x9751",
+            "input": "0x63e4bff4"
+        },
+        "noopTracer": {}
+    }"#;
+
+    #[test]
+    fn test_serialize_mux_trace() {
+        let mut opts = GethDebugTracingCallOptions::default();
+        opts.tracing_options.tracer =
+            Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::MuxTracer));
+        opts.tracing_options.tracer_config = Some(GethDebugTracerConfig::BuiltInTracer(
+            GethDebugBuiltInTracerConfig::MuxTracer(MuxConfig::default().tracer(
+                GethDebugBuiltInTracerType::CallTracer,
+                GethDebugBuiltInTracerConfig::CallTracer(CallConfig {
+                    only_top_call: Some(true),
+                    with_log: None,
+                }),
+            )),
+        ));
+
+        let json = serde_json::to_string(&opts).unwrap();
+        assert_eq!(
+            json,
+            r#"{"tracer":"muxTracer","tracerConfig":{"callTracer":{"onlyTopCall":true}}}"#
+        );
+        assert_eq!(serde_json::from_str::<GethDebugTracingCallOptions>(&json).unwrap(), opts);
+    }
+
+    #[test]
+    fn test_deserialize_mux_config() {
+        let opts: GethDebugTracingOptions = serde_json::from_str(
+            r#"{"tracer":"muxTracer","tracerConfig":{"flatCallTracer":{"includePrecompiles":true},"prestateTracer":{"diffMode":true},"4byteTracer":null}}"#,
+        )
+        .unwrap();
+        let config = MuxConfig::default()
+            .tracer(
+                GethDebugBuiltInTracerType::FlatCallTracer,
+                GethDebugBuiltInTracerConfig::FlatCallTracer(FlatCallConfig {
+                    convert_parity_errors: None,
+                    include_precompiles: Some(true),
+                }),
+            )
+            .tracer(
+                GethDebugBuiltInTracerType::PreStateTracer,
+                GethDebugBuiltInTracerConfig::PreStateTracer(PreStateConfig {
+                    diff_mode: Some(true),
+                }),
+            )
+            .tracer(GethDebugBuiltInTracerType::FourByteTracer, None);
+        assert_eq!(
+            opts.tracer_config,
+            Some(GethDebugTracerConfig::BuiltInTracer(GethDebugBuiltInTracerConfig::MuxTracer(
+                config
+            )))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_mux_trace() {
+        let trace: MuxFrame = serde_json::from_str(DEFAULT).unwrap();
+        assert!(matches!(
+            trace.0[&GethDebugBuiltInTracerType::FourByteTracer],
+            GethTraceFrame::FourByteTracer(_)
+        ));
+        assert!(matches!(
+            trace.0[&GethDebugBuiltInTracerType::CallTracer],
+            GethTraceFrame::CallTracer(_)
+        ));
+        assert!(matches!(
+            trace.0[&GethDebugBuiltInTracerType::NoopTracer],
+            GethTraceFrame::NoopTracer(_)
+        ));
+
+        let trace: GethTrace = serde_json::from_str(DEFAULT).unwrap();
+        assert!(matches!(trace, GethTrace::Known(GethTraceFrame::MuxTracer(_))));
+    }
+}
@@ -66,10 +66,12 @@ mod tests {
             GethDebugBuiltInTracerConfig::PreStateTracer(PreStateConfig { diff_mode: Some(true) }),
         ));
 
+        let json = serde_json::to_string(&opts).unwrap();
         assert_eq!(
-            serde_json::to_string(&opts).unwrap(),
+            json,
             r#"{"disableStorage":false,"tracer":"prestateTracer","tracerConfig":{"diffMode":true}}"#
         );
+        assert_eq!(serde_json::from_str::<GethDebugTracingCallOptions>(&json).unwrap(), opts);
     }
 
     #[test]
@@ -1,6 +1,9 @@
 use crate::{
     abi,
-    abi::{HumanReadableParser, ParamType, Token},
+    abi::{
+        struct_def::{FieldType, StructFieldType},
+        HumanReadableParser, ParamType, SolStruct, Token,
+    },
     types::{serde_helpers::StringifiedNumeric, Address, Bytes, U256},
     utils::keccak256,
 };
@@ -44,6 +47,8 @@ pub enum Eip712Error {
     NestedEip712StructNotImplemented,
     #[error("Error from Eip712 struct: {0:?}")]
     Message(String),
+    #[error("Invalid field `{path}`: {message}")]
+    InvalidField { path: String, message: String },
 }
 
 /// Helper methods for computing the typed data hash used in `eth_signTypedData`.
@@ -190,6 +195,21 @@ impl EIP712Domain {
 
         keccak256(encode(&tokens))
     }
+
+    /// Returns the `EIP712Domain` type of the fields that are set.
+    fn types(&self) -> Vec<Eip712DomainType> {
+        [
+            ("name", "string", self.name.is_some()),
+            ("version", "string", self.version.is_some()),
+            ("chainId", "uint256", self.chain_id.is_some()),
+            ("verifyingContract", "address", self.verifying_contract.is_some()),
+            ("salt", "bytes32", self.salt.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, _, is_set)| *is_set)
+        .map(|(name, ty, _)| Eip712DomainType { name: name.to_string(), r#type: ty.to_string() })
+        .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -324,6 +344,78 @@ impl<'de> Deserialize<'de> for TypedData {
 
 // === impl TypedData ===
 
+impl TypedData {
+    /// Creates typed data from Solidity struct definitions and a JSON message of type
+    /// `primary_type`.
+    ///
+    /// The types of `primary_type` and all structs it references are derived from `structs`,
+    /// together with the `EIP712Domain` type of the fields that are set in `domain`. The message is
+    /// checked against the types with [`TypedData::validate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_core::{
+    ///     abi::SolStruct,
+    ///     types::transaction::eip712::{EIP712Domain, Eip712, TypedData},
+    /// };
+    /// use serde_json::json;
+    ///
+    /// let structs = SolStruct::parse_all(
+    ///     "struct Person { string name; address wallet; }
+    ///      struct Mail { Person from; Person to; string contents; }",
+    /// )
+    /// .unwrap();
+    /// let domain = EIP712Domain {
+    ///     name: Some("Ether Mail".to_string()),
+    ///     version: Some("1".to_string()),
+    ///     chain_id: Some(1u64.into()),
+    ///     verifying_contract: Some("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC".parse().unwrap()),
+    ///     salt: None,
+    /// };
+    /// let message = json!({
+    ///     "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+    ///     "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+    ///     "contents": "Hello, Bob!"
+    /// });
+    /// let typed_data = TypedData::from_structs(domain, &structs, "Mail", message).unwrap();
+    /// assert_eq!(
+    ///     hex::encode(typed_data.encode_eip712().unwrap()),
+    ///     "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+    /// );
+    /// ```
+    pub fn from_structs(
+        domain: EIP712Domain,
+        structs: &[SolStruct],
+        primary_type: impl Into<String>,
+        message: serde_json::Value,
+    ) -> Result<Self, Eip712Error> {
+        let primary_type = primary_type.into();
+        let mut types = types_from_structs(&primary_type, structs)?;
+        types.insert("EIP712Domain".to_string(), domain.types());
+
+        let serde_json::Value::Object(message) = message else {
+            return Err(Eip712Error::Message(format!(
+                "Expected `{primary_type}` object, got `{message}`"
+            )))
+        };
+        let typed_data =
+            TypedData { domain, types, primary_type, message: message.into_iter().collect() };
+        typed_data.validate()?;
+        Ok(typed_data)
+    }
+
+    /// Checks that the message matches the types.
+    ///
+    /// Every field of a struct must be set and unknown fields are rejected, except for missing
+    /// fields of a struct type, which are encoded as zero. Errors name the path of the invalid
+    /// field, e.g. `offer[1].token`.
+    pub fn validate(&self) -> Result<(), Eip712Error> {
+        let message = serde_json::Value::Object(serde_json::Map::from_iter(self.message.clone()));
+        validate_struct(&self.types, &self.primary_type, &message, "")
+    }
+}
+
 impl Eip712 for TypedData {
     type Error = Eip712Error;
 
@@ -461,6 +553,137 @@ pub fn encode_type(primary_type: &str, types: &Types) -> Result<String, Eip712Er
     Ok(res)
 }
 
+/// Returns the types of `primary_type` and of all structs it references, derived from Solidity
+/// struct definitions.
+///
+/// Struct fields that are tuples or mappings can not be encoded and are rejected.
+pub fn types_from_structs(primary_type: &str, structs: &[SolStruct]) -> Result<Types, Eip712Error> {
+    let mut types = Types::new();
+    let mut pending = vec![primary_type.to_string()];
+    while let Some(name) = pending.pop() {
+        if types.contains_key(&name) {
+            continue
+        }
+        let def = structs.iter().find(|s| s.name() == name).ok_or_else(|| {
+            Eip712Error::Message(format!("No struct definition found for: `{name}`"))
+        })?;
+
+        let mut fields = Vec::with_capacity(def.fields().len());
+        for field in def.fields() {
+            let invalid = |message: String| Eip712Error::InvalidField {
+                path: format!("{name}.{}", field.name()),
+                message,
+            };
+            let ty = match field.r#type() {
+                FieldType::Elementary(ty) => {
+                    let ty = ty.to_string();
+                    if ty.contains('(') {
+                        return Err(invalid(format!("tuple type `{ty}` is not supported")))
+                    }
+                    ty
+                }
+                FieldType::Struct(ty) => {
+                    pending.push(ty.name().to_string());
+                    struct_field_type(ty)
+                }
+                FieldType::Mapping(_) => return Err(invalid("mappings are not supported".into())),
+            };
+            fields.push(Eip712DomainType { name: field.name().to_string(), r#type: ty });
+        }
+        types.insert(name, fields);
+    }
+    Ok(types)
+}
+
+/// Returns the EIP-712 type of a struct field, e.g. `Person[]`
+fn struct_field_type(ty: &StructFieldType) -> String {
+    match ty {
+        StructFieldType::Type(ty) => ty.name().to_string(),
+        StructFieldType::Array(ty) => format!("{}[]", struct_field_type(ty)),
+        StructFieldType::FixedArray(ty, size) => format!("{}[{size}]", struct_field_type(ty)),
+    }
+}
+
+/// Checks that `value` is an object of type `primary_type`, `path` is the path of the value
+fn validate_struct(
+    types: &Types,
+    primary_type: &str,
+    value: &serde_json::Value,
+    path: &str,
+) -> Result<(), Eip712Error> {
+    let invalid = |path: &str, message: String| Eip712Error::InvalidField {
+        path: if path.is_empty() { primary_type.to_string() } else { path.to_string() },
+        message,
+    };
+    let fields = types
+        .get(primary_type)
+        .ok_or_else(|| invalid(path, format!("no type definition found for `{primary_type}`")))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| invalid(path, format!("expected `{primary_type}` object, got `{value}`")))?;
+
+    let field_path = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{path}.{name}")
+        }
+    };
+    if let Some(name) = object.keys().find(|name| fields.iter().all(|field| &field.name != *name)) {
+        return Err(invalid(&field_path(name), format!("unknown field of `{primary_type}`")))
+    }
+    for field in fields {
+        let path = field_path(&field.name);
+        match object.get(&field.name) {
+            Some(value) => validate_field(types, &field.r#type, value, &path)?,
+            // missing struct fields are encoded as zero, see `encode_data`
+            None if types.contains_key(&field.r#type) => {}
+            None => return Err(invalid(&path, "missing field".to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `value` is of type `field_type`, `path` is the path of the value
+fn validate_field(
+    types: &Types,
+    field_type: &str,
+    value: &serde_json::Value,
+    path: &str,
+) -> Result<(), Eip712Error> {
+    let invalid = |message: String| Eip712Error::InvalidField { path: path.to_string(), message };
+    if types.contains_key(field_type) {
+        return validate_struct(types, field_type, value, path)
+    }
+    if let Some((item_type, size)) = field_type.strip_suffix(']').and_then(|ty| ty.rsplit_once('['))
+    {
+        let items =
+            value.as_array().ok_or_else(|| invalid(format!("expected array, got `{value}`")))?;
+        if !size.is_empty() && size.parse::<usize>().ok() != Some(items.len()) {
+            return Err(invalid(format!("expected {size} items, got {}", items.len())))
+        }
+        return items.iter().enumerate().try_for_each(|(i, item)| {
+            validate_field(types, item_type, item, &format!("{path}[{i}]"))
+        })
+    }
+
+    encode_field(types, path, field_type, value).map_err(|err| match err {
+        Eip712Error::Message(message) => invalid(message),
+        Eip712Error::SerdeJsonError(err) => {
+            invalid(format!("invalid `{field_type}` value `{value}`: {err}"))
+        }
+        err => invalid(format!("invalid `{field_type}` value `{value}`: {err}")),
+    })?;
+    if let Ok(ParamType::FixedBytes(size)) = HumanReadableParser::parse_type(field_type) {
+        let bytes: Bytes = serde_json::from_value(value.clone())
+            .map_err(|err| invalid(format!("invalid `{field_type}` value `{value}`: {err}")))?;
+        if bytes.len() != size {
+            return Err(invalid(format!("expected {size} bytes, got {}", bytes.len())))
+        }
+    }
+    Ok(())
+}
+
 /// Returns all the custom types used in the `primary_type`
 fn find_type_dependencies<'a>(
     primary_type: &'a str,
@@ -941,4 +1164,101 @@ mod tests {
             hex::encode(&hash[..])
         );
     }
+
+    #[test]
+    fn test_typed_data_from_structs() {
+        let structs = SolStruct::parse_all(
+            r#"
+            // the recipient
+            struct Person {
+                string name;
+                address[] wallet;
+            }
+            struct Mail { Person from; Person[] to; string contents; }
+            "#,
+        )
+        .unwrap();
+        let message = serde_json::json!({"from":{"name":"Cow","wallet":["0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826","0xDD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"]},"to":[{"name":"Bob","wallet":["0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"]}],"contents":"Hello, Bob!"});
+
+        let typed_data =
+            TypedData::from_structs(EIP712Domain::default(), &structs, "Mail", message).unwrap();
+        assert_eq!(
+            encode_type("Mail", &typed_data.types).unwrap(),
+            "Mail(Person from,Person[] to,string contents)Person(string name,address[] wallet)"
+        );
+        assert_eq!(
+            "80a3aeb51161cfc47884ddf8eac0d2343d6ae640efe78b6a69be65e3045c1321",
+            hex::encode(typed_data.encode_eip712().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_typed_data_from_structs_errors() {
+        let structs = SolStruct::parse_all(
+            "struct Item { address token; bytes32 salt; uint8[2] amounts; }
+             struct Order { Item[] offer; mapping(address => uint256) balances; }
+             struct Fill { Item[] items; Item extra; }",
+        )
+        .unwrap();
+        let domain = EIP712Domain::default();
+
+        let err = TypedData::from_structs(domain.clone(), &structs, "Order", Default::default())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid field `Order.balances`: mappings are not supported");
+
+        let item = serde_json::json!({
+            "token": "0x0000000000000000000000000000000000000001",
+            "salt": format!("0x{}", "00".repeat(32)),
+            "amounts": [1, 2],
+        });
+        let fill = |items: serde_json::Value| serde_json::json!({ "items": items });
+        assert!(TypedData::from_structs(
+            domain.clone(),
+            &structs,
+            "Fill",
+            fill(serde_json::json!([item]))
+        )
+        .is_ok());
+
+        let mut invalid = item.clone();
+        invalid["token"] = "0x01".into();
+        let err = TypedData::from_structs(
+            domain.clone(),
+            &structs,
+            "Fill",
+            fill(serde_json::json!([item, invalid])),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("Invalid field `items[1].token`: invalid `address`"));
+
+        let mut invalid = item.clone();
+        invalid["amounts"] = serde_json::json!([1]);
+        let err = TypedData::from_structs(
+            domain.clone(),
+            &structs,
+            "Fill",
+            fill(serde_json::json!([invalid])),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid field `items[0].amounts`: expected 2 items, got 1");
+
+        let mut invalid = item.clone();
+        invalid["salt"] = 1.into();
+        let err = TypedData::from_structs(
+            domain.clone(),
+            &structs,
+            "Fill",
+            fill(serde_json::json!([invalid])),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("Invalid field `items[0].salt`: invalid `bytes32`"));
+
+        let mut invalid = item.clone();
+        invalid["salt"] = "0x01".into();
+        invalid["extra"] = true.into();
+        let err =
+            TypedData::from_structs(domain, &structs, "Fill", fill(serde_json::json!([invalid])))
+                .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid field `items[0].extra`: unknown field of `Item`");
+    }
 }
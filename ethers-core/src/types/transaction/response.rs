@@ -511,7 +511,22 @@ pub struct TransactionReceipt {
     pub effective_gas_price: Option<U256>,
     /// Deposit nonce for Optimism deposited transactions
     #[cfg(feature = "optimism")]
+    #[serde(
+        rename = "depositNonce",
+        default,
+        deserialize_with = "crate::types::serde_helpers::deserialize_stringified_u64_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deposit_nonce: Option<u64>,
+    /// Deposit receipt version for Optimism deposited transactions, set since the Canyon upgrade
+    #[cfg(feature = "optimism")]
+    #[serde(
+        rename = "depositReceiptVersion",
+        default,
+        deserialize_with = "crate::types::serde_helpers::deserialize_stringified_u64_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deposit_receipt_version: Option<u64>,
     /// L1 fee for the transaction
     #[cfg(feature = "optimism")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -534,6 +549,48 @@ pub struct TransactionReceipt {
     pub other: crate::types::OtherFields,
 }
 
+impl TransactionReceipt {
+    /// Returns the consensus encoding of the receipt, as stored in the receipts trie of a block.
+    ///
+    /// Unlike the [`rlp::Encodable`] implementation, receipts of typed transactions are prefixed
+    /// with the transaction type as defined in [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718),
+    /// and receipts from before the Byzantium fork encode the intermediate state root instead of
+    /// the status. Optimism deposit receipts additionally encode the deposit nonce and the deposit
+    /// receipt version, if set.
+    pub fn rlp(&self) -> Bytes {
+        let mut rlp = RlpStream::new();
+        rlp.begin_unbounded_list();
+        match (self.status, self.root) {
+            (Some(status), _) => rlp.append(&status),
+            (None, Some(root)) => rlp.append(&root),
+            (None, None) => rlp.append_empty_data(),
+        };
+        rlp.append(&self.cumulative_gas_used);
+        rlp.append(&self.logs_bloom);
+        rlp.append_list(&self.logs);
+        #[cfg(feature = "optimism")]
+        if self.transaction_type == Some(U64::from(0x7E)) {
+            if let Some(deposit_nonce) = self.deposit_nonce {
+                rlp.append(&deposit_nonce);
+                if let Some(version) = self.deposit_receipt_version {
+                    rlp.append(&version);
+                }
+            }
+        }
+        rlp.finalize_unbounded_list();
+
+        let rlp_bytes = rlp.out();
+        match self.transaction_type {
+            Some(ty) if !ty.is_zero() => {
+                let mut encoded = vec![ty.as_u64() as u8];
+                encoded.extend_from_slice(&rlp_bytes);
+                encoded.into()
+            }
+            _ => rlp_bytes.freeze().into(),
+        }
+    }
+}
+
 impl rlp::Encodable for TransactionReceipt {
     fn rlp_append(&self, s: &mut RlpStream) {
         #[cfg(feature = "optimism")]
@@ -612,6 +669,7 @@ mod tests {
             transaction_type: Some(U64::from(0x7E)),
             effective_gas_price: None,
             deposit_nonce: Some(4012991),
+            deposit_receipt_version: None,
             l1_fee: None,
             l1_fee_scalar: None,
             l1_gas_price: None,
@@ -620,6 +678,38 @@ mod tests {
             other: crate::types::OtherFields::default(),
         };
         assert_eq!(expected.rlp_bytes().to_vec(), data);
+        assert_eq!(expected.rlp().to_vec(), data);
+    }
+
+    #[test]
+    fn encode_deposit_receipt_canyon() {
+        let receipt: TransactionReceipt = serde_json::from_value(serde_json::json!({
+            "transactionHash": H256::zero(),
+            "transactionIndex": "0x0",
+            "blockHash": null,
+            "blockNumber": null,
+            "from": Address::zero(),
+            "to": null,
+            "cumulativeGasUsed": "0xb741",
+            "gasUsed": null,
+            "contractAddress": null,
+            "logs": [],
+            "status": "0x1",
+            "logsBloom": Bloom::default(),
+            "type": "0x7e",
+            "depositNonce": "0x3d3bbf",
+            "depositReceiptVersion": "0x1"
+        }))
+        .unwrap();
+        assert_eq!(receipt.deposit_nonce, Some(4012991));
+        assert_eq!(receipt.deposit_receipt_version, Some(1));
+
+        let encoded = receipt.rlp();
+        assert_eq!(encoded[0], 0x7E);
+        let rlp = rlp::Rlp::new(&encoded[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 6);
+        assert_eq!(rlp.val_at::<u64>(4).unwrap(), 4012991);
+        assert_eq!(rlp.val_at::<u64>(5).unwrap(), 1);
     }
 
     #[test]
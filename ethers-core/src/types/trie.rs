@@ -0,0 +1,530 @@
+//! Merkle Patricia Trie proof verification.
+//!
+//! Ethereum stores accounts, contract storage, transactions and receipts in [Merkle Patricia
+//! Tries](https://ethereum.org/en/developers/docs/data-structures-and-encoding/patricia-merkle-trie/).
+//! A proof for a key is the list of RLP encoded trie nodes on the path from the root to the key,
+//! as returned by `eth_getProof`. [`verify_proof`] checks such a proof against a trusted root and
+//! returns the proven value, or `None` if the proof shows that the key is not in the trie.
+//!
+//! [`trie_root`] and [`ordered_trie_root`] compute the root of a trie from its contents, for
+//! example to check the transactions, receipts and withdrawals roots of a block, and
+//! [`trie_proof`] and [`ordered_trie_proof`] generate proofs for keys of such a trie.
+
+use crate::{
+    types::{Bytes, H256},
+    utils::keccak256,
+};
+use bytes::BytesMut;
+use rlp::{DecoderError, Rlp, RlpStream};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// The root of an empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// The hash of empty code, `keccak256("")`.
+pub const KECCAK_EMPTY: H256 = H256([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
+/// An error that occurred while verifying a Merkle Patricia Trie proof.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ProofError {
+    /// A node of the proof could not be decoded.
+    #[error("invalid trie node: {0}")]
+    Rlp(#[from] DecoderError),
+    /// A node of the proof is neither a branch, extension nor leaf node.
+    #[error("invalid trie node")]
+    InvalidNode,
+    /// The hash of a node does not match the reference in its parent node.
+    #[error("node hash mismatch: expected {expected:?}, got {actual:?}")]
+    HashMismatch {
+        /// The hash referenced by the parent node, or the root
+        expected: H256,
+        /// The hash of the node in the proof
+        actual: H256,
+    },
+    /// The proof ends before the key is reached.
+    #[error("proof is missing the node {0:?}")]
+    MissingNode(H256),
+    /// The proof contains more nodes than needed to reach the key.
+    #[error("proof contains unused nodes")]
+    UnusedNodes,
+    /// The proven value does not match the expected value.
+    #[error("proven value does not match the expected value")]
+    ValueMismatch,
+    /// The key of a proof does not match the requested key.
+    #[error("proof is for a different key")]
+    KeyMismatch,
+}
+
+/// A reference to a child node, which is either the hash of the node or the node itself if its
+/// encoding is shorter than 32 bytes
+enum NodeRef<'a> {
+    Hash(H256),
+    Inline(&'a [u8]),
+}
+
+impl<'a> NodeRef<'a> {
+    /// Decodes the reference, returns `None` for an empty reference
+    fn decode(rlp: Rlp<'a>) -> Result<Option<Self>, ProofError> {
+        if rlp.is_list() {
+            return Ok(Some(NodeRef::Inline(rlp.as_raw())))
+        }
+        match rlp.data()? {
+            [] => Ok(None),
+            hash if hash.len() == 32 => Ok(Some(NodeRef::Hash(H256::from_slice(hash)))),
+            _ => Err(ProofError::InvalidNode),
+        }
+    }
+}
+
+/// Verifies a proof for `path` against the trie `root`.
+///
+/// Returns the value stored at `path`, or `None` if the proof shows that `path` is not in the
+/// trie. Note that the state and storage tries are keyed by the keccak256 hash of the address or
+/// storage slot, so `path` must be the hashed key for these tries.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::{
+///     types::trie::{verify_proof, EMPTY_ROOT},
+///     utils::keccak256,
+/// };
+///
+/// // every key is absent from the empty trie
+/// let proof: Vec<Vec<u8>> = vec![];
+/// assert_eq!(verify_proof(EMPTY_ROOT, &keccak256([1u8; 20]), &proof), Ok(None));
+/// ```
+pub fn verify_proof<T: AsRef<[u8]>>(
+    root: H256,
+    path: &[u8],
+    proof: &[T],
+) -> Result<Option<Vec<u8>>, ProofError> {
+    let mut nodes = proof.iter().map(AsRef::as_ref);
+
+    if root == EMPTY_ROOT {
+        // some clients return the encoding of the empty root node as the proof
+        return match (nodes.next(), nodes.next()) {
+            (None, _) | (Some([0x80]), None) => Ok(None),
+            _ => Err(ProofError::UnusedNodes),
+        }
+    }
+
+    let nibbles = to_nibbles(path);
+    let mut nibbles = &nibbles[..];
+    let mut next = NodeRef::Hash(root);
+
+    let value = loop {
+        let node = match next {
+            NodeRef::Hash(expected) => {
+                let node = nodes.next().ok_or(ProofError::MissingNode(expected))?;
+                let actual = H256(keccak256(node));
+                if actual != expected {
+                    return Err(ProofError::HashMismatch { expected, actual })
+                }
+                node
+            }
+            NodeRef::Inline(node) => node,
+        };
+
+        let node = Rlp::new(node);
+        let child = match node.item_count()? {
+            17 => {
+                let Some((nibble, rest)) = nibbles.split_first() else {
+                    break Some(node.at(16)?.data()?)
+                };
+                nibbles = rest;
+                node.at(*nibble as usize)?
+            }
+            2 => {
+                let (key, is_leaf) = decode_path(node.at(0)?.data()?)?;
+                if is_leaf {
+                    break (nibbles == key).then_some(node.at(1)?.data()?)
+                }
+                if !nibbles.starts_with(&key) {
+                    break None
+                }
+                nibbles = &nibbles[key.len()..];
+                node.at(1)?
+            }
+            _ => return Err(ProofError::InvalidNode),
+        };
+
+        match NodeRef::decode(child)? {
+            Some(child) => next = child,
+            None => break None,
+        }
+    };
+
+    if nodes.next().is_some() {
+        return Err(ProofError::UnusedNodes)
+    }
+
+    Ok(value.filter(|value| !value.is_empty()).map(<[u8]>::to_vec))
+}
+
+/// Computes the root of the trie that contains the given key value pairs.
+///
+/// Entries with an empty value are not stored in the trie, and if a key occurs more than once the
+/// last value is used.
+pub fn trie_root<I, K, V>(entries: I) -> H256
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let entries = sorted_entries(entries);
+    if entries.is_empty() {
+        return EMPTY_ROOT
+    }
+    H256(keccak256(encode_node(&entries, 0, None)))
+}
+
+/// Computes the root of the trie that maps the RLP encoded index of each value to the value, like
+/// the transactions, receipts and withdrawals tries of a block.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::trie::{ordered_trie_root, EMPTY_ROOT};
+///
+/// let values: Vec<Vec<u8>> = vec![];
+/// assert_eq!(ordered_trie_root(values), EMPTY_ROOT);
+/// ```
+pub fn ordered_trie_root<I, V>(values: I) -> H256
+where
+    I: IntoIterator<Item = V>,
+    V: AsRef<[u8]>,
+{
+    trie_root(ordered_entries(values))
+}
+
+/// Generates a proof for `key` in the trie that contains the given key value pairs.
+///
+/// The proof can be checked with [`verify_proof`] against the [`trie_root`] of the entries. If
+/// `key` is not in the trie, the proof shows its absence.
+pub fn trie_proof<I, K, V>(entries: I, key: &[u8]) -> Vec<Bytes>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let entries = sorted_entries(entries);
+    if entries.is_empty() {
+        return Vec::new()
+    }
+
+    let key = to_nibbles(key);
+    let mut proof = ProofCollector { key: &key, nodes: Vec::new() };
+    encode_node(&entries, 0, Some(&mut proof));
+
+    // nodes are collected bottom up, and only the root and hashed nodes are part of the proof
+    // since shorter nodes are embedded in their parent
+    let mut nodes = proof.nodes;
+    let root = nodes.pop().expect("root node is always on the path");
+    std::iter::once(root)
+        .chain(nodes.into_iter().rev().filter(|node| node.len() >= 32))
+        .map(Into::into)
+        .collect()
+}
+
+/// Generates a proof for the value at `index` in the trie that maps the RLP encoded index of each
+/// value to the value.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::trie::{ordered_trie_proof, ordered_trie_root, verify_proof};
+///
+/// let values = vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()];
+/// let root = ordered_trie_root(&values);
+/// let proof = ordered_trie_proof(&values, 1);
+///
+/// let key = rlp::encode(&1u64);
+/// assert_eq!(verify_proof(root, &key, &proof), Ok(Some(b"b".to_vec())));
+/// ```
+pub fn ordered_trie_proof<I, V>(values: I, index: usize) -> Vec<Bytes>
+where
+    I: IntoIterator<Item = V>,
+    V: AsRef<[u8]>,
+{
+    trie_proof(ordered_entries(values), &rlp::encode(&(index as u64)))
+}
+
+/// Converts the keys to nibbles, sorts the entries by key and removes empty values
+fn sorted_entries<I, K, V>(entries: I) -> Vec<(Vec<u8>, V)>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    entries
+        .into_iter()
+        .map(|(key, value)| (to_nibbles(key.as_ref()), value))
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .filter(|(_, value)| !value.as_ref().is_empty())
+        .collect()
+}
+
+/// Keys the values by their RLP encoded index
+fn ordered_entries<I, V>(values: I) -> impl Iterator<Item = (BytesMut, V)>
+where
+    I: IntoIterator<Item = V>,
+{
+    values.into_iter().enumerate().map(|(idx, value)| (rlp::encode(&(idx as u64)), value))
+}
+
+/// Collects the encoded nodes on the path to a key while a trie is encoded
+struct ProofCollector<'a> {
+    key: &'a [u8],
+    nodes: Vec<Vec<u8>>,
+}
+
+impl ProofCollector<'_> {
+    /// Whether the nodes below the first `depth` nibbles and then `nibbles` are on the path
+    fn follows(&self, depth: usize, nibbles: &[u8]) -> bool {
+        self.key.get(depth..).map_or(false, |key| key.starts_with(nibbles))
+    }
+}
+
+/// Encodes the node that holds all `entries`, whose keys share the first `depth` nibbles.
+///
+/// The entries must be sorted by key and must not be empty. If `proof` is set, the node is on the
+/// path to the key of the proof and it is collected together with the nodes below it on the path.
+fn encode_node<V: AsRef<[u8]>>(
+    entries: &[(Vec<u8>, V)],
+    depth: usize,
+    mut proof: Option<&mut ProofCollector<'_>>,
+) -> Vec<u8> {
+    let node = if let [(key, value)] = entries {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encode_path(&key[depth..], true)).append(&value.as_ref());
+        stream.out().to_vec()
+    } else {
+        // since the keys are sorted, the common prefix of all keys is the common prefix of the
+        // first and the last key
+        let first = &entries[0].0[depth..];
+        let last = &entries[entries.len() - 1].0[depth..];
+        let prefix = first.iter().zip(last).take_while(|(a, b)| a == b).count();
+        if prefix > 0 {
+            let path = proof.as_deref_mut().filter(|p| p.follows(depth, &first[..prefix]));
+            let child = encode_node(entries, depth + prefix, path);
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&encode_path(&first[..prefix], false));
+            append_child(&mut stream, &child);
+            stream.out().to_vec()
+        } else {
+            encode_branch(entries, depth, proof.as_deref_mut())
+        }
+    };
+
+    if let Some(proof) = proof {
+        proof.nodes.push(node.clone());
+    }
+    node
+}
+
+/// Encodes a branch node for `entries`, whose keys diverge after the first `depth` nibbles
+fn encode_branch<V: AsRef<[u8]>>(
+    entries: &[(Vec<u8>, V)],
+    depth: usize,
+    mut proof: Option<&mut ProofCollector<'_>>,
+) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(17);
+    let (value, mut rest) = match entries.split_first() {
+        Some(((key, value), rest)) if key.len() == depth => (Some(value.as_ref()), rest),
+        _ => (None, entries),
+    };
+    for nibble in 0..16 {
+        let end = rest.iter().position(|(key, _)| key[depth] != nibble).unwrap_or(rest.len());
+        let (children, tail) = rest.split_at(end);
+        rest = tail;
+        if children.is_empty() {
+            stream.append_empty_data();
+        } else {
+            let path = proof.as_deref_mut().filter(|p| p.follows(depth, &[nibble]));
+            append_child(&mut stream, &encode_node(children, depth + 1, path));
+        }
+    }
+    match value {
+        Some(value) => stream.append(&value),
+        None => stream.append_empty_data(),
+    };
+    stream.out().to_vec()
+}
+
+/// Appends a reference to a child node, nodes shorter than 32 bytes are embedded
+fn append_child(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&H256(keccak256(node)));
+    }
+}
+
+/// Hex-prefix encodes a path of nibbles
+fn encode_path(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag);
+        nibbles
+    };
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
+
+/// Splits bytes into nibbles, high nibble first
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decodes a hex-prefix encoded path into its nibbles and whether it belongs to a leaf node
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), ProofError> {
+    let (first, rest) = encoded.split_first().ok_or(ProofError::InvalidNode)?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(ProofError::InvalidNode)
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    // odd length paths store the first nibble in the prefix byte
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(to_nibbles(rest));
+    Ok((nibbles, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlp::RlpStream;
+
+    fn leaf(path: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![0x20];
+        encoded.extend_from_slice(path);
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encoded).append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    #[test]
+    fn can_verify_single_leaf() {
+        let key = keccak256(b"key");
+        let node = leaf(&key, b"value");
+        let root = H256(keccak256(&node));
+
+        assert_eq!(verify_proof(root, &key, &[&node]), Ok(Some(b"value".to_vec())));
+        assert_eq!(verify_proof(root, &keccak256(b"other"), &[&node]), Ok(None));
+        assert_eq!(verify_proof(root, &key, &[&node, &node]).unwrap_err(), ProofError::UnusedNodes);
+        assert!(matches!(
+            verify_proof(H256::zero(), &key, &[&node]).unwrap_err(),
+            ProofError::HashMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn can_verify_branch_with_inline_children() {
+        // two short keys that differ in the first nibble are stored inline in a branch
+        let a = [0x10];
+        let b = [0x20];
+        let mut branch = RlpStream::new_list(17);
+        for i in 0..16u8 {
+            match i {
+                1 => branch.append_raw(&leaf_nibble(0, b"a"), 1),
+                2 => branch.append_raw(&leaf_nibble(0, b"b"), 1),
+                _ => branch.append_empty_data(),
+            };
+        }
+        branch.append_empty_data();
+        let branch = branch.out().to_vec();
+        let root = H256(keccak256(&branch));
+
+        assert_eq!(verify_proof(root, &a, &[&branch]), Ok(Some(b"a".to_vec())));
+        assert_eq!(verify_proof(root, &b, &[&branch]), Ok(Some(b"b".to_vec())));
+        assert_eq!(verify_proof(root, &[0x30], &[&branch]), Ok(None));
+        assert_eq!(verify_proof(root, &[0x11], &[&branch]), Ok(None));
+    }
+
+    /// leaf with a single remaining nibble
+    fn leaf_nibble(nibble: u8, value: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&vec![0x30 | nibble]).append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    #[test]
+    fn can_compute_root() {
+        // a single entry is stored in the root leaf
+        let key = keccak256(b"key");
+        assert_eq!(trie_root([(key, b"value")]), H256(keccak256(leaf(&key, b"value"))));
+
+        // the root does not depend on the order of the entries
+        let entries = (0u64..100)
+            .map(|i| (keccak256(i.to_be_bytes()).to_vec(), rlp::encode(&i).to_vec()))
+            .collect::<Vec<_>>();
+        let root = trie_root(entries.iter().cloned());
+        assert_ne!(root, EMPTY_ROOT);
+        assert_eq!(trie_root(entries.iter().rev().cloned()), root);
+        assert_eq!(trie_root([(b"a", b"")]), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn known_root() {
+        // <https://github.com/ethereum/go-ethereum/blob/master/trie/trie_test.go>
+        let root = trie_root([
+            (b"doe".to_vec(), b"reindeer".to_vec()),
+            (b"dog".to_vec(), b"puppy".to_vec()),
+            (b"dogglesworth".to_vec(), b"cat".to_vec()),
+        ]);
+        assert_eq!(
+            root,
+            "0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn can_generate_proofs() {
+        let entries = [
+            (b"doe".to_vec(), b"reindeer".to_vec()),
+            (b"dog".to_vec(), b"puppy".to_vec()),
+            (b"dogglesworth".to_vec(), b"cat".to_vec()),
+        ];
+        let root = trie_root(entries.iter().cloned());
+        for (key, value) in &entries {
+            let proof = trie_proof(entries.iter().cloned(), key);
+            assert_eq!(verify_proof(root, key, &proof), Ok(Some(value.clone())));
+        }
+        for key in [&b"do"[..], b"dogs", b"cat"] {
+            let proof = trie_proof(entries.iter().cloned(), key);
+            assert_eq!(verify_proof(root, key, &proof), Ok(None));
+        }
+
+        // indices 0 to 127 encode to a single byte, so the keys have different lengths
+        let values = (0u64..200).map(|i| keccak256(i.to_be_bytes()).to_vec()).collect::<Vec<_>>();
+        let root = ordered_trie_root(&values);
+        for index in [0, 1, 15, 16, 127, 128, 199, 200] {
+            let proof = ordered_trie_proof(&values, index);
+            let value = verify_proof(root, &rlp::encode(&(index as u64)), &proof).unwrap();
+            assert_eq!(value.as_ref(), values.get(index));
+        }
+    }
+
+    #[test]
+    fn empty_trie() {
+        let proof: Vec<Vec<u8>> = vec![vec![0x80]];
+        assert_eq!(verify_proof(EMPTY_ROOT, &[1], &proof), Ok(None));
+        assert_eq!(H256(keccak256([0x80])), EMPTY_ROOT);
+        assert_eq!(H256(keccak256([])), KECCAK_EMPTY);
+    }
+}
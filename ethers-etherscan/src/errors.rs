@@ -55,6 +55,25 @@ pub enum EtherscanError {
     PageNotFound,
 }
 
+/// Errors returned by the [`SourcifyClient`](crate::sourcify::SourcifyClient).
+#[derive(Debug, thiserror::Error)]
+pub enum SourcifyError {
+    #[error("Contract {address:?} is not verified on chain {chain}")]
+    ContractNotVerified { chain: u64, address: Address },
+    #[error("Received error response: {0}")]
+    ErrorResponse(String),
+    #[error("Missing or invalid metadata")]
+    MissingMetadata,
+    #[error("Invalid URL: {0}")]
+    Url(String),
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+}
+
 /// etherscan/polyscan is protected by cloudflare, which can lead to html responses like `Sorry, you have been blocked` See also <https://community.cloudflare.com/t/sorry-you-have-been-blocked/110790>
 ///
 /// This returns true if the `txt` is a cloudflare error response
@@ -0,0 +1,478 @@
+//! Client for the [Sourcify](https://sourcify.dev) verification service.
+//!
+//! Sourcify verifies contracts by recompiling them from their metadata file and sources, and it
+//! serves the verified files for every supported chain, including chains without an etherscan
+//! instance.
+
+use crate::{
+    errors::SourcifyError,
+    source_tree::{SourceTree, SourceTreeEntry},
+};
+use ethers_core::{abi::Abi, types::Address};
+use reqwest::{header, StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+use tracing::trace;
+
+#[cfg(feature = "ethers-solc")]
+use ethers_solc::{ConfigurableContractArtifact, Project};
+
+type Result<T, E = SourcifyError> = std::result::Result<T, E>;
+
+/// The public Sourcify server.
+pub const SOURCIFY_SERVER_URL: &str = "https://sourcify.dev/server/";
+
+/// The Sourcify API client.
+#[derive(Clone, Debug)]
+pub struct SourcifyClient {
+    /// Client that executes HTTP requests
+    client: reqwest::Client,
+    /// Sourcify server endpoint like <https://sourcify.dev/server/>
+    server_url: Url,
+}
+
+impl Default for SourcifyClient {
+    fn default() -> Self {
+        Self { client: Default::default(), server_url: Url::parse(SOURCIFY_SERVER_URL).unwrap() }
+    }
+}
+
+impl SourcifyClient {
+    /// Creates a new client for the public Sourcify server.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new client for a self-hosted Sourcify server.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `server_url` is not a valid `Url`
+    pub fn with_url(server_url: impl AsRef<str>) -> Result<Self> {
+        let mut server_url = server_url.as_ref().to_string();
+        if !server_url.ends_with('/') {
+            server_url.push('/');
+        }
+        let server_url =
+            Url::parse(&server_url).map_err(|err| SourcifyError::Url(err.to_string()))?;
+        Ok(Self { server_url, ..Default::default() })
+    }
+
+    /// Configures the `reqwest::Client`
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Returns the URL of the Sourcify server, with a trailing slash.
+    pub fn server_url(&self) -> &Url {
+        &self.server_url
+    }
+
+    /// Returns whether the contract at `address` is verified on the given chain, and how.
+    ///
+    /// Returns `None` if the contract is not verified.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ethers_etherscan::sourcify::SourcifyClient;
+    /// # use ethers_core::types::Chain;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = SourcifyClient::new();
+    /// let status = client
+    ///     .verification_status(Chain::Mainnet, "0x00000000219ab540356cBB839Cbe05303d7705Fa".parse().unwrap())
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub async fn verification_status(
+        &self,
+        chain: impl Into<u64>,
+        address: Address,
+    ) -> Result<Option<SourcifyMatch>> {
+        let chain = chain.into();
+        let statuses = self.check_by_addresses(&[address], &[chain]).await?;
+        Ok(statuses
+            .into_iter()
+            .filter(|status| status.address == address)
+            .flat_map(|status| status.chain_ids)
+            .find(|status| status.chain_id == chain.to_string())
+            .map(|status| status.status))
+    }
+
+    /// Returns the verification status of all given addresses on all given chains.
+    pub async fn check_by_addresses(
+        &self,
+        addresses: &[Address],
+        chains: &[u64],
+    ) -> Result<Vec<VerificationStatus>> {
+        let addresses = addresses.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>().join(",");
+        let chains = chains.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(",");
+        let url = self.url("check-all-by-addresses")?;
+        self.get_json(url, &[("addresses", addresses), ("chainIds", chains)]).await
+    }
+
+    /// Fetches all verified files of the contract at `address`, preferring a full match.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SourcifyError::ContractNotVerified`] if the contract is not verified.
+    pub async fn contract_files(
+        &self,
+        chain: impl Into<u64>,
+        address: Address,
+    ) -> Result<SourcifyFiles> {
+        let chain = chain.into();
+        let url = self.url(&format!("files/any/{chain}/{address:?}"))?;
+        trace!(target: "sourcify", "GET {}", url);
+        let response =
+            self.client.get(url).header(header::ACCEPT, "application/json").send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(SourcifyError::ContractNotVerified { chain, address })
+        }
+        let files: SourcifyFiles = sanitize_response(response.text().await?)?;
+        Ok(files)
+    }
+
+    /// Fetches the verified sources of the contract at `address` as a [`SourceTree`].
+    pub async fn contract_source_tree(
+        &self,
+        chain: impl Into<u64>,
+        address: Address,
+    ) -> Result<SourceTree> {
+        Ok(self.contract_files(chain, address).await?.source_tree())
+    }
+
+    /// Fetches the ABI of the verified contract at `address` from its metadata.
+    pub async fn contract_abi(&self, chain: impl Into<u64>, address: Address) -> Result<Abi> {
+        self.contract_files(chain, address).await?.abi()
+    }
+
+    /// Submits the contract for verification.
+    ///
+    /// Returns one [`VerificationResult`] per verified contract.
+    pub async fn verify(&self, request: &VerifySourcify) -> Result<Vec<VerificationResult>> {
+        let url = self.url("verify")?;
+        trace!(target: "sourcify", "POST {}", url);
+        let response = self.client.post(url).json(request).send().await?.text().await?;
+        let response: VerifyResponse = sanitize_response(response)?;
+        Ok(response.result)
+    }
+
+    fn url(&self, path: &str) -> Result<Url> {
+        self.server_url.join(path).map_err(|err| SourcifyError::Url(err.to_string()))
+    }
+
+    async fn get_json<T: DeserializeOwned, Q: Serialize + ?Sized>(
+        &self,
+        url: Url,
+        query: &Q,
+    ) -> Result<T> {
+        trace!(target: "sourcify", "GET {}", url);
+        let response = self
+            .client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .query(query)
+            .send()
+            .await?
+            .text()
+            .await?;
+        sanitize_response(response)
+    }
+}
+
+/// Deserializes the response, or returns the error message of an error response.
+fn sanitize_response<T: DeserializeOwned>(res: impl AsRef<str>) -> Result<T> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ResponseData<T> {
+        Error { error: String },
+        Success(T),
+    }
+
+    match serde_json::from_str(res.as_ref())? {
+        ResponseData::Success(res) => Ok(res),
+        ResponseData::Error { error } => Err(SourcifyError::ErrorResponse(error)),
+    }
+}
+
+/// How closely a verified contract matches its sources.
+///
+/// See also <https://docs.sourcify.dev/docs/full-vs-partial-match/>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourcifyMatch {
+    /// The bytecode matches including the metadata hash, called `perfect` by some endpoints.
+    #[serde(alias = "perfect")]
+    Full,
+    /// The bytecode matches except for the metadata hash.
+    Partial,
+}
+
+impl fmt::Display for SourcifyMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourcifyMatch::Full => f.write_str("full"),
+            SourcifyMatch::Partial => f.write_str("partial"),
+        }
+    }
+}
+
+/// The verification status of an address, as returned by [`SourcifyClient::check_by_addresses`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationStatus {
+    pub address: Address,
+    /// The chains the address is verified on, empty if it is not verified on any chain.
+    #[serde(default)]
+    pub chain_ids: Vec<ChainVerificationStatus>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainVerificationStatus {
+    pub chain_id: String,
+    pub status: SourcifyMatch,
+}
+
+/// A single file of a verified contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcifyFile {
+    pub name: String,
+    /// The path of the file in the Sourcify repository.
+    pub path: String,
+    pub content: String,
+}
+
+/// All files of a verified contract, as returned by [`SourcifyClient::contract_files`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcifyFiles {
+    /// Whether the files are a full or partial match.
+    pub status: SourcifyMatch,
+    pub files: Vec<SourcifyFile>,
+}
+
+impl SourcifyFiles {
+    /// Returns the contents of the `metadata.json` file.
+    pub fn raw_metadata(&self) -> Option<&str> {
+        self.files.iter().find(|f| f.name == "metadata.json").map(|f| f.content.as_str())
+    }
+
+    /// Returns the parsed `metadata.json` file.
+    pub fn metadata(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::from_str(self.raw_metadata().ok_or(SourcifyError::MissingMetadata)?)?)
+    }
+
+    /// Returns the ABI from the `metadata.json` file.
+    pub fn abi(&self) -> Result<Abi> {
+        let mut metadata = self.metadata()?;
+        let abi = metadata
+            .pointer_mut("/output/abi")
+            .map(serde_json::Value::take)
+            .ok_or(SourcifyError::MissingMetadata)?;
+        Ok(serde_json::from_value(abi)?)
+    }
+
+    /// Returns an iterator over the source files with their paths relative to the `sources`
+    /// directory of the contract.
+    pub fn sources(&self) -> impl Iterator<Item = (&str, &SourcifyFile)> + '_ {
+        self.files.iter().filter_map(|file| {
+            let (_, path) = file.path.split_once("/sources/")?;
+            Some((path, file))
+        })
+    }
+
+    /// Returns the source files as a [`SourceTree`].
+    pub fn source_tree(&self) -> SourceTree {
+        let entries = self
+            .sources()
+            .map(|(path, file)| SourceTreeEntry {
+                path: PathBuf::from(path),
+                contents: file.content.clone(),
+            })
+            .collect();
+        SourceTree { entries }
+    }
+}
+
+/// Arguments for verifying a contract with [`SourcifyClient::verify`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifySourcify {
+    pub address: Address,
+    /// The chain id as decimal string.
+    pub chain: String,
+    /// The metadata file and all sources, keyed by file name.
+    ///
+    /// The metadata file must be included as `metadata.json`.
+    pub files: BTreeMap<String, String>,
+    /// The index of the contract to verify if the metadata files contain several contracts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chosen_contract: Option<String>,
+    /// The hex encoded creation transaction hash, which helps verifying contracts with
+    /// immutables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_tx_hash: Option<String>,
+}
+
+impl VerifySourcify {
+    /// Creates a new request from the raw metadata JSON and sources, keyed by the source unit
+    /// names used in the metadata.
+    pub fn new(
+        chain: impl Into<u64>,
+        address: Address,
+        metadata: impl Into<String>,
+        sources: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let mut files = sources.into_iter().collect::<BTreeMap<_, _>>();
+        files.insert("metadata.json".to_string(), metadata.into());
+        Self {
+            address,
+            chain: chain.into().to_string(),
+            files,
+            chosen_contract: None,
+            creator_tx_hash: None,
+        }
+    }
+
+    /// Creates a new request from the raw metadata JSON, reading all sources listed in the
+    /// metadata that don't embed their content from `root`.
+    pub fn from_metadata(
+        chain: impl Into<u64>,
+        address: Address,
+        metadata: impl Into<String>,
+        root: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let metadata = metadata.into();
+        let parsed: serde_json::Value = serde_json::from_str(&metadata)?;
+        let sources = parsed
+            .get("sources")
+            .and_then(|sources| sources.as_object())
+            .ok_or(SourcifyError::MissingMetadata)?;
+
+        let root = root.as_ref();
+        let mut files = Vec::with_capacity(sources.len());
+        for (name, source) in sources {
+            let content = match source.get("content").and_then(|c| c.as_str()) {
+                Some(content) => content.to_string(),
+                None => std::fs::read_to_string(root.join(name))?,
+            };
+            files.push((name.clone(), content));
+        }
+        Ok(Self::new(chain, address, metadata, files))
+    }
+
+    /// Creates a new request for a compiled artifact of the `project`.
+    ///
+    /// The artifact must have been compiled with the `metadata` output selection.
+    #[cfg(feature = "ethers-solc")]
+    pub fn from_artifact(
+        chain: impl Into<u64>,
+        address: Address,
+        project: &Project,
+        artifact: &ConfigurableContractArtifact,
+    ) -> Result<Self> {
+        let metadata = artifact.raw_metadata.clone().ok_or(SourcifyError::MissingMetadata)?;
+        Self::from_metadata(chain, address, metadata, project.root())
+    }
+
+    #[must_use]
+    pub fn chosen_contract(mut self, index: usize) -> Self {
+        self.chosen_contract = Some(index.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn creator_tx_hash(mut self, tx_hash: impl Into<String>) -> Self {
+        self.creator_tx_hash = Some(tx_hash.into());
+        self
+    }
+}
+
+/// The result of a verification request for a single contract.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationResult {
+    pub address: Address,
+    pub chain_id: String,
+    pub status: SourcifyMatch,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VerifyResponse {
+    result: Vec<VerificationResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_check_response() {
+        let res = r#"[{"address":"0x00000000219ab540356cBB839Cbe05303d7705Fa","chainIds":[{"chainId":"1","status":"perfect"},{"chainId":"5","status":"partial"}]},{"address":"0x0000000000000000000000000000000000000001","status":"false"}]"#;
+        let statuses: Vec<VerificationStatus> = sanitize_response(res).unwrap();
+        assert_eq!(statuses[0].chain_ids[0].status, SourcifyMatch::Full);
+        assert_eq!(statuses[0].chain_ids[1].status, SourcifyMatch::Partial);
+        assert!(statuses[1].chain_ids.is_empty());
+    }
+
+    #[test]
+    fn can_parse_files() {
+        let res = r#"{"status":"full","files":[
+            {"name":"metadata.json","path":"/data/repository/contracts/full_match/1/0x00000000219ab540356cBB839Cbe05303d7705Fa/metadata.json","content":"{\"output\":{\"abi\":[{\"inputs\":[],\"name\":\"foo\",\"outputs\":[],\"stateMutability\":\"nonpayable\",\"type\":\"function\"}]}}"},
+            {"name":"A.sol","path":"/data/repository/contracts/full_match/1/0x00000000219ab540356cBB839Cbe05303d7705Fa/sources/src/A.sol","content":"contract A {}"}
+        ]}"#;
+        let files: SourcifyFiles = sanitize_response(res).unwrap();
+        assert_eq!(files.status, SourcifyMatch::Full);
+        assert!(files.abi().unwrap().function("foo").is_ok());
+
+        let tree = files.source_tree();
+        assert_eq!(tree.entries.len(), 1);
+        assert_eq!(tree.entries[0].path, PathBuf::from("src/A.sol"));
+        assert_eq!(tree.entries[0].contents, "contract A {}");
+    }
+
+    #[test]
+    fn can_parse_error_response() {
+        let err = sanitize_response::<SourcifyFiles>(r#"{"error":"Files have not been found!"}"#)
+            .unwrap_err();
+        assert!(
+            matches!(err, SourcifyError::ErrorResponse(msg) if msg == "Files have not been found!")
+        );
+    }
+
+    #[test]
+    fn can_create_verify_request() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/A.sol"), "contract A {}").unwrap();
+        let metadata = r#"{"sources":{"src/A.sol":{"keccak256":"0x"},"src/B.sol":{"content":"contract B {}"}}}"#;
+
+        let req = VerifySourcify::from_metadata(1u64, Address::zero(), metadata, dir.path())
+            .unwrap()
+            .chosen_contract(0);
+        assert_eq!(req.files["src/A.sol"], "contract A {}");
+        assert_eq!(req.files["src/B.sol"], "contract B {}");
+        assert_eq!(req.files["metadata.json"], metadata);
+
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["chain"], "1");
+        assert_eq!(json["chosenContract"], "0");
+    }
+
+    #[test]
+    fn can_parse_verify_response() {
+        let res = r#"{"result":[{"address":"0x00000000219ab540356cBB839Cbe05303d7705Fa","chainId":"1","status":"perfect","libraryMap":{}}]}"#;
+        let res: VerifyResponse = sanitize_response(res).unwrap();
+        assert_eq!(res.result[0].status, SourcifyMatch::Full);
+    }
+}
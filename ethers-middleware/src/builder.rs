@@ -1,6 +1,6 @@
 use crate::{
     gas_oracle::{GasOracle, GasOracleMiddleware},
-    NonceManagerMiddleware, SignerMiddleware,
+    MulticallBatchingMiddleware, NonceManagerMiddleware, SignerMiddleware,
 };
 use ethers_core::types::Address;
 use ethers_providers::Middleware;
@@ -80,6 +80,11 @@ pub trait MiddlewareBuilder: Middleware + Sized + 'static {
     {
         GasOracleMiddleware::new(self, gas_oracle)
     }
+
+    /// Wraps `self` inside a [`MulticallBatchingMiddleware`].
+    fn multicall_batching(self) -> MulticallBatchingMiddleware<Self> {
+        MulticallBatchingMiddleware::new(self)
+    }
 }
 
 impl<M> MiddlewareBuilder for M where M: Middleware + Sized + 'static {}
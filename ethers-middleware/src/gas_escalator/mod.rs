@@ -13,15 +13,13 @@ use std::{pin::Pin, sync::Arc};
 use thiserror::Error;
 use tracing_futures::Instrument;
 
-use ethers_core::types::{
-    transaction::eip2718::TypedTransaction, BlockId, TransactionRequest, TxHash, U256,
-};
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockId, TxHash, U256};
 use ethers_providers::{interval, Middleware, MiddlewareError, PendingTransaction, StreamExt};
 
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::spawn;
 
-type ToEscalate = Arc<Mutex<Vec<(TxHash, TransactionRequest, Instant, Option<BlockId>)>>>;
+type ToEscalate = Arc<Mutex<Vec<EscalatedTransaction>>>;
 
 #[cfg(target_arch = "wasm32")]
 type WatcherFuture<'a> = Pin<Box<dyn futures_util::stream::Stream<Item = ()> + 'a>>;
@@ -34,6 +32,24 @@ pub trait GasEscalator: Send + Sync + std::fmt::Debug {
     /// Given the initial gas price and the time elapsed since the transaction's
     /// first broadcast, it returns the new gas price
     fn get_gas_price(&self, initial_price: U256, time_elapsed: u64) -> U256;
+
+    /// Given the initial max fee and max priority fee per gas of an EIP-1559
+    /// transaction and the time elapsed since its first broadcast, it returns
+    /// the new `(max_fee_per_gas, max_priority_fee_per_gas)`.
+    ///
+    /// By default both fees are escalated with [`GasEscalator::get_gas_price`],
+    /// so any price cap of the escalator applies to both of them.
+    fn get_eip1559_fees(
+        &self,
+        initial_max_fee: U256,
+        initial_priority_fee: U256,
+        time_elapsed: u64,
+    ) -> (U256, U256) {
+        (
+            self.get_gas_price(initial_max_fee, time_elapsed),
+            self.get_gas_price(initial_priority_fee, time_elapsed),
+        )
+    }
 }
 
 /// Error thrown when the GasEscalator interacts with the blockchain
@@ -43,7 +59,7 @@ pub enum GasEscalatorError<M: Middleware> {
     /// Thrown when an internal middleware errors
     MiddlewareError(M::Error),
 
-    #[error("Gas escalation is only supported for EIP2930 or Legacy transactions")]
+    #[error("Gas escalation is only supported for Legacy, EIP2930 or EIP1559 transactions")]
     UnsupportedTxType,
 }
 
@@ -63,6 +79,100 @@ impl<M: Middleware> MiddlewareError for GasEscalatorError<M> {
     }
 }
 
+/// A transaction whose fees are being escalated by the [`GasEscalatorMiddleware`]
+#[derive(Debug, Clone)]
+pub struct EscalatedTransaction {
+    /// The most recently broadcast version of the transaction
+    pub tx: TypedTransaction,
+    /// The hashes of every broadcast version of the transaction, oldest first
+    pub hashes: Vec<TxHash>,
+    /// The gas price, or max fee per gas for EIP-1559 transactions, the
+    /// transaction was first broadcast with
+    pub initial_gas_price: U256,
+    /// The max priority fee per gas the transaction was first broadcast with,
+    /// only set for EIP-1559 transactions
+    pub initial_priority_fee: Option<U256>,
+    /// When the transaction was first broadcast
+    pub first_broadcast: Instant,
+    /// The block the transaction was sent with
+    pub block: Option<BlockId>,
+}
+
+impl EscalatedTransaction {
+    fn new(tx_hash: TxHash, tx: TypedTransaction, block: Option<BlockId>) -> Option<Self> {
+        let (initial_gas_price, initial_priority_fee) = match tx {
+            TypedTransaction::Legacy(ref inner) => (inner.gas_price?, None),
+            TypedTransaction::Eip2930(ref inner) => (inner.tx.gas_price?, None),
+            TypedTransaction::Eip1559(ref inner) => {
+                (inner.max_fee_per_gas?, Some(inner.max_priority_fee_per_gas?))
+            }
+            #[cfg(feature = "optimism")]
+            TypedTransaction::DepositTransaction(_) => return None,
+        };
+        Some(Self {
+            tx,
+            hashes: vec![tx_hash],
+            initial_gas_price,
+            initial_priority_fee,
+            first_broadcast: Instant::now(),
+            block,
+        })
+    }
+
+    /// The hash of the most recently broadcast version of the transaction
+    pub fn tx_hash(&self) -> TxHash {
+        *self.hashes.last().expect("at least one hash is always tracked")
+    }
+
+    /// How many times the transaction has been replaced with higher fees
+    pub fn escalations(&self) -> usize {
+        self.hashes.len() - 1
+    }
+
+    /// Returns the replacement for the current transaction after `time_elapsed`
+    /// seconds, or `None` if the escalated fees would not be accepted as a
+    /// replacement, i.e. if they do not bump every fee by at least 10%.
+    fn replacement<E: GasEscalator + ?Sized>(
+        &self,
+        escalator: &E,
+        time_elapsed: u64,
+    ) -> Option<TypedTransaction> {
+        let mut tx = self.tx.clone();
+        match tx {
+            TypedTransaction::Eip1559(ref mut inner) => {
+                let (max_fee, priority_fee) = escalator.get_eip1559_fees(
+                    self.initial_gas_price,
+                    self.initial_priority_fee?,
+                    time_elapsed,
+                );
+                // the tip can never exceed the max fee
+                let priority_fee = std::cmp::min(priority_fee, max_fee);
+                if max_fee < min_replacement_fee(inner.max_fee_per_gas?) ||
+                    priority_fee < min_replacement_fee(inner.max_priority_fee_per_gas?)
+                {
+                    return None
+                }
+                inner.max_fee_per_gas = Some(max_fee);
+                inner.max_priority_fee_per_gas = Some(priority_fee);
+            }
+            _ => {
+                let gas_price = escalator.get_gas_price(self.initial_gas_price, time_elapsed);
+                if gas_price < min_replacement_fee(tx.gas_price()?) {
+                    return None
+                }
+                tx.set_gas_price(gas_price);
+            }
+        }
+        Some(tx)
+    }
+}
+
+/// The lowest fee a node accepts to replace a transaction paying `fee`, using
+/// geth's default price bump of 10%
+fn min_replacement_fee(fee: U256) -> U256 {
+    std::cmp::max(fee.saturating_add(fee / 10), fee.saturating_add(U256::one()))
+}
+
 /// The frequency at which transactions will be bumped
 #[derive(Debug, Clone, Copy)]
 pub enum Frequency {
@@ -90,6 +200,17 @@ pub(crate) struct GasEscalatorMiddlewareInternal<M> {
 /// if any require fee bumps. If so, it will resend the same transaction with a
 /// higher fee.
 ///
+/// Legacy and EIP-2930 transactions have their gas price escalated, while
+/// EIP-1559 transactions have both their max fee and max priority fee per gas
+/// escalated via [`GasEscalator::get_eip1559_fees`]. A replacement is only
+/// broadcast once every fee has been bumped by at least 10%, as nodes reject
+/// replacements paying less, and the fees never exceed the escalator's price
+/// cap. Replacements are sent through the inner middleware, so they are
+/// re-signed by a [`SignerMiddleware`](crate::SignerMiddleware) further down
+/// the stack. A transaction stops being tracked as soon as it or any of its
+/// replacements is mined, and [`GasEscalatorMiddleware::escalations`] exposes
+/// the transactions that are still being escalated.
+///
 /// Using [`GasEscalatorMiddleware::new`] will create a new instance of the
 /// background task. Using [`GasEscalatorMiddleware::clone`] will crate a new
 /// instance of the middleware, but will not create a new background task. The
@@ -173,7 +294,14 @@ where
         tx: T,
         block: Option<BlockId>,
     ) -> Result<PendingTransaction<'_, M::Provider>, GasEscalatorError<M>> {
-        let tx = tx.into();
+        let mut tx = tx.into();
+        // fill the transaction before sending it, so that we know the fees it
+        // was broadcast with and can escalate them later on
+        self.inner.fill_transaction(&mut tx, block).await.map_err(MiddlewareError::from_err)?;
+        #[cfg(feature = "optimism")]
+        if matches!(tx, TypedTransaction::DepositTransaction(_)) {
+            return Err(GasEscalatorError::UnsupportedTxType)
+        }
 
         let pending_tx = self
             .inner
@@ -181,15 +309,10 @@ where
             .await
             .map_err(MiddlewareError::from_err)?;
 
-        let tx = match tx {
-            TypedTransaction::Legacy(inner) => inner,
-            TypedTransaction::Eip2930(inner) => inner.tx,
-            _ => return Err(GasEscalatorError::UnsupportedTxType),
-        };
-
         // insert the tx in the pending txs
-        let mut lock = self.txs.lock().await;
-        lock.push((*pending_tx, tx, Instant::now(), block));
+        let escalated = EscalatedTransaction::new(*pending_tx, tx, block)
+            .ok_or(GasEscalatorError::UnsupportedTxType)?;
+        self.txs.lock().await.push(escalated);
 
         Ok(pending_tx)
     }
@@ -227,6 +350,14 @@ where
 
         Self { inner: this }
     }
+
+    /// Returns a snapshot of the transactions which are currently being
+    /// escalated, i.e. which have been sent through this middleware and for
+    /// which neither the original transaction nor any of its replacements has
+    /// been mined yet.
+    pub async fn escalations(&self) -> Vec<EscalatedTransaction> {
+        self.inner.txs.lock().await.clone()
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -279,82 +410,154 @@ impl<M, E> EscalationTask<M, E> {
                 }
                 let now = Instant::now();
 
-                // We take the contents of the mutex, and then add them back in
-                // later.
-                let mut txs: Vec<_> = {
+                // Work on a snapshot so that the tracked transactions remain
+                // inspectable while we are talking to the node, and write back
+                // each result once it has been processed.
+                let txs = self.txs.lock().await.clone();
+                for mut escalated in txs {
+                    let id = escalated.hashes[0];
+                    let done = self.escalate_tx(&mut escalated, now).await?;
                     let mut txs = self.txs.lock().await;
-                    std::mem::take(&mut (*txs))
-                    // Lock scope ends
-                };
-
-                let len = txs.len();
-                // Pop all transactions and re-insert those that have not been included yet
-                for _ in 0..len {
-                    // this must never panic as we're explicitly within bounds
-                    let (tx_hash, mut replacement_tx, time, priority) =
-                        txs.pop().expect("should have element in vector");
-
-                    let receipt = self
-                        .inner
-                        .get_transaction_receipt(tx_hash)
-                        .await
-                        .map_err(MiddlewareError::from_err)?;
-
-                    tracing::trace!(tx_hash = ?tx_hash, "checking if exists");
-
-                    if receipt.is_none() {
-                        let old_gas_price = replacement_tx.gas_price.expect("gas price must be set");
-                        // Get the new gas price based on how much time passed since the
-                        // tx was last broadcast
-                        let new_gas_price = self
-                            .escalator
-                            .get_gas_price(old_gas_price, now.duration_since(time).as_secs());
-
-                        let new_txhash = if new_gas_price == old_gas_price {
-                             tx_hash
+                    if let Some(idx) = txs.iter().position(|tx| tx.hashes[0] == id) {
+                        if done {
+                            txs.remove(idx);
                         } else {
-                            // bump the gas price
-                            replacement_tx.gas_price = Some(new_gas_price);
-
-                            // the tx hash will be different so we need to update it
-                            match self.inner.send_transaction(replacement_tx.clone(), priority).await {
-                                Ok(new_tx_hash) => {
-                                    let new_tx_hash = *new_tx_hash;
-                                    tracing::trace!(
-                                        old_tx_hash = ?tx_hash,
-                                        new_tx_hash = ?new_tx_hash,
-                                        old_gas_price = ?old_gas_price,
-                                        new_gas_price = ?new_gas_price,
-                                        "escalated"
-                                    );
-                                    new_tx_hash
-                                }
-                                Err(err) => {
-                                    if err.to_string().contains("nonce too low") {
-                                        // ignore "nonce too low" errors because they
-                                        // may happen if we try to broadcast a higher
-                                        // gas price tx when one of the previous ones
-                                        // was already mined (meaning we also do not
-                                        // push it back to the pending txs vector)
-                                        continue
-                                    } else {
-                                        tracing::error!(
-                                            err = %err,
-                                            "Killing escalator backend"
-                                        );
-                                        return Err(GasEscalatorError::MiddlewareError(err))
-                                    }
-                                }
-                            }
-                        };
-                        txs.push((new_txhash, replacement_tx, time, priority));
+                            txs[idx] = escalated;
+                        }
                     }
                 }
-                // after this big ugly loop, we dump everything back in
-                // we don't replace here, as the vec in the mutex may contain
-                // items!
-                self.txs.lock().await.extend(txs);
             }}
         }
     }
+
+    /// Escalates a single transaction, returning `true` once it no longer needs
+    /// to be tracked
+    async fn escalate_tx(
+        &self,
+        escalated: &mut EscalatedTransaction,
+        now: Instant,
+    ) -> Result<bool, GasEscalatorError<M>>
+    where
+        M: Middleware,
+        E: GasEscalator,
+    {
+        // any of the broadcast versions may have been mined
+        for tx_hash in escalated.hashes.iter().rev() {
+            tracing::trace!(tx_hash = ?tx_hash, "checking if exists");
+            let receipt = self
+                .inner
+                .get_transaction_receipt(*tx_hash)
+                .await
+                .map_err(MiddlewareError::from_err)?;
+            if receipt.is_some() {
+                return Ok(true)
+            }
+        }
+
+        // a replacement must reuse the nonce the transaction was broadcast with
+        if escalated.tx.nonce().is_none() {
+            let tx = self
+                .inner
+                .get_transaction(escalated.tx_hash())
+                .await
+                .map_err(MiddlewareError::from_err)?;
+            match tx {
+                Some(tx) => {
+                    escalated.tx.set_nonce(tx.nonce);
+                }
+                // not yet visible to the node, try again next time
+                None => return Ok(false),
+            }
+        }
+
+        // Get the new fees based on how much time passed since the tx was
+        // first broadcast
+        let time_elapsed = now.duration_since(escalated.first_broadcast).as_secs();
+        let Some(replacement) = escalated.replacement(&self.escalator, time_elapsed) else {
+            return Ok(false)
+        };
+
+        // the tx hash will be different so we need to track it
+        match self.inner.send_transaction(replacement.clone(), escalated.block).await {
+            Ok(pending) => {
+                tracing::trace!(
+                    old_tx_hash = ?escalated.tx_hash(),
+                    new_tx_hash = ?*pending,
+                    escalations = escalated.hashes.len(),
+                    "escalated"
+                );
+                escalated.hashes.push(*pending);
+                escalated.tx = replacement;
+                Ok(false)
+            }
+            Err(err) => {
+                if err.to_string().contains("nonce too low") {
+                    // ignore "nonce too low" errors because they may happen if
+                    // we try to broadcast a higher gas price tx when one of the
+                    // previous ones was already mined (meaning we also do not
+                    // keep tracking it)
+                    Ok(true)
+                } else {
+                    tracing::error!(err = %err, "Killing escalator backend");
+                    Err(GasEscalatorError::MiddlewareError(err))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn legacy_replacement_requires_price_bump() {
+        let tx = TransactionRequest::new().gas_price(100).nonce(1);
+        let escalated = EscalatedTransaction::new(TxHash::zero(), tx.into(), None).unwrap();
+        let escalator = LinearGasPrice::new(5u64, 10u64, Some(125u64));
+
+        // +5 is not enough to replace the transaction
+        assert!(escalated.replacement(&escalator, 10).is_none());
+        let replacement = escalated.replacement(&escalator, 20).unwrap();
+        assert_eq!(replacement.gas_price(), Some(110.into()));
+        assert_eq!(replacement.nonce(), Some(&1.into()));
+    }
+
+    #[test]
+    fn eip1559_replacement_bumps_both_fees() {
+        let tx = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(1000)
+            .max_priority_fee_per_gas(100)
+            .nonce(1);
+        let mut escalated = EscalatedTransaction::new(TxHash::zero(), tx.into(), None).unwrap();
+        assert_eq!(escalated.initial_priority_fee, Some(100.into()));
+        let escalator = GeometricGasPrice::new(1.2, 10u64, Some(1500u64));
+
+        assert!(escalated.replacement(&escalator, 5).is_none());
+        let replacement = escalated.replacement(&escalator, 10).unwrap();
+        let TypedTransaction::Eip1559(ref inner) = replacement else { unreachable!() };
+        assert_eq!(inner.max_fee_per_gas, Some(1200.into()));
+        assert_eq!(inner.max_priority_fee_per_gas, Some(120.into()));
+
+        escalated.tx = replacement;
+        escalated.hashes.push(TxHash::repeat_byte(1));
+        assert_eq!(escalated.escalations(), 1);
+        assert_eq!(escalated.tx_hash(), TxHash::repeat_byte(1));
+
+        let replacement = escalated.replacement(&escalator, 20).unwrap();
+        let TypedTransaction::Eip1559(ref inner) = replacement else { unreachable!() };
+        assert_eq!(inner.max_fee_per_gas, Some(1440.into()));
+        escalated.tx = replacement;
+
+        // the max fee is capped at 1500, which is less than a 10% bump
+        assert!(escalated.replacement(&escalator, 30).is_none());
+    }
+
+    #[test]
+    fn replacement_fee_saturates() {
+        assert_eq!(min_replacement_fee(100.into()), 110.into());
+        assert_eq!(min_replacement_fee(1.into()), 2.into());
+        assert_eq!(min_replacement_fee(U256::MAX), U256::MAX);
+    }
 }
@@ -0,0 +1,379 @@
+use super::{GasCategory, GasOracle, GasOracleError, Result};
+use async_trait::async_trait;
+use ethers_core::types::{Block, BlockNumber, TxHash, U256};
+use ethers_providers::{Middleware, StreamExt};
+use futures_locks::RwLock;
+use std::{collections::VecDeque, fmt::Debug, sync::Arc};
+
+/// The default number of blocks kept in the fee history window.
+pub const DEFAULT_WINDOW: u64 = 20;
+
+/// The default reward percentile used as the lowest competitive priority fee of a block.
+pub const DEFAULT_REWARD_PERCENTILE: f64 = 10.0;
+
+/// A target for transaction inclusion: be included within `blocks` blocks with the given
+/// `probability`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InclusionTarget {
+    /// The number of blocks the transaction should be included within
+    pub blocks: u64,
+    /// The probability, in `(0, 1]`, with which it should be included
+    pub probability: f64,
+}
+
+impl InclusionTarget {
+    /// Creates a target of inclusion within `blocks` blocks with the given `probability`.
+    ///
+    /// # Panics
+    ///
+    /// If `blocks` is zero or `probability` is not in `(0, 1]`.
+    pub fn new(blocks: u64, probability: f64) -> Self {
+        assert!(blocks > 0, "inclusion target must be at least one block");
+        assert!(probability > 0.0 && probability <= 1.0, "probability must be in (0, 1]");
+        Self { blocks, probability }
+    }
+
+    /// The default target of the given category.
+    pub fn for_category(category: GasCategory) -> Self {
+        match category {
+            GasCategory::SafeLow => Self::new(10, 0.9),
+            GasCategory::Standard => Self::new(3, 0.9),
+            GasCategory::Fast => Self::new(1, 0.9),
+            GasCategory::Fastest => Self::new(1, 0.99),
+        }
+    }
+
+    /// The probability with which a single block must include the transaction for it to be
+    /// included within `blocks` blocks with `probability`.
+    fn per_block_probability(&self) -> f64 {
+        1.0 - (1.0 - self.probability).powf(1.0 / self.blocks as f64)
+    }
+}
+
+/// The fee data of a single block in the window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockFees {
+    /// The block number
+    pub number: u64,
+    /// The base fee per gas of the block
+    pub base_fee_per_gas: U256,
+    /// The ratio of gas used to the gas limit of the block
+    pub gas_used_ratio: f64,
+    /// The priority fee paid at the oracle's reward percentile, i.e. the lowest priority fee
+    /// that was competitive in this block
+    pub priority_fee: U256,
+}
+
+#[derive(Debug, Default)]
+struct FeeWindow {
+    blocks: VecDeque<BlockFees>,
+    /// The base fee of the block following the most recent one in the window
+    next_base_fee: U256,
+    /// Whether [`FeeHistoryOracle::watch`] is keeping the window up to date
+    watching: bool,
+}
+
+/// A local gas oracle based on `eth_feeHistory`.
+///
+/// The oracle keeps a rolling window of the base fees and of the priority fees paid in recent
+/// blocks. Priority fees are estimated so that a transaction is included within a number of
+/// blocks with a given probability (see [`InclusionTarget`]), and the base fee is projected that
+/// many blocks ahead with [`Block::next_block_base_fee`], assuming every block is full.
+///
+/// Every [`GasCategory`] maps to a configurable [`InclusionTarget`], which makes this oracle a
+/// drop-in replacement for the third party gas APIs.
+///
+/// The window is refreshed on every estimate, unless it is kept up to date by
+/// [`FeeHistoryOracle::watch`].
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::gas_oracle::{FeeHistoryOracle, GasCategory, GasOracle, InclusionTarget};
+/// use ethers_providers::{Http, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let oracle = FeeHistoryOracle::new(provider)
+///     .target(GasCategory::Fast, InclusionTarget::new(2, 0.95))
+///     .category(GasCategory::Fast);
+/// let (max_fee, priority_fee) = oracle.estimate_eip1559_fees().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct FeeHistoryOracle<M: Middleware> {
+    provider: M,
+    window: u64,
+    reward_percentile: f64,
+    category: GasCategory,
+    targets: [InclusionTarget; 4],
+    history: Arc<RwLock<FeeWindow>>,
+}
+
+impl<M: Middleware> FeeHistoryOracle<M> {
+    /// Creates an oracle with the default window, reward percentile and inclusion targets, which
+    /// estimates the fees of the [`GasCategory::Standard`] category.
+    pub fn new(provider: M) -> Self {
+        Self {
+            provider,
+            window: DEFAULT_WINDOW,
+            reward_percentile: DEFAULT_REWARD_PERCENTILE,
+            category: GasCategory::Standard,
+            targets: [
+                InclusionTarget::for_category(GasCategory::SafeLow),
+                InclusionTarget::for_category(GasCategory::Standard),
+                InclusionTarget::for_category(GasCategory::Fast),
+                InclusionTarget::for_category(GasCategory::Fastest),
+            ],
+            history: Default::default(),
+        }
+    }
+
+    /// Sets the number of blocks kept in the window.
+    pub fn window(mut self, window: u64) -> Self {
+        assert!(window > 0, "fee history window must not be empty");
+        self.window = window;
+        self
+    }
+
+    /// Sets the reward percentile whose priority fee is considered the lowest competitive one of
+    /// a block.
+    pub fn reward_percentile(mut self, percentile: f64) -> Self {
+        assert!((0.0..=100.0).contains(&percentile), "percentile must be in [0, 100]");
+        self.reward_percentile = percentile;
+        self
+    }
+
+    /// Sets the gas category used by [`GasOracle`] estimates.
+    pub fn category(mut self, category: GasCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Sets the inclusion target of the given gas category.
+    pub fn target(mut self, category: GasCategory, target: InclusionTarget) -> Self {
+        self.targets[category as usize] = target;
+        self
+    }
+
+    /// Returns the fee data of the blocks currently in the window, oldest first.
+    pub async fn history(&self) -> Vec<BlockFees> {
+        self.history.read().await.blocks.iter().cloned().collect()
+    }
+
+    /// Fetches the fee history of the blocks mined since the last update.
+    pub async fn update(&self) -> Result<()>
+    where
+        M::Error: 'static,
+    {
+        let latest = self.provider.get_block_number().await.map_err(provider_error)?.as_u64();
+
+        let mut history = self.history.write().await;
+        let first = match history.blocks.back() {
+            Some(last) if last.number >= latest => return Ok(()),
+            Some(last) => std::cmp::max(last.number + 1, latest.saturating_sub(self.window - 1)),
+            None => latest.saturating_sub(self.window - 1),
+        };
+        let fee_history = self
+            .provider
+            .fee_history(
+                latest - first + 1,
+                BlockNumber::Number(latest.into()),
+                &[self.reward_percentile],
+            )
+            .await
+            .map_err(provider_error)?;
+
+        let len = fee_history.gas_used_ratio.len();
+        // the base fees include the one of the block following the requested range
+        if fee_history.base_fee_per_gas.len() != len + 1 {
+            return Err(GasOracleError::Eip1559EstimationNotSupported)
+        }
+        if fee_history.reward.len() != len {
+            return Err(GasOracleError::InvalidResponse)
+        }
+
+        let oldest_block = fee_history.oldest_block.as_u64();
+        for (i, (gas_used_ratio, reward)) in
+            fee_history.gas_used_ratio.into_iter().zip(fee_history.reward).enumerate()
+        {
+            let number = oldest_block + i as u64;
+            // the fee history of the first block is known already if it raced another update
+            if history.blocks.back().map_or(false, |last| last.number >= number) {
+                continue
+            }
+            history.blocks.push_back(BlockFees {
+                number,
+                base_fee_per_gas: fee_history.base_fee_per_gas[i],
+                gas_used_ratio,
+                priority_fee: reward.first().copied().unwrap_or_default(),
+            });
+        }
+        history.next_base_fee = fee_history.base_fee_per_gas[len];
+        while history.blocks.len() as u64 > self.window {
+            history.blocks.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Keeps the window up to date by updating it on every new block, until the block stream
+    /// ends.
+    ///
+    /// While this future is running, estimates do not make any requests to the provider. It is
+    /// meant to be spawned on a clone of the oracle.
+    pub async fn watch(&self) -> Result<()>
+    where
+        M::Error: 'static,
+    {
+        let mut blocks = self.provider.watch_blocks().await.map_err(provider_error)?;
+        self.update().await?;
+        self.history.write().await.watching = true;
+
+        let result = async {
+            while blocks.next().await.is_some() {
+                self.update().await?;
+            }
+            Ok(())
+        }
+        .await;
+
+        self.history.write().await.watching = false;
+        result
+    }
+
+    /// Estimates the `(max_fee_per_gas, max_priority_fee_per_gas)` for a transaction to be
+    /// included according to the given target.
+    pub async fn estimate(&self, target: InclusionTarget) -> Result<(U256, U256)>
+    where
+        M::Error: 'static,
+    {
+        let watching = {
+            let history = self.history.read().await;
+            history.watching && !history.blocks.is_empty()
+        };
+        if !watching {
+            self.update().await?;
+        }
+
+        let history = self.history.read().await;
+        let priority_fee = estimate_priority_fee(&history.blocks, target)?;
+        let base_fee = project_base_fee(history.next_base_fee, target.blocks);
+        Ok((base_fee + priority_fee, priority_fee))
+    }
+}
+
+/// Returns the lowest priority fee which was competitive in enough blocks of the window for
+/// the target to be met.
+fn estimate_priority_fee(blocks: &VecDeque<BlockFees>, target: InclusionTarget) -> Result<U256> {
+    let mut fees = blocks.iter().map(|block| block.priority_fee).collect::<Vec<_>>();
+    if fees.is_empty() {
+        return Err(GasOracleError::NoValues)
+    }
+    fees.sort_unstable();
+
+    // a fee which was competitive in a share `q` of the blocks is included within `n` blocks
+    // with probability `1 - (1 - q)^n`
+    let share = target.per_block_probability();
+    let idx = ((share * fees.len() as f64).ceil() as usize).clamp(1, fees.len()) - 1;
+    Ok(fees[idx])
+}
+
+/// Projects the base fee `blocks` blocks after the next one, assuming they are all full.
+fn project_base_fee(next_base_fee: U256, blocks: u64) -> U256 {
+    let gas_limit = U256::from(30_000_000u64);
+    let mut block = Block::<TxHash> {
+        base_fee_per_gas: Some(next_base_fee),
+        gas_limit,
+        gas_used: gas_limit,
+        ..Default::default()
+    };
+    for _ in 1..blocks {
+        block.base_fee_per_gas = block.next_block_base_fee();
+    }
+    block.base_fee_per_gas.unwrap_or_default()
+}
+
+fn provider_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> GasOracleError {
+    GasOracleError::ProviderError(Box::new(err))
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> GasOracle for FeeHistoryOracle<M>
+where
+    M::Error: 'static,
+{
+    async fn fetch(&self) -> Result<U256> {
+        let (max_fee, _) = self.estimate_eip1559_fees().await?;
+        Ok(max_fee)
+    }
+
+    async fn estimate_eip1559_fees(&self) -> Result<(U256, U256)> {
+        self.estimate(self.targets[self.category as usize]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::FeeHistory;
+    use ethers_providers::Provider;
+
+    #[test]
+    fn projects_full_blocks() {
+        assert_eq!(project_base_fee(1000.into(), 1), 1000.into());
+        assert_eq!(project_base_fee(1000.into(), 2), 1125.into());
+        assert_eq!(project_base_fee(1000.into(), 3), 1265.into());
+    }
+
+    #[test]
+    fn priority_fee_meets_inclusion_probability() {
+        let blocks = (1..=10u64)
+            .map(|i| BlockFees {
+                number: i,
+                base_fee_per_gas: 100.into(),
+                gas_used_ratio: 0.5,
+                priority_fee: (i * 10).into(),
+            })
+            .collect::<VecDeque<_>>();
+
+        // a single block needs a fee that was competitive in 90% of the blocks
+        let fee = estimate_priority_fee(&blocks, InclusionTarget::new(1, 0.9)).unwrap();
+        assert_eq!(fee, 90.into());
+        // 1 - 0.1^(1/3) ~= 0.54
+        let fee = estimate_priority_fee(&blocks, InclusionTarget::new(3, 0.9)).unwrap();
+        assert_eq!(fee, 60.into());
+        let fee = estimate_priority_fee(&blocks, InclusionTarget::new(1, 1.0)).unwrap();
+        assert_eq!(fee, 100.into());
+        assert!(estimate_priority_fee(&VecDeque::new(), InclusionTarget::new(1, 0.5)).is_err());
+    }
+
+    #[tokio::test]
+    async fn keeps_rolling_window() {
+        let (provider, mock) = Provider::mocked();
+        let oracle = FeeHistoryOracle::new(provider).window(3);
+
+        let fee_history = |oldest: u64, rewards: &[u64]| FeeHistory {
+            base_fee_per_gas: vec![100.into(); rewards.len() + 1],
+            gas_used_ratio: vec![0.5; rewards.len()],
+            oldest_block: oldest.into(),
+            reward: rewards.iter().map(|r| vec![(*r).into()]).collect(),
+        };
+        // responses are popped in reverse order
+        mock.push(fee_history(11, &[40])).unwrap();
+        mock.push(ethers_core::types::U64::from(11)).unwrap();
+        mock.push(fee_history(8, &[10, 20, 30])).unwrap();
+        mock.push(ethers_core::types::U64::from(10)).unwrap();
+
+        oracle.update().await.unwrap();
+        let (max_fee, priority_fee) = oracle.estimate(InclusionTarget::new(1, 1.0)).await.unwrap();
+        assert_eq!(priority_fee, 40.into());
+        assert_eq!(max_fee, 140.into());
+
+        let history = oracle.history().await;
+        assert_eq!(history.iter().map(|b| b.number).collect::<Vec<_>>(), vec![9, 10, 11]);
+    }
+}
@@ -25,6 +25,11 @@ pub use cache::Cache;
 pub mod polygon;
 pub use polygon::Polygon;
 
+#[cfg(not(feature = "celo"))]
+pub mod fee_history;
+#[cfg(not(feature = "celo"))]
+pub use fee_history::{FeeHistoryOracle, InclusionTarget};
+
 pub mod gas_now;
 pub use gas_now::GasNow;
 
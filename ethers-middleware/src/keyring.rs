@@ -0,0 +1,381 @@
+use crate::{signer::SignerMiddlewareError, SignerMiddleware};
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed, eip712::Eip712},
+    Address, BlockId, Bytes, Signature, U256,
+};
+use ethers_providers::{maybe, Middleware, MiddlewareError, PendingTransaction};
+use ethers_signers::{DynSigner, Signer};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Clone, Debug)]
+/// Middleware used for locally signing transactions with several accounts.
+///
+/// Signers of any type, e.g. local wallets, Ledgers and AWS KMS keys, are held behind a
+/// [`DynSigner`], each in a [`SignerMiddleware`] over a clone of the inner middleware, and are
+/// picked by the `from` field of transactions. Transactions without a `from` field are sent from
+/// the default sender, which is the first signer added unless set with
+/// [`KeyringMiddleware::with_default_sender`]. Transactions from addresses without a signer are
+/// delegated to the inner middleware.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::TransactionRequest;
+/// use ethers_middleware::KeyringMiddleware;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::{LocalWallet, Signer};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let alice = LocalWallet::new(&mut rand::thread_rng());
+/// let bob = LocalWallet::new(&mut rand::thread_rng());
+/// let bob_address = bob.address();
+///
+/// let client = KeyringMiddleware::new(provider).with_signer(alice).with_signer(bob);
+///
+/// // signed by alice, the default sender
+/// let tx = TransactionRequest::pay("vitalik.eth", 100);
+/// client.send_transaction(tx, None).await?;
+///
+/// // signed by bob
+/// let tx = TransactionRequest::pay("vitalik.eth", 100).from(bob_address);
+/// client.send_transaction(tx, None).await?;
+///
+/// let signature = client.sign(b"hello".to_vec(), &bob_address).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct KeyringMiddleware<M> {
+    pub(crate) inner: M,
+    pub(crate) signers: HashMap<Address, SignerMiddleware<M, DynSigner>>,
+    pub(crate) default_sender: Option<Address>,
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the client interacts with the blockchain
+pub enum KeyringMiddlewareError<M: Middleware> {
+    #[error(transparent)]
+    /// Thrown when signing with one of the signers fails
+    SignerError(SignerMiddlewareError<M, DynSigner>),
+
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    /// Thrown if a signature is requested from an address without a signer
+    #[error("no signer for {0:?}")]
+    UnknownSigner(Address),
+    /// Thrown if the transaction has no `from` field and there is no default sender
+    #[error("no sender was specified")]
+    SenderMissing,
+}
+
+impl<M: Middleware> MiddlewareError for KeyringMiddlewareError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        KeyringMiddlewareError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            KeyringMiddlewareError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl<M: Middleware> From<SignerMiddlewareError<M, DynSigner>> for KeyringMiddlewareError<M> {
+    fn from(err: SignerMiddlewareError<M, DynSigner>) -> Self {
+        match err {
+            SignerMiddlewareError::MiddlewareError(e) => KeyringMiddlewareError::MiddlewareError(e),
+            err => KeyringMiddlewareError::SignerError(err),
+        }
+    }
+}
+
+impl<M> KeyringMiddleware<M>
+where
+    M: Middleware + Clone,
+{
+    /// Creates a new client without any signer.
+    pub fn new(inner: M) -> Self {
+        Self { inner, signers: HashMap::new(), default_sender: None }
+    }
+
+    /// Adds a signer to the keyring, replacing any signer with the same address.
+    #[must_use]
+    pub fn with_signer<S>(mut self, signer: S) -> Self
+    where
+        S: Signer + 'static,
+        S::Error: 'static,
+    {
+        self.add_signer(signer);
+        self
+    }
+
+    /// Sets the sender of transactions without a `from` field.
+    #[must_use]
+    pub fn with_default_sender(mut self, address: Address) -> Self {
+        self.default_sender = Some(address);
+        self
+    }
+
+    /// Adds a signer to the keyring, replacing any signer with the same address, and returns
+    /// its address.
+    pub fn add_signer<S>(&mut self, signer: S) -> Address
+    where
+        S: Signer + 'static,
+        S::Error: 'static,
+    {
+        let address = signer.address();
+        let client = SignerMiddleware::new(self.inner.clone(), DynSigner::new(signer));
+        self.signers.insert(address, client);
+        self.default_sender.get_or_insert(address);
+        address
+    }
+
+    /// Removes the signer of the address from the keyring.
+    ///
+    /// If the address was the default sender, the keyring has no default sender until one is set
+    /// or another signer is added.
+    pub fn remove_signer(&mut self, address: &Address) -> Option<DynSigner> {
+        let client = self.signers.remove(address)?;
+        if self.default_sender == Some(*address) {
+            self.default_sender = None;
+        }
+        Some(client.signer)
+    }
+
+    /// Returns the signer of the address.
+    pub fn signer(&self, address: &Address) -> Option<&DynSigner> {
+        self.signers.get(address).map(SignerMiddleware::signer)
+    }
+
+    /// Returns the addresses of the signers of the keyring.
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.signers.keys()
+    }
+
+    /// Sets the chain id of every signer to the chain id of the inner [`Middleware`].
+    pub async fn with_provider_chain(mut self) -> Result<Self, KeyringMiddlewareError<M>> {
+        let chain_id =
+            self.inner.get_chainid().await.map_err(KeyringMiddlewareError::MiddlewareError)?;
+        for client in self.signers.values_mut() {
+            *client = client.with_signer(client.signer().clone().with_chain_id(chain_id.as_u64()));
+        }
+        Ok(self)
+    }
+
+    /// Signs EIP-712 typed data with the signer of the address.
+    pub async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+        from: &Address,
+    ) -> Result<Signature, KeyringMiddlewareError<M>> {
+        self.signer_of(from)?
+            .signer()
+            .sign_typed_data(payload)
+            .await
+            .map_err(|e| SignerMiddlewareError::SignerError(e).into())
+    }
+
+    fn signer_of(
+        &self,
+        address: &Address,
+    ) -> Result<&SignerMiddleware<M, DynSigner>, KeyringMiddlewareError<M>> {
+        self.signers.get(address).ok_or(KeyringMiddlewareError::UnknownSigner(*address))
+    }
+
+    fn sender(&self, tx: &TypedTransaction) -> Result<Address, KeyringMiddlewareError<M>> {
+        tx.from().copied().or(self.default_sender).ok_or(KeyringMiddlewareError::SenderMissing)
+    }
+
+    fn set_tx_from_if_none(&self, tx: &TypedTransaction) -> TypedTransaction {
+        let mut tx = tx.clone();
+        if let (None, Some(from)) = (tx.from(), self.default_sender) {
+            tx.set_from(from);
+        }
+        tx
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for KeyringMiddleware<M>
+where
+    M: Middleware + Clone,
+{
+    type Error = KeyringMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Returns the default sender of the keyring
+    fn default_sender(&self) -> Option<Address> {
+        self.default_sender
+    }
+
+    /// `KeyringMiddleware` is instantiated with signers.
+    async fn is_signer(&self) -> bool {
+        true
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &TypedTransaction,
+        from: Address,
+    ) -> Result<Signature, Self::Error> {
+        Ok(Middleware::sign_transaction(self.signer_of(&from)?, tx, from).await?)
+    }
+
+    /// Fills the transaction with the signer of its sender, or with the inner middleware if the
+    /// keyring does not hold it.
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        let from = self.sender(tx)?;
+        tx.set_from(from);
+        if let Some(client) = self.signers.get(&from) {
+            return Ok(client.fill_transaction(tx, block).await?)
+        }
+
+        let nonce = maybe(tx.nonce().cloned(), self.get_transaction_count(from, block)).await?;
+        tx.set_nonce(nonce);
+        self.inner()
+            .fill_transaction(tx, block)
+            .await
+            .map_err(KeyringMiddlewareError::MiddlewareError)?;
+        Ok(())
+    }
+
+    /// Signs the transaction with the signer of its `from` address and broadcasts it. If the
+    /// keyring does not hold a signer for that address, the transaction is delegated to the
+    /// inner middleware.
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        let from = self.sender(&tx)?;
+        tx.set_from(from);
+        if let Some(client) = self.signers.get(&from) {
+            return Ok(client.send_transaction(tx, block).await?)
+        }
+
+        self.fill_transaction(&mut tx, block).await?;
+        self.inner
+            .send_transaction(tx, block)
+            .await
+            .map_err(KeyringMiddlewareError::MiddlewareError)
+    }
+
+    /// Signs a message with the signer of the address, or if the keyring does not hold it, makes
+    /// a call to the connected node's `eth_sign` API.
+    async fn sign<T: Into<Bytes> + Send + Sync>(
+        &self,
+        data: T,
+        from: &Address,
+    ) -> Result<Signature, Self::Error> {
+        match self.signers.get(from) {
+            Some(client) => Ok(client.sign(data, from).await?),
+            None => {
+                self.inner.sign(data, from).await.map_err(KeyringMiddlewareError::MiddlewareError)
+            }
+        }
+    }
+
+    async fn estimate_gas(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let tx = self.set_tx_from_if_none(tx);
+        self.inner.estimate_gas(&tx, block).await.map_err(KeyringMiddlewareError::MiddlewareError)
+    }
+
+    async fn create_access_list(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<AccessListWithGasUsed, Self::Error> {
+        let tx = self.set_tx_from_if_none(tx);
+        self.inner
+            .create_access_list(&tx, block)
+            .await
+            .map_err(KeyringMiddlewareError::MiddlewareError)
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let tx = self.set_tx_from_if_none(tx);
+        self.inner().call(&tx, block).await.map_err(KeyringMiddlewareError::MiddlewareError)
+    }
+}
+
+#[cfg(all(test, not(feature = "celo")))]
+mod tests {
+    use super::*;
+    use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest, H256};
+    use ethers_providers::Provider;
+    use ethers_signers::LocalWallet;
+
+    fn wallet(key: u8) -> LocalWallet {
+        LocalWallet::from_bytes(&[key; 32]).unwrap().with_chain_id(1u64)
+    }
+
+    #[tokio::test]
+    async fn routes_by_from_address() {
+        let (provider, mock) = Provider::mocked();
+        let (alice, bob) = (wallet(1), wallet(2));
+        let client = KeyringMiddleware::new(provider)
+            .with_signer(alice.clone())
+            .with_signer(bob.clone())
+            .with_default_sender(bob.address());
+        assert_eq!(client.default_sender(), Some(bob.address()));
+
+        let tx = TransactionRequest::pay(Address::zero(), 1).gas(21000).gas_price(1).nonce(0);
+        for (tx, signer) in [(tx.clone().from(alice.address()), &alice), (tx.clone(), &bob)] {
+            mock.push(H256::zero()).unwrap();
+            client.send_transaction(tx.clone(), None).await.unwrap();
+
+            let expected: TypedTransaction = tx.from(signer.address()).chain_id(1).into();
+            let signature = signer.sign_transaction(&expected).await.unwrap();
+            mock.assert_request("eth_sendRawTransaction", [expected.rlp_signed(&signature)])
+                .unwrap();
+        }
+
+        let signature = client.sign(b"hello".to_vec(), &alice.address()).await.unwrap();
+        assert_eq!(signature, alice.sign_message(b"hello").await.unwrap());
+        let tx = TransactionRequest::new().into();
+        let err = client.sign_transaction(&tx, Address::zero()).await.unwrap_err();
+        assert!(matches!(err, KeyringMiddlewareError::UnknownSigner(_)));
+    }
+
+    #[test]
+    fn removing_default_signer_clears_default_sender() {
+        let (provider, _) = Provider::mocked();
+        let (alice, bob) = (wallet(1), wallet(2));
+        let mut client = KeyringMiddleware::new(provider).with_signer(alice.clone());
+        client.add_signer(bob.clone());
+        assert_eq!(client.default_sender(), Some(alice.address()));
+
+        client.remove_signer(&alice.address()).unwrap();
+        assert_eq!(client.default_sender(), None);
+        client.remove_signer(&bob.address()).unwrap();
+        client.add_signer(bob.clone());
+        assert_eq!(client.default_sender(), Some(bob.address()));
+    }
+}
@@ -29,6 +29,11 @@ pub use transformer::TransformerMiddleware;
 pub mod signer;
 pub use signer::SignerMiddleware;
 
+/// The [KeyringMiddleware] is used to locally sign transactions with several accounts, picking
+/// the signer by the `from` field of each transaction.
+pub mod keyring;
+pub use keyring::KeyringMiddleware;
+
 /// The [Policy] is used to ensure transactions comply with the rules configured in the
 /// [`PolicyMiddleware`] before sending them.
 pub mod policy;
@@ -41,6 +46,16 @@ pub use policy::{
 pub mod timelag;
 pub use timelag::TimeLag;
 
+/// The [MulticallBatchingMiddleware] batches concurrent `eth_call`s into a single Multicall3
+/// `aggregate3` call.
+pub mod multicall_batching;
+pub use multicall_batching::MulticallBatchingMiddleware;
+
+/// The [VerifyingMiddleware] verifies account and storage queries with Merkle proofs against a
+/// trusted block header.
+pub mod verifying;
+pub use verifying::VerifyingMiddleware;
+
 /// [MiddlewareBuilder] provides a way to compose many [`Middleware`]s in a concise way.
 pub mod builder;
 pub use builder::MiddlewareBuilder;
@@ -0,0 +1,411 @@
+use async_trait::async_trait;
+use ethers_contract::{
+    multicall_contract::{Aggregate3Call, Aggregate3Return, Call3},
+    MULTICALL_ADDRESS,
+};
+use ethers_core::{
+    abi::{AbiDecode, AbiEncode},
+    types::{transaction::eip2718::TypedTransaction, *},
+};
+use ethers_providers::{JsonRpcError, Middleware, MiddlewareError};
+use futures_channel::oneshot;
+use futures_timer::Delay;
+use futures_util::future::select;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use thiserror::Error;
+
+/// The default time the first call of a batch waits for more calls to join.
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(10);
+
+/// The default maximum number of calls in a single batch.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+/// Middleware that transparently batches concurrent `eth_call`s into a single Multicall3
+/// `aggregate3` call.
+///
+/// The first call at a given block tag opens a batch and waits for [`window`](Self::window) or
+/// until the batch holds [`max_batch_size`](Self::max_batch_size) calls, whichever comes first.
+/// All calls at the same block tag that are issued in the meantime join the batch. The results of
+/// the `aggregate3` call are then handed back to the individual callers, and a reverting call
+/// receives an error with its own revert data.
+///
+/// Calls are only batched if they can be executed by the Multicall3 contract without changing
+/// their result, so calls with a `from` address, a non-zero `value` or an ENS name as `to` are
+/// always sent directly. Calls with state overrides go through
+/// [`RawCall`](ethers_providers::RawCall) on the provider and are never batched. Note that batched
+/// calls observe the Multicall3 contract as `msg.sender`.
+///
+/// If the `aggregate3` call itself fails, for example because Multicall3 is not deployed on the
+/// chain, every call of the batch is retried on its own.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::MulticallBatchingMiddleware;
+/// use ethers_providers::{Http, Provider};
+/// use std::{convert::TryFrom, time::Duration};
+///
+/// let provider = Provider::<Http>::try_from("http://localhost:8545").unwrap();
+/// let provider = MulticallBatchingMiddleware::new(provider)
+///     .window(Duration::from_millis(20))
+///     .max_batch_size(50);
+/// ```
+#[derive(Debug)]
+pub struct MulticallBatchingMiddleware<M> {
+    inner: M,
+    multicall_address: Address,
+    window: Duration,
+    max_batch_size: usize,
+    next_batch_id: AtomicU64,
+    batches: Mutex<Vec<PendingBatch>>,
+}
+
+/// A batch of calls that has not been sent yet
+#[derive(Debug)]
+struct PendingBatch {
+    id: u64,
+    block: Option<BlockId>,
+    calls: Vec<(Call3, oneshot::Sender<BatchedResult>)>,
+    /// Wakes up the leader of the batch once it is full
+    full: Option<oneshot::Sender<()>>,
+}
+
+/// Removes a pending batch when its leader is dropped, so the remaining calls of the batch fall
+/// back to individual calls instead of waiting forever
+struct BatchGuard<'a> {
+    batches: &'a Mutex<Vec<PendingBatch>>,
+    id: u64,
+}
+
+impl BatchGuard<'_> {
+    fn take(&self) -> Option<PendingBatch> {
+        let mut batches = self.batches.lock().unwrap();
+        let idx = batches.iter().position(|b| b.id == self.id)?;
+        Some(batches.remove(idx))
+    }
+}
+
+impl Drop for BatchGuard<'_> {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+/// The result of a single call of a batch
+#[derive(Debug)]
+enum BatchedResult {
+    Success(Bytes),
+    Revert(Bytes),
+    /// The batch could not be executed, the call should be sent on its own
+    Fallback,
+}
+
+impl<M> MulticallBatchingMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Creates a new batching middleware that uses the Multicall3 contract at
+    /// [`MULTICALL_ADDRESS`].
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            multicall_address: MULTICALL_ADDRESS,
+            window: DEFAULT_BATCH_WINDOW,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            next_batch_id: Default::default(),
+            batches: Default::default(),
+        }
+    }
+
+    /// Sets the address of the Multicall3 contract.
+    #[must_use]
+    pub fn multicall_address(mut self, address: Address) -> Self {
+        self.multicall_address = address;
+        self
+    }
+
+    /// Sets how long the first call of a batch waits for more calls.
+    #[must_use]
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets the maximum number of calls in a batch. A full batch is sent immediately.
+    #[must_use]
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    /// Returns the target and calldata of the call if it can be batched.
+    fn batchable(&self, tx: &TypedTransaction) -> Option<Call3> {
+        let target = match tx.to()? {
+            NameOrAddress::Address(addr) => *addr,
+            NameOrAddress::Name(_) => return None,
+        };
+        if tx.from().is_some() || tx.value().map(|v| !v.is_zero()).unwrap_or_default() {
+            return None
+        }
+        let call_data = tx.data().cloned().unwrap_or_default();
+        Some(Call3 { target, allow_failure: true, call_data })
+    }
+
+    async fn batched_call(
+        &self,
+        call: Call3,
+        block: Option<BlockId>,
+    ) -> Result<BatchedResult, MulticallBatchingError<M>> {
+        let (tx, rx) = oneshot::channel();
+
+        // join a pending batch at the same block or open a new one
+        let leader = {
+            let mut batches = self.batches.lock().unwrap();
+            match batches.iter_mut().find(|b| b.block == block && b.full.is_some()) {
+                Some(batch) => {
+                    batch.calls.push((call, tx));
+                    if batch.calls.len() >= self.max_batch_size {
+                        let _ = batch.full.take().map(|full| full.send(()));
+                    }
+                    None
+                }
+                None => {
+                    let id = self.next_batch_id.fetch_add(1, Ordering::Relaxed);
+                    let (full_tx, full_rx) = oneshot::channel();
+                    let mut batch =
+                        PendingBatch { id, block, calls: vec![(call, tx)], full: Some(full_tx) };
+                    if self.max_batch_size == 1 {
+                        batch.full = None;
+                    }
+                    batches.push(batch);
+                    Some((id, full_rx))
+                }
+            }
+        };
+
+        if let Some((id, full_rx)) = leader {
+            // wait until the window elapsed or the batch is full
+            let guard = BatchGuard { batches: &self.batches, id };
+            let _ = select(Delay::new(self.window), full_rx).await;
+            if let Some(batch) = guard.take() {
+                self.send_batch(batch).await;
+            }
+        }
+
+        // if the leader was dropped before sending the batch, send the call on its own
+        Ok(rx.await.unwrap_or(BatchedResult::Fallback))
+    }
+
+    /// Executes the batch and hands the results to the callers.
+    async fn send_batch(&self, batch: PendingBatch) {
+        let PendingBatch { block, calls, .. } = batch;
+        if calls.len() == 1 {
+            // not worth the multicall overhead
+            for (_, tx) in calls {
+                let _ = tx.send(BatchedResult::Fallback);
+            }
+            return
+        }
+
+        let (calls, senders): (Vec<_>, Vec<_>) = calls.into_iter().unzip();
+        let data = Aggregate3Call { calls }.encode();
+        let tx: TypedTransaction =
+            TransactionRequest::new().to(self.multicall_address).data(data).into();
+
+        let results = self
+            .inner
+            .call(&tx, block)
+            .await
+            .ok()
+            .and_then(|res| Aggregate3Return::decode(res).ok())
+            .map(|res| res.return_data)
+            .filter(|res| res.len() == senders.len());
+
+        match results {
+            Some(results) => {
+                for (result, sender) in results.into_iter().zip(senders) {
+                    let result = if result.success {
+                        BatchedResult::Success(result.return_data)
+                    } else {
+                        BatchedResult::Revert(result.return_data)
+                    };
+                    let _ = sender.send(result);
+                }
+            }
+            None => {
+                tracing::debug!(target: "ethers::multicall_batching", "aggregate3 call failed, falling back to individual calls");
+                for sender in senders {
+                    let _ = sender.send(BatchedResult::Fallback);
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for MulticallBatchingMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = MulticallBatchingError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        if let Some(call) = self.batchable(tx) {
+            match self.batched_call(call, block).await? {
+                BatchedResult::Success(data) => return Ok(data),
+                BatchedResult::Revert(data) => return Err(MulticallBatchingError::revert(data)),
+                BatchedResult::Fallback => {}
+            }
+        }
+        self.inner.call(tx, block).await.map_err(MiddlewareError::from_err)
+    }
+}
+
+/// Thrown when an error happens at the [`MulticallBatchingMiddleware`]
+#[derive(Error, Debug)]
+pub enum MulticallBatchingError<M: Middleware> {
+    /// A batched call reverted. This holds a JSON-RPC revert error with the revert data, like the
+    /// one a node returns for a reverting `eth_call`.
+    #[error("{0}")]
+    Revert(JsonRpcError),
+
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> MulticallBatchingError<M> {
+    fn revert(data: Bytes) -> Self {
+        MulticallBatchingError::Revert(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(data.to_string().into()),
+        })
+    }
+
+    /// Returns the revert data if a batched call reverted.
+    pub fn as_revert_data(&self) -> Option<Bytes> {
+        self.as_error_response()?.as_revert_data()
+    }
+}
+
+impl<M: Middleware> MiddlewareError for MulticallBatchingError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        MulticallBatchingError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            MulticallBatchingError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            MulticallBatchingError::Revert(e) => Some(e),
+            MulticallBatchingError::MiddlewareError(e) => e.as_error_response(),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use ethers_contract::multicall_contract::Result as MulticallResult;
+    use ethers_providers::{MockProvider, Provider};
+
+    fn call(to: Address, data: Vec<u8>) -> TypedTransaction {
+        TransactionRequest::new().to(to).data(data).into()
+    }
+
+    #[tokio::test]
+    async fn batches_concurrent_calls() {
+        let (provider, mock) = Provider::mocked();
+        let middleware = MulticallBatchingMiddleware::new(provider).window(Duration::from_secs(60));
+        let middleware = middleware.max_batch_size(2);
+
+        let response = Aggregate3Return {
+            return_data: vec![
+                MulticallResult { success: true, return_data: vec![1u8].into() },
+                MulticallResult { success: false, return_data: vec![2u8].into() },
+            ],
+        };
+        mock.push::<Bytes, _>(Bytes::from(response.encode())).unwrap();
+
+        let a = call(Address::repeat_byte(1), vec![0xaa]);
+        let b = call(Address::repeat_byte(2), vec![0xbb]);
+        let (a, b) = futures_util::join!(middleware.call(&a, None), middleware.call(&b, None));
+
+        assert_eq!(a.unwrap(), Bytes::from(vec![1u8]));
+        let err = b.unwrap_err();
+        assert_eq!(err.as_revert_data(), Some(Bytes::from(vec![2u8])));
+
+        // a single request to the multicall contract was made
+        let expected = Aggregate3Call {
+            calls: vec![
+                Call3 {
+                    target: Address::repeat_byte(1),
+                    allow_failure: true,
+                    call_data: vec![0xaa].into(),
+                },
+                Call3 {
+                    target: Address::repeat_byte(2),
+                    allow_failure: true,
+                    call_data: vec![0xbb].into(),
+                },
+            ],
+        };
+        let tx: TypedTransaction =
+            TransactionRequest::new().to(MULTICALL_ADDRESS).data(expected.encode()).into();
+        mock.assert_request("eth_call", (tx, BlockNumber::Latest)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn bypasses_calls_with_sender() {
+        let (provider, mock): (_, MockProvider) = Provider::mocked();
+        let middleware = MulticallBatchingMiddleware::new(provider);
+
+        mock.push::<Bytes, _>(Bytes::from(vec![3u8])).unwrap();
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(Address::repeat_byte(9))
+            .to(Address::repeat_byte(1))
+            .data(vec![0xaa])
+            .into();
+        let res = middleware.call(&tx, None).await.unwrap();
+        assert_eq!(res, Bytes::from(vec![3u8]));
+        mock.assert_request("eth_call", (tx, BlockNumber::Latest)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sends_single_call_directly() {
+        let (provider, mock) = Provider::mocked();
+        let middleware =
+            MulticallBatchingMiddleware::new(provider).window(Duration::from_millis(1));
+
+        mock.push::<Bytes, _>(Bytes::from(vec![4u8])).unwrap();
+        let tx = call(Address::repeat_byte(1), vec![0xaa]);
+        let res = middleware.call(&tx, None).await.unwrap();
+        assert_eq!(res, Bytes::from(vec![4u8]));
+        mock.assert_request("eth_call", (tx, BlockNumber::Latest)).unwrap();
+    }
+}
@@ -0,0 +1,394 @@
+use super::{
+    AllowSelectors, AllowTo, ArgumentRule, ChainId, DenySelectors, DenyTo, MaxFee, PolicySet,
+    SpendLimit, SpendStore,
+};
+use ethers_core::{
+    abi::{tokenize_json, AbiParser, ParamType, Token},
+    types::{
+        serde_helpers::{deserialize_stringified_numeric, deserialize_stringified_numeric_opt},
+        Address, Selector, U256,
+    },
+    utils::hex,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+use thiserror::Error;
+
+/// Error thrown when building a [`PolicySet`] from a [`PolicyConfig`].
+#[derive(Debug, Error)]
+pub enum PolicyConfigError {
+    /// Thrown when the config file could not be read
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Thrown when the config is not valid JSON
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Thrown when a selector is neither 4 hex bytes nor a function signature
+    #[error("invalid selector `{0}`")]
+    InvalidSelector(String),
+    /// Thrown when the period of a spend limit is zero
+    #[error("spend limit period must be at least one second")]
+    InvalidPeriod,
+    /// Thrown when a function signature can not be parsed
+    #[error("invalid function `{function}`: {message}")]
+    InvalidFunction { function: String, message: String },
+    /// Thrown when an argument constraint is invalid
+    #[error("invalid constraint on `{param}` of `{function}`: {message}")]
+    InvalidConstraint { function: String, param: String, message: String },
+}
+
+/// A serializable description of a [`PolicySet`], e.g. loaded from a JSON file.
+///
+/// # Example
+///
+/// ```
+/// use ethers_middleware::policy::PolicyConfig;
+///
+/// let config: PolicyConfig = serde_json::from_str(r#"{
+///     "rules": [
+///         { "type": "chain_id", "chain_id": 1 },
+///         { "type": "deny_selectors", "selectors": ["approve(address,uint256)"] },
+///         { "type": "spend_limit", "period_secs": 86400, "max_value": "1000000000000000000" },
+///         {
+///             "type": "arguments",
+///             "function": "transfer(address to, uint256 amount)",
+///             "constraints": [{ "param": "amount", "max": "1000000" }]
+///         }
+///     ]
+/// }"#).unwrap();
+/// let policy = config.build().unwrap();
+/// assert_eq!(policy.rules().len(), 4);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// The rules of the policy, all of which must be complied with
+    #[serde(default)]
+    pub rules: Vec<RuleConfig>,
+}
+
+/// A serializable description of a built-in [`Rule`](super::Rule).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleConfig {
+    /// See [`AllowTo`]
+    AllowTo { addresses: Vec<Address> },
+    /// See [`DenyTo`]
+    DenyTo { addresses: Vec<Address> },
+    /// See [`AllowSelectors`], selectors are either 4 hex bytes or function signatures
+    AllowSelectors { selectors: Vec<String> },
+    /// See [`DenySelectors`], selectors are either 4 hex bytes or function signatures
+    DenySelectors { selectors: Vec<String> },
+    /// See [`SpendLimit`], limits sharing a store must have distinct names
+    SpendLimit {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        period_secs: u64,
+        #[serde(
+            default,
+            deserialize_with = "deserialize_stringified_numeric_opt",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_value: Option<U256>,
+        #[serde(
+            default,
+            deserialize_with = "deserialize_stringified_numeric_opt",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_fees: Option<U256>,
+    },
+    /// See [`MaxFee`]
+    MaxFee {
+        #[serde(
+            default,
+            deserialize_with = "deserialize_stringified_numeric_opt",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_fee_per_gas: Option<U256>,
+        #[serde(
+            default,
+            deserialize_with = "deserialize_stringified_numeric_opt",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_priority_fee_per_gas: Option<U256>,
+        #[serde(
+            default,
+            deserialize_with = "deserialize_stringified_numeric_opt",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_gas: Option<U256>,
+    },
+    /// See [`ChainId`]
+    ChainId { chain_id: u64 },
+    /// See [`ArgumentRule`], the function is given as a human readable signature
+    Arguments {
+        function: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        to: Option<Address>,
+        constraints: Vec<ArgumentConstraint>,
+    },
+}
+
+/// A constraint on a decoded argument.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArgumentConstraint {
+    /// The name or index of the parameter
+    pub param: String,
+    /// The check the argument must pass
+    #[serde(flatten)]
+    pub check: ArgumentCheck,
+}
+
+/// The checks of an [`ArgumentConstraint`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgumentCheck {
+    /// The unsigned integer argument must be at most the value
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    Max(U256),
+    /// The unsigned integer argument must be at least the value
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    Min(U256),
+    /// The argument must be one of the values
+    OneOf(Vec<Value>),
+    /// The argument must not be any of the values
+    NoneOf(Vec<Value>),
+}
+
+/// An [`ArgumentCheck`] resolved against the parameter it applies to.
+enum CompiledCheck {
+    Max(U256),
+    Min(U256),
+    OneOf(Vec<Token>),
+    NoneOf(Vec<Token>),
+}
+
+impl PolicyConfig {
+    /// Reads the config from a JSON file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, PolicyConfigError> {
+        let content = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    /// Builds the policy, recording the spend limits in memory.
+    pub fn build(&self) -> Result<PolicySet, PolicyConfigError> {
+        self.build_with_store(Arc::new(super::MemorySpendStore::default()))
+    }
+
+    /// Builds the policy, recording the spend limits in the given store.
+    pub fn build_with_store(
+        &self,
+        store: Arc<dyn SpendStore>,
+    ) -> Result<PolicySet, PolicyConfigError> {
+        self.rules.iter().try_fold(PolicySet::new(), |set, rule| {
+            Ok(match rule {
+                RuleConfig::AllowTo { addresses } => set.rule(AllowTo::new(addresses.clone())),
+                RuleConfig::DenyTo { addresses } => set.rule(DenyTo::new(addresses.clone())),
+                RuleConfig::AllowSelectors { selectors } => {
+                    set.rule(AllowSelectors::new(parse_selectors(selectors)?))
+                }
+                RuleConfig::DenySelectors { selectors } => {
+                    set.rule(DenySelectors::new(parse_selectors(selectors)?))
+                }
+                RuleConfig::SpendLimit { name, period_secs, max_value, max_fees } => {
+                    if *period_secs == 0 {
+                        return Err(PolicyConfigError::InvalidPeriod)
+                    }
+                    let mut limit = SpendLimit::new(Duration::from_secs(*period_secs))
+                        .shared_store(store.clone());
+                    if let Some(name) = name {
+                        limit = limit.named(name.clone());
+                    }
+                    if let Some(max_value) = max_value {
+                        limit = limit.max_value(*max_value);
+                    }
+                    if let Some(max_fees) = max_fees {
+                        limit = limit.max_fees(*max_fees);
+                    }
+                    set.rule(limit)
+                }
+                RuleConfig::MaxFee { max_fee_per_gas, max_priority_fee_per_gas, max_gas } => {
+                    let mut rule = MaxFee::new();
+                    if let Some(max_fee_per_gas) = max_fee_per_gas {
+                        rule = rule.max_fee_per_gas(*max_fee_per_gas);
+                    }
+                    if let Some(max_priority_fee_per_gas) = max_priority_fee_per_gas {
+                        rule = rule.max_priority_fee_per_gas(*max_priority_fee_per_gas);
+                    }
+                    if let Some(max_gas) = max_gas {
+                        rule = rule.max_gas(*max_gas);
+                    }
+                    set.rule(rule)
+                }
+                RuleConfig::ChainId { chain_id } => set.rule(ChainId::new(*chain_id)),
+                RuleConfig::Arguments { function, to, constraints } => {
+                    let mut rule = argument_rule(function, constraints)?;
+                    if let Some(to) = to {
+                        rule = rule.to(*to);
+                    }
+                    set.rule(rule)
+                }
+            })
+        })
+    }
+}
+
+fn parse_selectors(selectors: &[String]) -> Result<Vec<Selector>, PolicyConfigError> {
+    selectors.iter().map(|s| parse_selector(s)).collect()
+}
+
+fn parse_selector(s: &str) -> Result<Selector, PolicyConfigError> {
+    if let Some(hex) = s.strip_prefix("0x") {
+        let bytes = hex::decode(hex).map_err(|_| PolicyConfigError::InvalidSelector(s.into()))?;
+        return bytes.try_into().map_err(|_| PolicyConfigError::InvalidSelector(s.into()))
+    }
+    AbiParser::default()
+        .parse_function(s)
+        .map(|function| function.short_signature())
+        .map_err(|_| PolicyConfigError::InvalidSelector(s.into()))
+}
+
+fn argument_rule(
+    signature: &str,
+    constraints: &[ArgumentConstraint],
+) -> Result<ArgumentRule, PolicyConfigError> {
+    let function = AbiParser::default().parse_function(signature).map_err(|err| {
+        PolicyConfigError::InvalidFunction { function: signature.into(), message: err.to_string() }
+    })?;
+
+    let mut checks = Vec::with_capacity(constraints.len());
+    for constraint in constraints {
+        let invalid = |message: String| PolicyConfigError::InvalidConstraint {
+            function: signature.into(),
+            param: constraint.param.clone(),
+            message,
+        };
+        let idx = function
+            .inputs
+            .iter()
+            .position(|input| input.name == constraint.param)
+            .or_else(|| constraint.param.parse().ok().filter(|idx| *idx < function.inputs.len()))
+            .ok_or_else(|| invalid("unknown parameter".into()))?;
+        let kind = &function.inputs[idx].kind;
+        let tokenize = |values: &[Value]| {
+            values
+                .iter()
+                .map(|value| tokenize_json(kind, value))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| invalid(err.to_string()))
+        };
+        let check = match &constraint.check {
+            ArgumentCheck::Max(_) | ArgumentCheck::Min(_)
+                if !matches!(kind, ParamType::Uint(_)) =>
+            {
+                return Err(invalid(format!("`{kind}` is not an unsigned integer")))
+            }
+            ArgumentCheck::Max(max) => CompiledCheck::Max(*max),
+            ArgumentCheck::Min(min) => CompiledCheck::Min(*min),
+            ArgumentCheck::OneOf(values) => CompiledCheck::OneOf(tokenize(values)?),
+            ArgumentCheck::NoneOf(values) => CompiledCheck::NoneOf(tokenize(values)?),
+        };
+        checks.push((idx, constraint.param.clone(), check));
+    }
+
+    Ok(ArgumentRule::from_function(function, move |args| {
+        for (idx, param, check) in &checks {
+            let arg = &args[*idx];
+            let allowed = match (check, arg) {
+                (CompiledCheck::Max(max), Token::Uint(value)) => value <= max,
+                (CompiledCheck::Min(min), Token::Uint(value)) => value >= min,
+                (CompiledCheck::Max(_) | CompiledCheck::Min(_), _) => false,
+                (CompiledCheck::OneOf(values), arg) => values.contains(arg),
+                (CompiledCheck::NoneOf(values), arg) => !values.contains(arg),
+            };
+            if !allowed {
+                return Err(format!("argument `{param}` is not allowed: {arg}"))
+            }
+        }
+        Ok(())
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Policy, PolicyError};
+    use ethers_core::{
+        abi::AbiEncode,
+        types::{transaction::eip2718::TypedTransaction, TransactionRequest},
+    };
+
+    #[tokio::test]
+    async fn enforces_configured_rules() {
+        let token = Address::random();
+        let config: PolicyConfig = serde_json::from_value(serde_json::json!({
+            "rules": [
+                { "type": "allow_to", "addresses": [token] },
+                { "type": "allow_selectors", "selectors": ["0xa9059cbb"] },
+                {
+                    "type": "arguments",
+                    "function": "transfer(address to, uint256 amount)",
+                    "to": token,
+                    "constraints": [{ "param": "amount", "max": "1000" }]
+                },
+                { "type": "spend_limit", "period_secs": 86400, "max_value": 10 },
+            ]
+        }))
+        .unwrap();
+        let policy = config.build().unwrap();
+
+        let transfer = |amount: u64, value: u64| -> TypedTransaction {
+            let mut data = parse_selector("transfer(address,uint256)").unwrap().to_vec();
+            data.extend((Address::zero(), U256::from(amount)).encode());
+            TransactionRequest::new()
+                .from(Address::repeat_byte(1))
+                .to(token)
+                .value(value)
+                .data(data)
+                .into()
+        };
+
+        assert!(policy.ensure_can_send(transfer(1000, 6)).await.is_ok());
+        let err = policy.ensure_can_send(transfer(1001, 0)).await.unwrap_err();
+        assert!(
+            matches!(err, PolicyError::Violation { ref rule, .. } if rule.starts_with("arguments"))
+        );
+        // the value spent in the period is 6 already
+        let err = policy.ensure_can_send(transfer(1, 5)).await.unwrap_err();
+        assert!(matches!(err, PolicyError::Violation { ref rule, .. } if rule == "spend_limit"));
+        assert!(policy.ensure_can_send(transfer(1, 4)).await.is_ok());
+
+        let approve = TransactionRequest::new().to(token).data(vec![0x09, 0x5e, 0xa7, 0xb3]);
+        let err = policy.ensure_can_send(approve.into()).await.unwrap_err();
+        assert!(
+            matches!(err, PolicyError::Violation { ref rule, .. } if rule == "allow_selectors")
+        );
+        // recipients can not be checked before their name is resolved
+        let mut tx = transfer(1, 0);
+        tx.set_to("token.eth");
+        assert!(policy.ensure_can_send(tx).await.is_err());
+        let create = TransactionRequest::new().data(vec![0x60]);
+        assert!(policy.ensure_can_send(create.into()).await.is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        let config = |rule: Value| -> PolicyConfig {
+            serde_json::from_value(serde_json::json!({ "rules": [rule] })).unwrap()
+        };
+        let err = config(serde_json::json!({ "type": "deny_selectors", "selectors": ["0x01"] }))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, PolicyConfigError::InvalidSelector(_)));
+
+        let err = config(serde_json::json!({
+            "type": "arguments",
+            "function": "approve(address spender, uint256 amount)",
+            "constraints": [{ "param": "spender", "max": 1 }]
+        }))
+        .build()
+        .unwrap_err();
+        assert!(matches!(err, PolicyConfigError::InvalidConstraint { .. }));
+    }
+}
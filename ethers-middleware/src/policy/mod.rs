@@ -5,6 +5,20 @@ use async_trait::async_trait;
 use std::fmt::Debug;
 use thiserror::Error;
 
+mod set;
+pub use set::{AuditLog, AuditRecord, Decision, PolicyError, PolicySet, Rule, TracingAuditLog};
+
+mod rules;
+pub use rules::{AllowSelectors, AllowTo, ArgumentRule, ChainId, DenySelectors, DenyTo, MaxFee};
+
+mod spend;
+#[cfg(not(target_arch = "wasm32"))]
+pub use spend::FileSpendStore;
+pub use spend::{MemorySpendStore, Spend, SpendLimit, SpendRecord, SpendStore, StoreError};
+
+mod config;
+pub use config::{ArgumentCheck, ArgumentConstraint, PolicyConfig, PolicyConfigError, RuleConfig};
+
 /// Basic trait to ensure that transactions about to be sent follow certain rules.
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -15,6 +29,12 @@ pub trait Policy: Sync + Send + Debug {
     ///
     /// Returns Ok with the `tx` or an Err otherwise.
     async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error>;
+
+    /// Called when a transaction allowed by [`Policy::ensure_can_send`] could not be sent, so
+    /// that stateful policies can forget it.
+    async fn rollback(&self, _tx: &TypedTransaction) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 /// A policy that does not restrict anything.
@@ -50,6 +70,7 @@ impl Policy for RejectEverything {
 pub struct PolicyMiddleware<M, P> {
     pub(crate) inner: M,
     pub(crate) policy: P,
+    pub(crate) fill: bool,
 }
 
 impl<M, P> PolicyMiddleware<M, P>
@@ -59,7 +80,14 @@ where
 {
     /// Creates a new client from the provider and policy.
     pub fn new(inner: M, policy: P) -> Self {
-        Self { inner, policy }
+        Self { inner, policy, fill: false }
+    }
+
+    /// Fills transactions with the inner middleware before evaluating them, so that rules can
+    /// check the sender, gas and fees of transactions which do not set them.
+    pub fn fill_transactions(mut self, fill: bool) -> Self {
+        self.fill = fill;
+        self
     }
 }
 
@@ -111,11 +139,23 @@ where
         tx: T,
         block: Option<BlockId>,
     ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
-        let tx = self
-            .policy
-            .ensure_can_send(tx.into())
-            .await
-            .map_err(PolicyMiddlewareError::PolicyError)?;
-        self.inner.send_transaction(tx, block).await.map_err(PolicyMiddlewareError::MiddlewareError)
+        let mut tx = tx.into();
+        if self.fill {
+            self.inner
+                .fill_transaction(&mut tx, block)
+                .await
+                .map_err(PolicyMiddlewareError::MiddlewareError)?;
+        }
+        let tx =
+            self.policy.ensure_can_send(tx).await.map_err(PolicyMiddlewareError::PolicyError)?;
+        match self.inner.send_transaction(tx.clone(), block).await {
+            Ok(pending) => Ok(pending),
+            Err(err) => {
+                if let Err(rollback) = self.policy.rollback(&tx).await {
+                    tracing::error!(err = ?rollback, "failed to roll back policy");
+                }
+                Err(PolicyMiddlewareError::MiddlewareError(err))
+            }
+        }
     }
 }
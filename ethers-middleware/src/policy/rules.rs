@@ -0,0 +1,351 @@
+use super::{PolicyError, Rule};
+use async_trait::async_trait;
+use ethers_core::{
+    abi::{Abi, Function, Token},
+    types::{transaction::eip2718::TypedTransaction, Address, NameOrAddress, Selector, U256},
+    utils::hex,
+};
+use std::{collections::HashSet, fmt, sync::Arc};
+
+/// Returns the address the transaction is sent to, `None` for contract creations.
+fn recipient(rule: &impl Rule, tx: &TypedTransaction) -> Result<Option<Address>, PolicyError> {
+    match tx.to() {
+        Some(NameOrAddress::Address(addr)) => Ok(Some(*addr)),
+        Some(NameOrAddress::Name(name)) => {
+            Err(PolicyError::violation(rule, format!("unresolved ENS name `{name}`")))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Returns the selector of the function called by the transaction, if any.
+fn selector(tx: &TypedTransaction) -> Option<Selector> {
+    let data = tx.data()?;
+    data.get(..4).map(|selector| selector.try_into().expect("4 bytes"))
+}
+
+/// Only allows transactions sent to the given addresses. Contract creations are rejected.
+#[derive(Clone, Debug, Default)]
+pub struct AllowTo {
+    addresses: HashSet<Address>,
+}
+
+impl AllowTo {
+    /// Creates a rule only allowing transactions sent to one of the `addresses`.
+    pub fn new(addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self { addresses: addresses.into_iter().collect() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for AllowTo {
+    fn name(&self) -> &str {
+        "allow_to"
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        match recipient(self, tx)? {
+            Some(to) if self.addresses.contains(&to) => Ok(()),
+            Some(to) => Err(PolicyError::violation(self, format!("{to:?} is not allowed"))),
+            None => Err(PolicyError::violation(self, "contract creations are not allowed")),
+        }
+    }
+}
+
+/// Rejects transactions sent to the given addresses.
+#[derive(Clone, Debug, Default)]
+pub struct DenyTo {
+    addresses: HashSet<Address>,
+}
+
+impl DenyTo {
+    /// Creates a rule rejecting transactions sent to one of the `addresses`.
+    pub fn new(addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self { addresses: addresses.into_iter().collect() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for DenyTo {
+    fn name(&self) -> &str {
+        "deny_to"
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        match recipient(self, tx)? {
+            Some(to) if self.addresses.contains(&to) => {
+                Err(PolicyError::violation(self, format!("{to:?} is denied")))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Only allows calls to the given function selectors.
+///
+/// Transactions without calldata, i.e. plain value transfers, are not restricted by this rule.
+#[derive(Clone, Debug, Default)]
+pub struct AllowSelectors {
+    selectors: HashSet<Selector>,
+}
+
+impl AllowSelectors {
+    /// Creates a rule only allowing calls to one of the `selectors`.
+    pub fn new(selectors: impl IntoIterator<Item = Selector>) -> Self {
+        Self { selectors: selectors.into_iter().collect() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for AllowSelectors {
+    fn name(&self) -> &str {
+        "allow_selectors"
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        if tx.data().map_or(true, |data| data.is_empty()) {
+            return Ok(())
+        }
+        match selector(tx) {
+            Some(selector) if self.selectors.contains(&selector) => Ok(()),
+            Some(selector) => Err(PolicyError::violation(
+                self,
+                format!("selector 0x{} is not allowed", hex::encode(selector)),
+            )),
+            None => Err(PolicyError::violation(self, "calldata is shorter than a selector")),
+        }
+    }
+}
+
+/// Rejects calls to the given function selectors.
+#[derive(Clone, Debug, Default)]
+pub struct DenySelectors {
+    selectors: HashSet<Selector>,
+}
+
+impl DenySelectors {
+    /// Creates a rule rejecting calls to one of the `selectors`.
+    pub fn new(selectors: impl IntoIterator<Item = Selector>) -> Self {
+        Self { selectors: selectors.into_iter().collect() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for DenySelectors {
+    fn name(&self) -> &str {
+        "deny_selectors"
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        match selector(tx) {
+            Some(selector) if self.selectors.contains(&selector) => Err(PolicyError::violation(
+                self,
+                format!("selector 0x{} is denied", hex::encode(selector)),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Caps the fees and the gas limit of transactions.
+///
+/// The gas price of legacy and EIP-2930 transactions is capped by `max_fee_per_gas`.
+/// Transactions which do not set a capped field are rejected.
+#[derive(Clone, Debug, Default)]
+pub struct MaxFee {
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    max_gas: Option<U256>,
+}
+
+impl MaxFee {
+    /// Creates a rule without caps, set them with the builder methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the cap of the max fee per gas, or gas price.
+    pub fn max_fee_per_gas<T: Into<U256>>(mut self, max_fee_per_gas: T) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas.into());
+        self
+    }
+
+    /// Sets the cap of the max priority fee per gas.
+    pub fn max_priority_fee_per_gas<T: Into<U256>>(mut self, max_priority_fee_per_gas: T) -> Self {
+        self.max_priority_fee_per_gas = Some(max_priority_fee_per_gas.into());
+        self
+    }
+
+    /// Sets the cap of the gas limit.
+    pub fn max_gas<T: Into<U256>>(mut self, max_gas: T) -> Self {
+        self.max_gas = Some(max_gas.into());
+        self
+    }
+
+    fn ensure_below(
+        &self,
+        field: &str,
+        value: Option<U256>,
+        cap: Option<U256>,
+    ) -> Result<(), PolicyError> {
+        let Some(cap) = cap else { return Ok(()) };
+        match value {
+            Some(value) if value <= cap => Ok(()),
+            Some(value) => {
+                Err(PolicyError::violation(self, format!("{field} {value} exceeds the cap {cap}")))
+            }
+            None => Err(PolicyError::violation(self, format!("{field} is not set"))),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for MaxFee {
+    fn name(&self) -> &str {
+        "max_fee"
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                self.ensure_below("max fee per gas", inner.max_fee_per_gas, self.max_fee_per_gas)?;
+                self.ensure_below(
+                    "max priority fee per gas",
+                    inner.max_priority_fee_per_gas,
+                    self.max_priority_fee_per_gas,
+                )?;
+            }
+            _ => self.ensure_below("gas price", tx.gas_price(), self.max_fee_per_gas)?,
+        }
+        self.ensure_below("gas", tx.gas().copied(), self.max_gas)
+    }
+}
+
+/// Only allows transactions for the given chain.
+#[derive(Clone, Copy, Debug)]
+pub struct ChainId {
+    chain_id: u64,
+}
+
+impl ChainId {
+    /// Creates a rule only allowing transactions for `chain_id`.
+    pub fn new(chain_id: u64) -> Self {
+        Self { chain_id }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for ChainId {
+    fn name(&self) -> &str {
+        "chain_id"
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        match tx.chain_id() {
+            Some(chain_id) if chain_id == self.chain_id.into() => Ok(()),
+            Some(chain_id) => {
+                Err(PolicyError::violation(self, format!("chain id {chain_id} is not allowed")))
+            }
+            None => Err(PolicyError::violation(self, "chain id is not set")),
+        }
+    }
+}
+
+type ArgumentPredicate = dyn Fn(&[Token]) -> Result<(), String> + Send + Sync;
+
+/// Checks the decoded arguments of calls to a function with a predicate.
+///
+/// Calls to other functions are not restricted by this rule. The predicate returns the reason
+/// of the rejection when the arguments are not allowed.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::{abi::{parse_abi, Token}, types::U256};
+/// use ethers_middleware::policy::ArgumentRule;
+///
+/// let abi = parse_abi(&["function transfer(address to, uint256 amount)"]).unwrap();
+/// let rule = ArgumentRule::new(&abi, "transfer", |args| match &args[1] {
+///     Token::Uint(amount) if *amount <= U256::exp10(18) => Ok(()),
+///     _ => Err("amount too large".to_string()),
+/// })
+/// .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct ArgumentRule {
+    name: String,
+    function: Function,
+    to: Option<Address>,
+    predicate: Arc<ArgumentPredicate>,
+}
+
+impl ArgumentRule {
+    /// Creates a rule checking the arguments of the function of the ABI with the given name.
+    pub fn new<F>(abi: &Abi, function: &str, predicate: F) -> Result<Self, ethers_core::abi::Error>
+    where
+        F: Fn(&[Token]) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Ok(Self::from_function(abi.function(function)?.clone(), predicate))
+    }
+
+    /// Creates a rule checking the arguments of the given function.
+    pub fn from_function<F>(function: Function, predicate: F) -> Self
+    where
+        F: Fn(&[Token]) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            name: format!("arguments of `{}`", function.signature()),
+            function,
+            to: None,
+            predicate: Arc::new(predicate),
+        }
+    }
+
+    /// Only checks calls to the given contract.
+    ///
+    /// Transactions sent to an unresolved ENS name are rejected by the rule.
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+}
+
+impl fmt::Debug for ArgumentRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArgumentRule")
+            .field("function", &self.function.signature())
+            .field("to", &self.to)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for ArgumentRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        if let Some(to) = self.to {
+            if recipient(self, tx)? != Some(to) {
+                return Ok(())
+            }
+        }
+        if selector(tx) != Some(self.function.short_signature()) {
+            return Ok(())
+        }
+        let data = tx.data().expect("selector was found");
+        let args = self
+            .function
+            .decode_input(&data[4..])
+            .map_err(|err| PolicyError::violation(self, format!("invalid arguments: {err}")))?;
+        (self.predicate)(&args).map_err(|reason| PolicyError::violation(self, reason))
+    }
+}
@@ -0,0 +1,228 @@
+use super::Policy;
+use async_trait::async_trait;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use std::{error::Error, fmt::Debug, sync::Arc};
+use thiserror::Error;
+
+/// A single check of a [`PolicySet`].
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait Rule: Send + Sync + Debug {
+    /// The name of the rule, used in violations and audit records
+    fn name(&self) -> &str;
+
+    /// Checks whether the transaction complies with the rule.
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError>;
+
+    /// Called once the transaction has passed the checks of every rule of the set, right before
+    /// it is sent. Stateful rules record the transaction here and may still reject it.
+    async fn commit(&self, _tx: &TypedTransaction) -> Result<(), PolicyError> {
+        Ok(())
+    }
+
+    /// Undoes [`Rule::commit`], called when the transaction is rejected by a later rule of the
+    /// set or could not be sent.
+    async fn rollback(&self, _tx: &TypedTransaction) -> Result<(), PolicyError> {
+        Ok(())
+    }
+}
+
+/// Error thrown by a [`PolicySet`].
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    /// Thrown when a transaction violates a rule
+    #[error("transaction rejected by `{rule}`: {reason}")]
+    Violation { rule: String, reason: String },
+    /// Thrown when the state of a stateful rule could not be accessed
+    #[error("policy store error: {0}")]
+    Store(#[source] Box<dyn Error + Send + Sync>),
+}
+
+impl PolicyError {
+    /// Creates a violation of the given rule.
+    pub fn violation(rule: &(impl Rule + ?Sized), reason: impl Into<String>) -> Self {
+        PolicyError::Violation { rule: rule.name().to_string(), reason: reason.into() }
+    }
+}
+
+/// The outcome of a policy evaluation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// The transaction complies with every rule
+    Allowed,
+    /// The transaction was rejected
+    Rejected {
+        /// The rule which rejected the transaction, if any
+        rule: Option<String>,
+        /// Why the transaction was rejected
+        reason: String,
+    },
+}
+
+/// A policy decision recorded by an [`AuditLog`].
+#[derive(Clone, Debug)]
+pub struct AuditRecord {
+    /// The evaluated transaction
+    pub tx: TypedTransaction,
+    /// The decision taken
+    pub decision: Decision,
+    /// The seconds since the unix epoch at which the decision was taken
+    pub timestamp: u64,
+}
+
+/// A sink for the decisions of a [`PolicySet`].
+pub trait AuditLog: Send + Sync + Debug {
+    /// Records a decision.
+    fn record(&self, record: &AuditRecord);
+}
+
+/// An [`AuditLog`] that emits every decision as a `tracing` event with the
+/// `ethers_middleware::policy::audit` target.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingAuditLog;
+
+impl AuditLog for TracingAuditLog {
+    fn record(&self, record: &AuditRecord) {
+        let tx = &record.tx;
+        match &record.decision {
+            Decision::Allowed => tracing::info!(
+                target: "ethers_middleware::policy::audit",
+                from = ?tx.from(),
+                to = ?tx.to(),
+                value = ?tx.value(),
+                nonce = ?tx.nonce(),
+                timestamp = record.timestamp,
+                "transaction allowed"
+            ),
+            Decision::Rejected { rule, reason } => tracing::warn!(
+                target: "ethers_middleware::policy::audit",
+                from = ?tx.from(),
+                to = ?tx.to(),
+                value = ?tx.value(),
+                nonce = ?tx.nonce(),
+                timestamp = record.timestamp,
+                rule = ?rule,
+                reason = %reason,
+                "transaction rejected"
+            ),
+        }
+    }
+}
+
+/// A [`Policy`] made of several [`Rule`]s, all of which must be complied with.
+///
+/// Every decision is recorded in an [`AuditLog`], by default a [`TracingAuditLog`].
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::policy::{AllowTo, ChainId, Policy, PolicySet};
+///
+/// # async fn foo() {
+/// let recipient = Address::random();
+/// let policy = PolicySet::new().rule(AllowTo::new([recipient])).rule(ChainId::new(1));
+///
+/// let tx = TransactionRequest::pay(recipient, 100).chain_id(1);
+/// assert!(policy.ensure_can_send(tx.into()).await.is_ok());
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100).chain_id(1);
+/// assert!(policy.ensure_can_send(tx.into()).await.is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PolicySet {
+    rules: Vec<Arc<dyn Rule>>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl Default for PolicySet {
+    fn default() -> Self {
+        Self { rules: Vec::new(), audit: Arc::new(TracingAuditLog) }
+    }
+}
+
+impl PolicySet {
+    /// Creates an empty set, which allows every transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule to the set.
+    pub fn rule<R: Rule + 'static>(mut self, rule: R) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Adds a shared rule to the set.
+    pub fn shared_rule(mut self, rule: Arc<dyn Rule>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Sets the log the decisions are recorded in.
+    pub fn audit_log<A: AuditLog + 'static>(mut self, audit: A) -> Self {
+        self.audit = Arc::new(audit);
+        self
+    }
+
+    /// Returns the rules of the set.
+    pub fn rules(&self) -> &[Arc<dyn Rule>] {
+        &self.rules
+    }
+
+    async fn evaluate(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        for rule in &self.rules {
+            rule.check(tx).await?;
+        }
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if let Err(err) = rule.commit(tx).await {
+                rollback(&self.rules[..idx], tx).await;
+                return Err(err)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rolls back the commits of the rules, in reverse order.
+async fn rollback(rules: &[Arc<dyn Rule>], tx: &TypedTransaction) {
+    for rule in rules.iter().rev() {
+        if let Err(err) = rule.rollback(tx).await {
+            tracing::error!(rule = rule.name(), %err, "failed to roll back policy rule");
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for PolicySet {
+    type Error = PolicyError;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        let result = self.evaluate(&tx).await;
+        let decision = match &result {
+            Ok(()) => Decision::Allowed,
+            Err(PolicyError::Violation { rule, reason }) => {
+                Decision::Rejected { rule: Some(rule.clone()), reason: reason.clone() }
+            }
+            Err(err) => Decision::Rejected { rule: None, reason: err.to_string() },
+        };
+        let record = AuditRecord { tx, decision, timestamp: unix_timestamp() };
+        self.audit.record(&record);
+        result.map(|_| record.tx)
+    }
+
+    async fn rollback(&self, tx: &TypedTransaction) -> Result<(), Self::Error> {
+        rollback(&self.rules, tx).await;
+        Ok(())
+    }
+}
+
+/// Returns the seconds since the unix epoch.
+pub(crate) fn unix_timestamp() -> u64 {
+    instant::SystemTime::now()
+        .duration_since(instant::SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,351 @@
+use super::{set::unix_timestamp, PolicyError, Rule};
+use async_trait::async_trait;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, Address, U256};
+use futures_util::lock::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, error::Error, fmt::Debug, sync::Arc, time::Duration};
+
+/// Error returned by a [`SpendStore`].
+pub type StoreError = Box<dyn Error + Send + Sync>;
+
+/// The amounts spent by a sender.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spend {
+    /// The value transferred, in wei
+    pub value: U256,
+    /// The maximum fees paid, i.e. the gas limit times the max fee per gas, in wei
+    pub fees: U256,
+}
+
+impl Spend {
+    fn saturating_add(self, other: Spend) -> Spend {
+        Spend {
+            value: self.value.saturating_add(other.value),
+            fees: self.fees.saturating_add(other.fees),
+        }
+    }
+
+    fn saturating_sub(self, other: Spend) -> Spend {
+        Spend {
+            value: self.value.saturating_sub(other.value),
+            fees: self.fees.saturating_sub(other.fees),
+        }
+    }
+}
+
+/// The amounts spent by a sender in a period.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendRecord {
+    /// The start of the period, in seconds since the unix epoch
+    pub period_start: u64,
+    /// The amounts spent since the start of the period
+    pub spend: Spend,
+}
+
+/// Persistence of the amounts spent by each sender, used by [`SpendLimit`].
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait SpendStore: Send + Sync + Debug {
+    /// Loads the last record of the sender for the limit with the given name.
+    async fn load(&self, limit: &str, sender: Address) -> Result<Option<SpendRecord>, StoreError>;
+
+    /// Replaces the record of the sender for the limit with the given name.
+    async fn save(
+        &self,
+        limit: &str,
+        sender: Address,
+        record: SpendRecord,
+    ) -> Result<(), StoreError>;
+}
+
+/// A [`SpendStore`] keeping the records in memory.
+#[derive(Debug, Default)]
+pub struct MemorySpendStore {
+    records: std::sync::Mutex<Records>,
+}
+
+type Records = HashMap<String, HashMap<Address, SpendRecord>>;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SpendStore for MemorySpendStore {
+    async fn load(&self, limit: &str, sender: Address) -> Result<Option<SpendRecord>, StoreError> {
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .get(limit)
+            .and_then(|records| records.get(&sender))
+            .copied())
+    }
+
+    async fn save(
+        &self,
+        limit: &str,
+        sender: Address,
+        record: SpendRecord,
+    ) -> Result<(), StoreError> {
+        self.records.lock().unwrap().entry(limit.to_string()).or_default().insert(sender, record);
+        Ok(())
+    }
+}
+
+/// A [`SpendStore`] persisting the records of all senders in a JSON file.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileSpendStore {
+    path: std::path::PathBuf,
+    /// Serializes the saves, which read, modify and replace the whole file
+    lock: tokio::sync::Mutex<()>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSpendStore {
+    /// Creates a store backed by the file at `path`, which is created on the first save.
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into(), lock: Default::default() }
+    }
+
+    /// Returns a path for a temporary file next to the store, unique to this process and save.
+    fn tmp_path(&self) -> std::path::PathBuf {
+        static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}.{n}.tmp", std::process::id()));
+        self.path.with_file_name(name)
+    }
+
+    async fn read(&self) -> Result<Records, StoreError> {
+        match tokio::fs::read(&self.path).await {
+            Ok(content) => Ok(serde_json::from_slice(&content)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl SpendStore for FileSpendStore {
+    async fn load(&self, limit: &str, sender: Address) -> Result<Option<SpendRecord>, StoreError> {
+        Ok(self.read().await?.get(limit).and_then(|records| records.get(&sender)).copied())
+    }
+
+    async fn save(
+        &self,
+        limit: &str,
+        sender: Address,
+        record: SpendRecord,
+    ) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().await;
+        let mut records = self.read().await?;
+        records.entry(limit.to_string()).or_default().insert(sender, record);
+        // write to a temporary file first so that the records are never left half-written
+        let tmp = self.tmp_path();
+        tokio::fs::write(&tmp, serde_json::to_vec_pretty(&records)?).await?;
+        if let Err(err) = tokio::fs::rename(&tmp, &self.path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(err.into())
+        }
+        Ok(())
+    }
+}
+
+/// Limits the value and fees each sender spends per period.
+///
+/// Periods are fixed windows aligned to the unix epoch, e.g. a period of a day resets at
+/// midnight UTC. The fees of a transaction are its gas limit times its max fee per gas, so
+/// transactions must have their sender, gas and fees set to be allowed when fees are limited.
+///
+/// Limits sharing a store must have distinct names, as their records are kept by name.
+#[derive(Debug, Clone)]
+pub struct SpendLimit {
+    name: String,
+    period: u64,
+    max_value: Option<U256>,
+    max_fees: Option<U256>,
+    store: Arc<dyn SpendStore>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl SpendLimit {
+    /// Creates a limit over periods of the given duration, recorded in memory.
+    pub fn new(period: Duration) -> Self {
+        assert!(period.as_secs() > 0, "spend limit period must be at least one second");
+        Self {
+            name: "spend_limit".to_string(),
+            period: period.as_secs(),
+            max_value: None,
+            max_fees: None,
+            store: Arc::new(MemorySpendStore::default()),
+            lock: Default::default(),
+        }
+    }
+
+    /// Sets the name of the limit, which defaults to `spend_limit`.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the maximum value transferred per period.
+    pub fn max_value<T: Into<U256>>(mut self, max_value: T) -> Self {
+        self.max_value = Some(max_value.into());
+        self
+    }
+
+    /// Sets the maximum fees paid per period.
+    pub fn max_fees<T: Into<U256>>(mut self, max_fees: T) -> Self {
+        self.max_fees = Some(max_fees.into());
+        self
+    }
+
+    /// Sets the store the spent amounts are persisted in.
+    pub fn store<S: SpendStore + 'static>(self, store: S) -> Self {
+        self.shared_store(Arc::new(store))
+    }
+
+    /// Sets a shared store the spent amounts are persisted in.
+    pub fn shared_store(mut self, store: Arc<dyn SpendStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Returns the amounts spent by the sender in the current period.
+    pub async fn spent(&self, sender: Address) -> Result<Spend, PolicyError> {
+        let period_start = self.period_start(unix_timestamp());
+        let record = self.store.load(&self.name, sender).await.map_err(PolicyError::Store)?;
+        Ok(record.filter(|r| r.period_start == period_start).map(|r| r.spend).unwrap_or_default())
+    }
+
+    fn period_start(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.period
+    }
+
+    fn spend_of(&self, tx: &TypedTransaction) -> Result<Spend, PolicyError> {
+        let value = tx.value().copied().unwrap_or_default();
+        if self.max_fees.is_none() {
+            return Ok(Spend { value, fees: U256::zero() })
+        }
+        let fee_per_gas = match tx {
+            TypedTransaction::Eip1559(inner) => inner.max_fee_per_gas,
+            _ => tx.gas_price(),
+        };
+        let (Some(gas), Some(fee_per_gas)) = (tx.gas(), fee_per_gas) else {
+            return Err(PolicyError::violation(self, "gas and fees must be set"))
+        };
+        Ok(Spend { value, fees: gas.saturating_mul(fee_per_gas) })
+    }
+
+    /// Returns the sender and the amounts it would have spent with the transaction.
+    async fn ensure_within_limits(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<(Address, Spend), PolicyError> {
+        let Some(sender) = tx.from().copied() else {
+            return Err(PolicyError::violation(self, "sender is not set"))
+        };
+        let total = self.spent(sender).await?.saturating_add(self.spend_of(tx)?);
+        if let Some(max_value) = self.max_value {
+            if total.value > max_value {
+                return Err(PolicyError::violation(
+                    self,
+                    format!("value spent in the period would exceed {max_value}"),
+                ))
+            }
+        }
+        if let Some(max_fees) = self.max_fees {
+            if total.fees > max_fees {
+                return Err(PolicyError::violation(
+                    self,
+                    format!("fees spent in the period would exceed {max_fees}"),
+                ))
+            }
+        }
+        Ok((sender, total))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Rule for SpendLimit {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        self.ensure_within_limits(tx).await.map(drop)
+    }
+
+    async fn commit(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        // check again while holding the lock, concurrent transactions may have been recorded
+        // since the transaction was checked
+        let _lock = self.lock.lock().await;
+        let (sender, spend) = self.ensure_within_limits(tx).await?;
+        let record = SpendRecord { period_start: self.period_start(unix_timestamp()), spend };
+        self.store.save(&self.name, sender, record).await.map_err(PolicyError::Store)
+    }
+
+    async fn rollback(&self, tx: &TypedTransaction) -> Result<(), PolicyError> {
+        let Some(sender) = tx.from().copied() else { return Ok(()) };
+        let _lock = self.lock.lock().await;
+        let period_start = self.period_start(unix_timestamp());
+        let record = self.store.load(&self.name, sender).await.map_err(PolicyError::Store)?;
+        // nothing to undo if the transaction was recorded in a previous period
+        let Some(record) = record.filter(|r| r.period_start == period_start) else { return Ok(()) };
+        let spend = record.spend.saturating_sub(self.spend_of(tx)?);
+        let record = SpendRecord { period_start, spend };
+        self.store.save(&self.name, sender, record).await.map_err(PolicyError::Store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::{Policy, PolicySet};
+    use ethers_core::types::TransactionRequest;
+
+    #[tokio::test]
+    async fn rolls_back_spend() {
+        let sender = Address::repeat_byte(1);
+        let limit = SpendLimit::new(Duration::from_secs(86400)).max_value(10);
+        let policy = PolicySet::new().rule(limit.clone());
+
+        let tx: TypedTransaction = TransactionRequest::pay(Address::zero(), 6).from(sender).into();
+        let tx = policy.ensure_can_send(tx).await.unwrap();
+        assert_eq!(limit.spent(sender).await.unwrap().value, 6.into());
+
+        // the transaction could not be sent, so it must not count towards the limit
+        policy.rollback(&tx).await.unwrap();
+        assert_eq!(limit.spent(sender).await.unwrap(), Spend::default());
+        assert!(policy.ensure_can_send(tx).await.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn file_store_keeps_concurrent_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: Arc<dyn SpendStore> =
+            Arc::new(FileSpendStore::new(dir.path().join("spend.json")));
+        let limits = ["a", "b"].map(|name| {
+            SpendLimit::new(Duration::from_secs(86400)).named(name).shared_store(store.clone())
+        });
+
+        let senders: Vec<_> = (1..=20).map(Address::repeat_byte).collect();
+        let saves = limits.iter().flat_map(|limit| {
+            senders.iter().map(move |sender| {
+                let tx: TypedTransaction =
+                    TransactionRequest::pay(Address::zero(), 1).from(*sender).into();
+                async move { limit.commit(&tx).await }
+            })
+        });
+        for result in futures_util::future::join_all(saves).await {
+            result.unwrap();
+        }
+
+        for limit in &limits {
+            for sender in &senders {
+                assert_eq!(limit.spent(*sender).await.unwrap().value, 1.into());
+            }
+        }
+    }
+}
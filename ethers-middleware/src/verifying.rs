@@ -0,0 +1,328 @@
+use async_trait::async_trait;
+use ethers_core::{
+    types::{
+        trie::{ProofError, KECCAK_EMPTY},
+        Address, Block, BlockId, BlockNumber, Bytes, EIP1186ProofResponse, NameOrAddress, H256,
+        U256, U64,
+    },
+    utils::keccak256,
+};
+use ethers_providers::{Middleware, MiddlewareError};
+use std::sync::RwLock;
+use thiserror::Error;
+
+/// A block header that is trusted by the [`VerifyingMiddleware`], for example because it was
+/// obtained from a light client or from several independent providers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedHeader {
+    /// The number of the block.
+    pub number: U64,
+    /// The hash of the block.
+    pub hash: H256,
+    /// The state root of the block.
+    pub state_root: H256,
+}
+
+impl TrustedHeader {
+    /// Returns the trusted header of a block, or `None` for a pending block.
+    pub fn from_block<TX>(block: &Block<TX>) -> Option<Self> {
+        Some(Self { number: block.number?, hash: block.hash?, state_root: block.state_root })
+    }
+
+    /// Whether `block` refers to this header, `latest` refers to the trusted header.
+    fn matches(&self, block: BlockId) -> bool {
+        match block {
+            BlockId::Number(BlockNumber::Latest) => true,
+            BlockId::Number(BlockNumber::Number(number)) => number == self.number,
+            BlockId::Hash(hash) => hash == self.hash,
+            _ => false,
+        }
+    }
+}
+
+/// Middleware that verifies account and storage queries with `eth_getProof` against the state
+/// root of a [`TrustedHeader`], so that the answers of the inner provider do not need to be
+/// trusted.
+///
+/// [`get_balance`](Middleware::get_balance),
+/// [`get_transaction_count`](Middleware::get_transaction_count),
+/// [`get_storage_at`](Middleware::get_storage_at), [`get_code`](Middleware::get_code) and
+/// [`get_proof`](Middleware::get_proof) are answered at the block of the trusted header, which is
+/// used for requests without a block, at `latest`, or at the number or hash of the header. Requests
+/// for other blocks fail with [`VerifyingMiddlewareError::UntrustedBlock`], and all other requests
+/// are passed through unverified.
+///
+/// Note that ENS names are resolved by the inner provider without verification.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::Address;
+/// use ethers_middleware::verifying::{TrustedHeader, VerifyingMiddleware};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo(trusted: Provider<Http>) -> Result<(), Box<dyn std::error::Error>> {
+/// let block = trusted.get_block(100u64).await?.unwrap();
+/// let header = TrustedHeader::from_block(&block).unwrap();
+///
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let provider = VerifyingMiddleware::new(provider, header);
+/// let balance = provider.get_balance(Address::random(), None).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct VerifyingMiddleware<M> {
+    inner: M,
+    header: RwLock<TrustedHeader>,
+}
+
+impl<M> VerifyingMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Creates a new middleware that verifies responses against `header`.
+    pub fn new(inner: M, header: TrustedHeader) -> Self {
+        Self { inner, header: RwLock::new(header) }
+    }
+
+    /// Returns the current trusted header.
+    pub fn trusted_header(&self) -> TrustedHeader {
+        *self.header.read().unwrap()
+    }
+
+    /// Replaces the trusted header, e.g. when a light client follows the chain.
+    pub fn set_trusted_header(&self, header: TrustedHeader) {
+        *self.header.write().unwrap() = header;
+    }
+
+    /// Returns the trusted header if `block` refers to it
+    fn header_at(
+        &self,
+        block: Option<BlockId>,
+    ) -> Result<TrustedHeader, VerifyingMiddlewareError<M>> {
+        let header = self.trusted_header();
+        match block {
+            Some(block) if !header.matches(block) => {
+                Err(VerifyingMiddlewareError::UntrustedBlock(block))
+            }
+            _ => Ok(header),
+        }
+    }
+
+    async fn resolve(
+        &self,
+        account: NameOrAddress,
+    ) -> Result<Address, VerifyingMiddlewareError<M>> {
+        match account {
+            NameOrAddress::Name(ens_name) => {
+                self.inner.resolve_name(&ens_name).await.map_err(MiddlewareError::from_err)
+            }
+            NameOrAddress::Address(addr) => Ok(addr),
+        }
+    }
+
+    /// Fetches and verifies the proof for `address` and the storage `locations`
+    async fn verified_proof(
+        &self,
+        address: Address,
+        locations: Vec<H256>,
+        header: TrustedHeader,
+    ) -> Result<EIP1186ProofResponse, VerifyingMiddlewareError<M>> {
+        let proof = self
+            .inner
+            .get_proof(address, locations.clone(), Some(header.hash.into()))
+            .await
+            .map_err(VerifyingMiddlewareError::from_err)?;
+
+        let keys = proof.storage_proof.iter().map(|p| u256_to_h256(p.key));
+        if proof.address != address || !keys.eq(locations) {
+            return Err(ProofError::KeyMismatch.into())
+        }
+        proof.verify(header.state_root)?;
+        Ok(proof)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for VerifyingMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = VerifyingMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn get_balance<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let header = self.header_at(block)?;
+        let address = self.resolve(from.into()).await?;
+        Ok(self.verified_proof(address, vec![], header).await?.balance)
+    }
+
+    async fn get_transaction_count<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let header = self.header_at(block)?;
+        let address = self.resolve(from.into()).await?;
+        Ok(self.verified_proof(address, vec![], header).await?.nonce.as_u64().into())
+    }
+
+    async fn get_code<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        at: T,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let header = self.header_at(block)?;
+        let address = self.resolve(at.into()).await?;
+        let proof = self.verified_proof(address, vec![], header).await?;
+        if proof.code_hash == KECCAK_EMPTY {
+            return Ok(Bytes::default())
+        }
+
+        let code = self
+            .inner
+            .get_code(address, Some(header.hash.into()))
+            .await
+            .map_err(VerifyingMiddlewareError::from_err)?;
+        let actual = H256(keccak256(&code));
+        if actual != proof.code_hash {
+            return Err(VerifyingMiddlewareError::CodeHashMismatch {
+                expected: proof.code_hash,
+                actual,
+            })
+        }
+        Ok(code)
+    }
+
+    async fn get_storage_at<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        location: H256,
+        block: Option<BlockId>,
+    ) -> Result<H256, Self::Error> {
+        let header = self.header_at(block)?;
+        let address = self.resolve(from.into()).await?;
+        let proof = self.verified_proof(address, vec![location], header).await?;
+        Ok(u256_to_h256(proof.storage_proof[0].value))
+    }
+
+    async fn get_proof<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        locations: Vec<H256>,
+        block: Option<BlockId>,
+    ) -> Result<EIP1186ProofResponse, Self::Error> {
+        let header = self.header_at(block)?;
+        let address = self.resolve(from.into()).await?;
+        self.verified_proof(address, locations, header).await
+    }
+}
+
+fn u256_to_h256(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256(bytes)
+}
+
+/// Thrown when an error happens at the [`VerifyingMiddleware`]
+#[derive(Error, Debug)]
+pub enum VerifyingMiddlewareError<M: Middleware> {
+    /// The request is for a block other than the trusted header.
+    #[error("block {0:?} does not match the trusted header")]
+    UntrustedBlock(BlockId),
+
+    /// The proof returned by the inner middleware is invalid.
+    #[error(transparent)]
+    InvalidProof(#[from] ProofError),
+
+    /// The code returned by the inner middleware does not match the proven code hash.
+    #[error("code hash mismatch: expected {expected:?}, got {actual:?}")]
+    CodeHashMismatch {
+        /// The proven code hash
+        expected: H256,
+        /// The hash of the returned code
+        actual: H256,
+    },
+
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> MiddlewareError for VerifyingMiddlewareError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        VerifyingMiddlewareError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            VerifyingMiddlewareError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use ethers_providers::Provider;
+
+    fn proof() -> EIP1186ProofResponse {
+        serde_json::from_str(include_str!("../../ethers-core/testdata/proof.json")).unwrap()
+    }
+
+    fn header(proof: &EIP1186ProofResponse) -> TrustedHeader {
+        TrustedHeader {
+            number: 100u64.into(),
+            hash: H256::repeat_byte(1),
+            state_root: H256(keccak256(&proof.account_proof[0])),
+        }
+    }
+
+    #[tokio::test]
+    async fn verifies_storage() {
+        let proof = proof();
+        let (provider, mock) = Provider::mocked();
+        let middleware = VerifyingMiddleware::new(provider, header(&proof));
+
+        mock.push(proof.clone()).unwrap();
+        let value = middleware.get_storage_at(proof.address, H256::zero(), None).await.unwrap();
+        assert_eq!(value, H256::zero());
+        mock.assert_request(
+            "eth_getProof",
+            (proof.address, vec![H256::zero()], BlockId::from(H256::repeat_byte(1))),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_proofs() {
+        let proof = proof();
+        let (provider, mock) = Provider::mocked();
+        let middleware = VerifyingMiddleware::new(provider, header(&proof));
+
+        let mut wrong = proof.clone();
+        wrong.nonce = 2u64.into();
+        wrong.storage_proof.clear();
+        mock.push(wrong).unwrap();
+        let err = middleware.get_transaction_count(proof.address, None).await.unwrap_err();
+        assert!(matches!(err, VerifyingMiddlewareError::InvalidProof(ProofError::ValueMismatch)));
+
+        let err = middleware.get_balance(proof.address, Some(99u64.into())).await.unwrap_err();
+        assert!(matches!(err, VerifyingMiddlewareError::UntrustedBlock(_)));
+    }
+}
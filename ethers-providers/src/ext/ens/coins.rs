@@ -0,0 +1,108 @@
+//! [ENSIP-9](https://docs.ens.domains/ensip/9) multi-coin address formats.
+
+use super::{
+    coin_type::{BTC, DOGE, ETH, LTC, SOL},
+    EnsDecodeError,
+};
+use bech32::{u5, ToBase32, Variant};
+use ethers_core::{types::Address, utils::to_checksum};
+
+/// Formats an address stored in the binary format of its ENSIP-9 coin type, as returned by the
+/// `addr(bytes32,uint256)` method of resolvers.
+///
+/// Supported are Bitcoin, Litecoin and Dogecoin scripts, Solana addresses, and the addresses of
+/// Ethereum and the EVM chains of [ENSIP-11](https://docs.ens.domains/ensip/11), which are
+/// formatted with their EIP-55 checksum.
+///
+/// # Example
+///
+/// ```
+/// use ethers_providers::ens::format_coin_address;
+///
+/// let script = hex::decode("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+/// assert_eq!(format_coin_address(0, &script).unwrap(), "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+/// ```
+pub fn format_coin_address(coin_type: u64, address: &[u8]) -> Result<String, EnsDecodeError> {
+    let formatted = match coin_type {
+        BTC => format_bitcoin_script(address, 0x00, 0x05, Some("bc")),
+        LTC => format_bitcoin_script(address, 0x30, 0x32, Some("ltc")),
+        DOGE => format_bitcoin_script(address, 0x1e, 0x16, None),
+        SOL if address.len() == 32 => Some(bs58::encode(address).into_string()),
+        ETH | 0x8000_0000..=0xffff_ffff if address.len() == 20 => {
+            Some(to_checksum(&Address::from_slice(address), None))
+        }
+        SOL | ETH | 0x8000_0000..=0xffff_ffff => None,
+        _ => return Err(EnsDecodeError::UnsupportedCoinType(coin_type)),
+    };
+    formatted.ok_or(EnsDecodeError::InvalidAddress(coin_type))
+}
+
+/// Formats a P2PKH, P2SH or, if the coin has a human-readable part, segwit output script.
+fn format_bitcoin_script(script: &[u8], p2pkh: u8, p2sh: u8, hrp: Option<&str>) -> Option<String> {
+    let base58check = |version: u8, hash: &[u8]| {
+        bs58::encode([&[version], hash].concat()).with_check().into_string()
+    };
+    match script {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            Some(base58check(p2pkh, hash))
+        }
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => Some(base58check(p2sh, hash)),
+        [op, len, program @ ..] if *len as usize == program.len() && (2..=40).contains(len) => {
+            let (version, variant) = match op {
+                0x00 => (0, Variant::Bech32),
+                0x51..=0x60 => (op - 0x50, Variant::Bech32m),
+                _ => return None,
+            };
+            let data = [vec![u5::try_from_u8(version).ok()?], program.to_base32()].concat();
+            bech32::encode(hrp?, data, variant).ok()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_coin_addresses() {
+        for (coin_type, address, expected) in [
+            (
+                0,
+                "a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1887",
+                "3Ai1JZ8pdJb2ksieUV8FsxSNVJCpoPi8W6",
+            ),
+            (
+                0,
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            ),
+            (
+                2,
+                "76a914a5f4d12ce3685781b227c1f39548ddef429e978388ac",
+                "LaMT348PWRnrqeeWArpwQPbuanpXDZGEUz",
+            ),
+            (
+                3,
+                "76a9144620b70031f0e9437e374a2100934fba4911046088ac",
+                "DBXu2kgc3xtvCUWFcxFE3r9hEYgmuaaCyD",
+            ),
+            (
+                60,
+                "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            ),
+            (
+                0x8000_000a,
+                "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            ),
+        ] {
+            let address = hex::decode(address).unwrap();
+            assert_eq!(format_coin_address(coin_type, &address).unwrap(), expected);
+        }
+
+        assert_eq!(format_coin_address(60, &[0; 19]), Err(EnsDecodeError::InvalidAddress(60)));
+        assert_eq!(format_coin_address(22, &[]), Err(EnsDecodeError::UnsupportedCoinType(22)));
+    }
+}
@@ -0,0 +1,154 @@
+//! [ENSIP-7](https://docs.ens.domains/ensip/7) content hashes.
+
+use super::EnsDecodeError;
+use ethers_core::types::H256;
+use std::fmt;
+
+const IPFS: u64 = 0xe3;
+const SWARM: u64 = 0xe4;
+const IPNS: u64 = 0xe5;
+const ONION: u64 = 0x01bc;
+const ONION3: u64 = 0x01bd;
+
+/// The content hash of a name, decoded from the multicodec format returned by the
+/// `contenthash(bytes32)` method of resolvers.
+///
+/// The [`Display`](fmt::Display) implementation formats the content hash as a URI, e.g.
+/// `ipfs://QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Contenthash {
+    /// An IPFS CID, as CIDv0 if possible and as base32 CIDv1 otherwise.
+    Ipfs(String),
+    /// An IPNS name, as base32 CIDv1.
+    Ipns(String),
+    /// The hash of a Swarm manifest.
+    Swarm(H256),
+    /// A Tor onion service address, without the `.onion` suffix.
+    Onion(String),
+    /// A Tor v3 onion service address, without the `.onion` suffix.
+    Onion3(String),
+}
+
+impl Contenthash {
+    /// Decodes a content hash.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_providers::ens::Contenthash;
+    ///
+    /// let hash = hex::decode(
+    ///     "e3010170122029f2d17be6139079dc48696d1f582a8530eb9805b561eda517e22a892c7e3f1f",
+    /// )
+    /// .unwrap();
+    /// let contenthash = Contenthash::decode(&hash).unwrap();
+    /// assert_eq!(contenthash.to_string(), "ipfs://QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4");
+    /// ```
+    pub fn decode(hash: &[u8]) -> Result<Self, EnsDecodeError> {
+        let (codec, value) = read_varint(hash).ok_or(EnsDecodeError::InvalidContenthash)?;
+        match codec {
+            IPFS => Ok(Self::Ipfs(format_cid(value)?)),
+            IPNS => Ok(Self::Ipns(format_cid(value)?)),
+            SWARM => match value {
+                // CIDv1, swarm-manifest, keccak-256 multihash
+                [0x01, 0xfa, 0x01, 0x1b, 0x20, digest @ ..] if digest.len() == 32 => {
+                    Ok(Self::Swarm(H256::from_slice(digest)))
+                }
+                _ => Err(EnsDecodeError::InvalidContenthash),
+            },
+            ONION | ONION3 => {
+                let address = String::from_utf8(value.to_vec())
+                    .map_err(|_| EnsDecodeError::InvalidContenthash)?;
+                Ok(if codec == ONION { Self::Onion(address) } else { Self::Onion3(address) })
+            }
+            codec => Err(EnsDecodeError::UnsupportedCodec(codec)),
+        }
+    }
+}
+
+impl fmt::Display for Contenthash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ipfs(cid) => write!(f, "ipfs://{cid}"),
+            Self::Ipns(cid) => write!(f, "ipns://{cid}"),
+            Self::Swarm(hash) => write!(f, "bzz://{}", hex::encode(hash)),
+            Self::Onion(address) => write!(f, "onion://{address}"),
+            Self::Onion3(address) => write!(f, "onion3://{address}"),
+        }
+    }
+}
+
+/// Reads an unsigned LEB128 varint, returning it and the remaining bytes.
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[i + 1..]))
+        }
+    }
+    None
+}
+
+/// Formats a binary CIDv1, as CIDv0 if it is a dag-pb sha2-256 CID.
+fn format_cid(cid: &[u8]) -> Result<String, EnsDecodeError> {
+    match cid {
+        [0x01, 0x70, multihash @ ..] if multihash.len() == 34 && multihash[..2] == [0x12, 0x20] => {
+            Ok(bs58::encode(multihash).into_string())
+        }
+        [0x01, ..] => Ok(format!("b{}", base32(cid))),
+        _ => Err(EnsDecodeError::InvalidContenthash),
+    }
+}
+
+/// Encodes bytes as lowercase, unpadded RFC 4648 base32, as used by multibase.
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut encoded = String::with_capacity((bytes.len() * 8 + 4) / 5);
+    let (mut buffer, mut bits) = (0u16, 0);
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_contenthashes() {
+        for (hash, expected) in [
+            (
+                "e301017012201687de19f1516b9e560ab8655faa678e3a023ebff43494ac06a36581aafc957e",
+                "ipfs://QmPrbqiw6XpWQDgR8uAGPM1tVV8xqRYJZa3WC8Yi3NjcCu",
+            ),
+            (
+                "e50101720024080112205cbd1cc86ac20d6640795809c2a185bb2504538a2de8076da5a6971b8acb4715",
+                "ipns://bafzaajaiaejcaxf5dtegvqqnmzahswajykqylozfarjyulpia5w2ljuxdofmwryv",
+            ),
+            (
+                "e40101fa011b20d1de9994b4d039f6548d191eb26786769f580809256b4685ef316805265ea162",
+                "bzz://d1de9994b4d039f6548d191eb26786769f580809256b4685ef316805265ea162",
+            ),
+            ("bc037a716b746c776934666563766f367269", "onion://zqktlwi4fecvo6ri"),
+        ] {
+            let hash = hex::decode(hash).unwrap();
+            assert_eq!(Contenthash::decode(&hash).unwrap().to_string(), expected);
+        }
+
+        assert_eq!(
+            Contenthash::decode(&[0x90, 0xb2, 0xca, 0x05]),
+            Err(EnsDecodeError::UnsupportedCodec(0xb29910))
+        );
+        assert_eq!(Contenthash::decode(&[]), Err(EnsDecodeError::InvalidContenthash));
+    }
+}
@@ -6,6 +6,19 @@ use ethers_core::{
     utils::keccak256,
 };
 
+#[cfg(feature = "ens-records")]
+mod coins;
+#[cfg(feature = "ens-records")]
+pub use coins::format_coin_address;
+
+#[cfg(feature = "ens-records")]
+mod contenthash;
+#[cfg(feature = "ens-records")]
+pub use contenthash::Contenthash;
+
+mod normalize;
+pub use normalize::{normalize, EnsNormalizeError};
+
 /// ENS registry address (`0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e`)
 pub const ENS_ADDRESS: Address = H160([
     // cannot set type aliases as constructors
@@ -30,6 +43,61 @@ pub const FIELD_SELECTOR: Selector = [89, 209, 212, 60];
 /// supportsInterface(bytes4 interfaceID)
 pub const INTERFACE_SELECTOR: Selector = [1, 255, 201, 167];
 
+/// addr(bytes32, uint256)
+pub const COIN_ADDR_SELECTOR: Selector = [241, 203, 126, 6];
+
+/// contenthash(bytes32)
+pub const CONTENTHASH_SELECTOR: Selector = [188, 28, 88, 209];
+
+/// ABI(bytes32, uint256)
+pub const ABI_SELECTOR: Selector = [34, 3, 171, 86];
+
+/// pubkey(bytes32)
+pub const PUBKEY_SELECTOR: Selector = [200, 105, 2, 51];
+
+/// The [ENSIP-9](https://docs.ens.domains/ensip/9) coin types of address records.
+pub mod coin_type {
+    /// Bitcoin
+    pub const BTC: u64 = 0;
+    /// Litecoin
+    pub const LTC: u64 = 2;
+    /// Dogecoin
+    pub const DOGE: u64 = 3;
+    /// Ethereum
+    pub const ETH: u64 = 60;
+    /// Solana
+    pub const SOL: u64 = 501;
+
+    /// Returns the coin type of the EVM chain with the given id as specified in
+    /// [ENSIP-11](https://docs.ens.domains/ensip/11), i.e. [`ETH`] for the Ethereum mainnet and
+    /// `0x80000000 | chain_id` for other chains.
+    pub const fn evm(chain_id: u64) -> u64 {
+        if chain_id == 1 {
+            ETH
+        } else {
+            0x8000_0000 | chain_id
+        }
+    }
+}
+
+/// Errors decoding the records of a name
+#[cfg(feature = "ens-records")]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EnsDecodeError {
+    /// The coin type of an address record is not supported
+    #[error("unsupported coin type {0}")]
+    UnsupportedCoinType(u64),
+    /// An address record is not valid for its coin type
+    #[error("invalid address for coin type {0}")]
+    InvalidAddress(u64),
+    /// The codec of a content hash is not supported
+    #[error("unsupported contenthash codec {0:#x}")]
+    UnsupportedCodec(u64),
+    /// A content hash is malformed
+    #[error("invalid contenthash")]
+    InvalidContenthash,
+}
+
 /// Returns a transaction request for calling the `resolver` method on the ENS server
 pub fn get_resolver<T: Into<NameOrAddress>>(ens_address: T, name: &str) -> TransactionRequest {
     // keccak256('resolver(bytes32)')
@@ -75,14 +143,27 @@ pub fn reverse_address(addr: Address) -> String {
 }
 
 /// Returns the ENS namehash as specified in [EIP-137](https://eips.ethereum.org/EIPS/eip-137)
+///
+/// The name is normalized with [`normalize`] first. Names that cannot be normalized are hashed as
+/// they are, without their U+FE0F variation selectors, use [`try_namehash`] to reject them instead.
 pub fn namehash(name: &str) -> H256 {
+    match normalize(name) {
+        Ok(name) => hash_labels(&name),
+        Err(_) => hash_labels(&name.replace('\u{fe0f}', "")),
+    }
+}
+
+/// Returns the ENS namehash of the name normalized with [`normalize`], or the error if the name
+/// cannot be normalized.
+pub fn try_namehash(name: &str) -> Result<H256, EnsNormalizeError> {
+    normalize(name).map(|name| hash_labels(&name))
+}
+
+fn hash_labels(name: &str) -> H256 {
     if name.is_empty() {
         return H256::zero()
     }
 
-    // Remove the variation selector U+FE0F
-    let name = name.replace('\u{fe0f}', "");
-
     // Generate the node starting from the right
     name.rsplit('.')
         .fold([0u8; 32], |node, label| keccak256([node, keccak256(label.as_bytes())].concat()))
@@ -127,6 +208,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_namehash_normalizes() {
+        assert_eq!(namehash("Alice.ETH"), namehash("alice.eth"));
+        assert_eq!(try_namehash("Alice.ETH").unwrap(), namehash("alice.eth"));
+        assert_eq!(try_namehash("a_b.eth").unwrap_err(), EnsNormalizeError::UnderscoreNotLeading);
+    }
+
+    #[test]
+    fn test_selectors() {
+        use ethers_core::utils::id;
+
+        assert_eq!(COIN_ADDR_SELECTOR, id("addr(bytes32,uint256)"));
+        assert_eq!(CONTENTHASH_SELECTOR, id("contenthash(bytes32)"));
+        assert_eq!(ABI_SELECTOR, id("ABI(bytes32,uint256)"));
+        assert_eq!(PUBKEY_SELECTOR, id("pubkey(bytes32)"));
+    }
+
     #[test]
     fn test_parametershash() {
         assert_eq!(
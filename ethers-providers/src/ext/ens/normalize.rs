@@ -0,0 +1,244 @@
+//! ENS name normalization.
+//!
+//! With the `ens-normalize` feature, labels are mapped and normalized to NFC with the UTS-46
+//! mapping, without the IDNA bidi and joiner rules, and then validated: underscores, label
+//! extensions, combining marks, fenced characters and zero width joiners outside of emoji
+//! sequences are rejected. Emoji sequences are kept, without their U+FE0F variation selectors.
+//!
+//! Without the feature, only ASCII characters are mapped and validated, and all other characters
+//! are kept as they are.
+//!
+//! Confusable characters, i.e. labels mixing scripts or resembling labels of another script, are
+//! not detected in either case.
+
+#[cfg(feature = "ens-normalize")]
+use icu_normalizer::uts46::Uts46MapperBorrowed;
+#[cfg(feature = "ens-normalize")]
+use icu_properties::{
+    props::{EmojiModifier, ExtendedPictographic, GeneralCategory, GeneralCategoryGroup},
+    CodePointMapData, CodePointSetData,
+};
+#[cfg(feature = "ens-normalize")]
+use std::iter;
+
+#[cfg(feature = "ens-normalize")]
+const ZWJ: char = '\u{200d}';
+
+/// Characters that cannot be at the start or the end of a label, or next to each other.
+#[cfg(feature = "ens-normalize")]
+const FENCED: [char; 3] = ['\u{2019}', '\u{2044}', '\u{30fb}'];
+
+/// Errors of [`normalize`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EnsNormalizeError {
+    /// The name contains an empty label
+    #[error("empty label")]
+    EmptyLabel,
+    /// The name contains a disallowed character
+    #[error("disallowed character {0:?}")]
+    DisallowedCharacter(char),
+    /// An underscore is not at the start of a label
+    #[error("underscore allowed only at start")]
+    UnderscoreNotLeading,
+    /// An ASCII label has hyphens as third and fourth characters, like punycode labels
+    #[error("invalid label extension: {0}")]
+    LabelExtension(String),
+    /// A label starts with a combining mark
+    #[error("leading combining mark")]
+    LeadingCombiningMark,
+    /// A combining mark follows an emoji
+    #[error("emoji + combining mark")]
+    CombiningMarkAfterEmoji,
+    /// A label starts with a fenced character
+    #[error("leading {0:?}")]
+    FencedLeading(char),
+    /// A label ends with a fenced character
+    #[error("trailing {0:?}")]
+    FencedTrailing(char),
+    /// Two fenced characters are next to each other
+    #[error("adjacent {0:?} + {1:?}")]
+    FencedAdjacent(char, char),
+}
+
+/// Normalizes an ENS name, e.g. lowercases it, and rejects names which are not valid.
+///
+/// Without the `ens-normalize` feature, only the ASCII characters of the name are normalized and
+/// validated. Confusable names are not rejected, see the [module docs](self).
+///
+/// # Example
+///
+/// ```
+/// use ethers_providers::ens::normalize;
+///
+/// assert_eq!(normalize("Nick.ETH").unwrap(), "nick.eth");
+/// assert_eq!(normalize("ret↩️rn.eth").unwrap(), "ret↩rn.eth");
+/// assert!(normalize("a_b.eth").is_err());
+/// ```
+pub fn normalize(name: &str) -> Result<String, EnsNormalizeError> {
+    if name.is_empty() {
+        return Ok(String::new())
+    }
+    let labels = name.split('.').map(normalize_label).collect::<Result<Vec<_>, _>>()?;
+    Ok(labels.join("."))
+}
+
+#[cfg(feature = "ens-normalize")]
+fn normalize_label(label: &str) -> Result<String, EnsNormalizeError> {
+    let mapper = Uts46MapperBorrowed::new();
+    // ENS maps the ASCII apostrophe, which UTS-46 keeps
+    let input = || label.chars().map(|c| if c == '\'' { '\u{2019}' } else { c });
+
+    // the joiners are only valid in emoji sequences, which is checked after mapping
+    for c in input().filter(|c| *c != ZWJ) {
+        if mapper.map_normalize(iter::once(c)).any(|c| c == '\u{fffd}' || !is_valid_ascii(c)) {
+            return Err(EnsNormalizeError::DisallowedCharacter(c))
+        }
+    }
+    let chars: Vec<char> = mapper.map_normalize(input()).collect();
+    validate_label(&chars)?;
+    Ok(chars.into_iter().collect())
+}
+
+#[cfg(not(feature = "ens-normalize"))]
+fn normalize_label(label: &str) -> Result<String, EnsNormalizeError> {
+    let chars: Vec<char> = label
+        .chars()
+        .filter(|c| *c != '\u{fe0f}')
+        .map(|c| if c == '\'' { '\u{2019}' } else { c.to_ascii_lowercase() })
+        .collect();
+    if let Some(c) = chars.iter().find(|c| !is_valid_ascii(**c)) {
+        return Err(EnsNormalizeError::DisallowedCharacter(*c))
+    }
+    validate_label(&chars)?;
+    Ok(chars.into_iter().collect())
+}
+
+fn is_valid_ascii(c: char) -> bool {
+    !c.is_ascii() || matches!(c, 'a'..='z' | '0'..='9' | '-' | '_' | '$')
+}
+
+fn validate_label(chars: &[char]) -> Result<(), EnsNormalizeError> {
+    if chars.is_empty() {
+        return Err(EnsNormalizeError::EmptyLabel)
+    }
+
+    let leading_underscores = chars.iter().take_while(|c| **c == '_').count();
+    if chars[leading_underscores..].contains(&'_') {
+        return Err(EnsNormalizeError::UnderscoreNotLeading)
+    }
+
+    if chars.iter().all(char::is_ascii) {
+        if chars.len() >= 4 && chars[2] == '-' && chars[3] == '-' {
+            return Err(EnsNormalizeError::LabelExtension(chars.iter().collect()))
+        }
+        return Ok(())
+    }
+
+    #[cfg(feature = "ens-normalize")]
+    validate_unicode_label(chars)?;
+    Ok(())
+}
+
+#[cfg(feature = "ens-normalize")]
+fn validate_unicode_label(chars: &[char]) -> Result<(), EnsNormalizeError> {
+    let (first, last) = (chars[0], chars[chars.len() - 1]);
+    let gc = CodePointMapData::<GeneralCategory>::new();
+    let is_mark = |c: char| GeneralCategoryGroup::Mark.contains(gc.get(c));
+    let is_emoji = |c: char| CodePointSetData::new::<ExtendedPictographic>().contains(c);
+
+    if is_mark(first) {
+        return Err(EnsNormalizeError::LeadingCombiningMark)
+    }
+    if first == ZWJ {
+        return Err(EnsNormalizeError::DisallowedCharacter(ZWJ))
+    }
+    if FENCED.contains(&first) {
+        return Err(EnsNormalizeError::FencedLeading(first))
+    }
+    if FENCED.contains(&last) {
+        return Err(EnsNormalizeError::FencedTrailing(last))
+    }
+    for (i, pair) in chars.windows(2).enumerate() {
+        let (prev, c) = (pair[0], pair[1]);
+        if FENCED.contains(&prev) && FENCED.contains(&c) {
+            return Err(EnsNormalizeError::FencedAdjacent(prev, c))
+        }
+        if is_mark(c) && is_emoji(prev) {
+            return Err(EnsNormalizeError::CombiningMarkAfterEmoji)
+        }
+        if c == ZWJ {
+            let joins_emoji = (is_emoji(prev) ||
+                CodePointSetData::new::<EmojiModifier>().contains(prev)) &&
+                chars.get(i + 2).map_or(false, |next| is_emoji(*next));
+            if !joins_emoji {
+                return Err(EnsNormalizeError::DisallowedCharacter(ZWJ))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_names() {
+        for (name, expected) in [
+            ("", ""),
+            ("vitalik.eth", "vitalik.eth"),
+            ("VITALIK.Eth", "vitalik.eth"),
+            ("__ab.eth", "__ab.eth"),
+            ("$$$.eth", "$$$.eth"),
+            ("nick's.eth", "nick’s.eth"),
+            ("😀.eth", "😀.eth"),
+            ("ret↩️rn.eth", "ret↩rn.eth"),
+        ] {
+            assert_eq!(normalize(name).unwrap(), expected, "{name}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "ens-normalize")]
+    fn normalizes_unicode_names() {
+        for (name, expected) in [
+            ("ＡＢＣ.eth", "abc.eth"),
+            ("e\u{301}.eth", "é.eth"),
+            ("١٢٣.eth", "١٢٣.eth"),
+            ("🏳️‍🌈.eth", "🏳\u{200d}🌈.eth"),
+            ("👩🏽‍💻.eth", "👩🏽\u{200d}💻.eth"),
+        ] {
+            assert_eq!(normalize(name).unwrap(), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        use EnsNormalizeError::*;
+        for (name, error) in [
+            ("a..eth", EmptyLabel),
+            ("\u{fe0f}.eth", EmptyLabel),
+            ("a b.eth", DisallowedCharacter(' ')),
+            ("a@b.eth", DisallowedCharacter('@')),
+            ("a_b.eth", UnderscoreNotLeading),
+            ("xn--ls8h.eth", LabelExtension("xn--ls8h".to_string())),
+        ] {
+            assert_eq!(normalize(name).unwrap_err(), error, "{name}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "ens-normalize")]
+    fn rejects_invalid_unicode_names() {
+        use EnsNormalizeError::*;
+        for (name, error) in [
+            ("a\u{200d}b.eth", DisallowedCharacter(ZWJ)),
+            ("\u{301}a.eth", LeadingCombiningMark),
+            ("’a.eth", FencedLeading('’')),
+            ("a’.eth", FencedTrailing('’')),
+            ("a’⁄b.eth", FencedAdjacent('’', '⁄')),
+        ] {
+            assert_eq!(normalize(name).unwrap_err(), error, "{name}");
+        }
+    }
+}
@@ -12,7 +12,7 @@ use url::Url;
 use crate::{
     erc, EscalatingPending, EscalationPolicy, FilterKind, FilterWatcher, JsonRpcClient, LogQuery,
     MiddlewareError, NodeInfo, PeerInfo, PendingTransaction, Provider, ProviderError, PubsubClient,
-    SubscriptionStream,
+    SubscriptionKind, SubscriptionStream,
 };
 
 /// A middleware allows customizing requests send and received from an ethereum node.
@@ -297,6 +297,65 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().resolve_field(ens_name, field).await.map_err(MiddlewareError::from_err)
     }
 
+    /// Returns the address of `ens_name` for an [ENSIP-9](https://docs.ens.domains/ensip/9) coin
+    /// type, formatted for its chain.
+    ///
+    /// Requires the `ens-records` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use ethers_providers::{Provider, Http, Middleware};
+    /// # async fn foo(provider: Provider<Http>) -> Result<(), Box<dyn std::error::Error>> {
+    /// // Bitcoin
+    /// let btc = provider.resolve_coin_address("brantly.eth", 0).await?;
+    /// // Optimism, see ENSIP-11
+    /// let optimism = provider.resolve_coin_address("brantly.eth", 0x80000000 | 10).await?;
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "ens-records")]
+    async fn resolve_coin_address(
+        &self,
+        ens_name: &str,
+        coin_type: u64,
+    ) -> Result<String, Self::Error> {
+        self.inner()
+            .resolve_coin_address(ens_name, coin_type)
+            .await
+            .map_err(MiddlewareError::from_err)
+    }
+
+    /// Returns the [ENSIP-7](https://docs.ens.domains/ensip/7) content hash of `ens_name`, e.g.
+    /// an IPFS CID.
+    ///
+    /// Requires the `ens-records` feature.
+    #[cfg(feature = "ens-records")]
+    async fn resolve_contenthash(
+        &self,
+        ens_name: &str,
+    ) -> Result<crate::ens::Contenthash, Self::Error> {
+        self.inner().resolve_contenthash(ens_name).await.map_err(MiddlewareError::from_err)
+    }
+
+    /// Returns the ABI record of `ens_name` in the first of the given `content_types` that is set,
+    /// as specified in [EIP-205](https://eips.ethereum.org/EIPS/eip-205).
+    ///
+    /// The content types are a bitmask of `1` (JSON), `2` (zlib-compressed JSON), `4` (CBOR) and
+    /// `8` (URI). Returns the content type of the record and its data.
+    async fn resolve_abi(
+        &self,
+        ens_name: &str,
+        content_types: u64,
+    ) -> Result<(u64, Bytes), Self::Error> {
+        self.inner().resolve_abi(ens_name, content_types).await.map_err(MiddlewareError::from_err)
+    }
+
+    /// Returns the x and y coordinates of the SECP256k1 public key of `ens_name`, as specified in
+    /// [EIP-619](https://github.com/ethereum/EIPs/pull/619).
+    async fn resolve_pubkey(&self, ens_name: &str) -> Result<(H256, H256), Self::Error> {
+        self.inner().resolve_pubkey(ens_name).await.map_err(MiddlewareError::from_err)
+    }
+
     /// Gets the block at `block_hash_or_number` (transaction hashes only)
     async fn get_block<T: Into<BlockId> + Send + Sync>(
         &self,
@@ -907,6 +966,40 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().unsubscribe(id).await.map_err(MiddlewareError::from_err)
     }
 
+    /// Subscribe to a stream of notifications of the given [`SubscriptionKind`], e.g. one of
+    /// [`subscriptions`](crate::subscriptions) or a kind of a client's custom namespace.
+    ///
+    /// This function is only available on pubsub clients, such as Websockets
+    /// or IPC.
+    ///
+    /// ```no_run
+    /// use ethers_core::types::Address;
+    /// use ethers_providers::{
+    ///     subscriptions::FilteredPendingTransactions, Middleware, Provider, StreamExt, Ws,
+    /// };
+    ///
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = Provider::<Ws>::connect("wss://eth-mainnet.g.alchemy.com/v2/<key>").await?;
+    /// let to: Address = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".parse()?;
+    /// let mut stream =
+    ///     provider.subscribe_to(FilteredPendingTransactions::new().to(to)).await?;
+    /// while let Some(tx) = stream.next().await {
+    ///     println!("{:?}", tx.hash);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn subscribe_to<K>(
+        &self,
+        kind: K,
+    ) -> Result<SubscriptionStream<'_, Self::Provider, K::Item>, Self::Error>
+    where
+        K: SubscriptionKind + Send + Sync,
+        <Self as Middleware>::Provider: PubsubClient,
+    {
+        self.inner().subscribe_to(kind).await.map_err(MiddlewareError::from_err)
+    }
+
     /// Subscribe to a stream of incoming blocks.
     ///
     /// This function is only available on pubsub clients, such as Websockets
@@ -922,6 +1015,22 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().subscribe_blocks().await.map_err(MiddlewareError::from_err)
     }
 
+    /// Subscribe to a stream of incoming blocks, including their transactions.
+    ///
+    /// This function is only available on pubsub clients, such as Websockets
+    /// or IPC.
+    ///
+    /// Note: This subscription is supported by Nethermind, see
+    /// [`NewHeadsWithTransactions`](crate::subscriptions::NewHeadsWithTransactions).
+    async fn subscribe_full_blocks(
+        &self,
+    ) -> Result<SubscriptionStream<'_, Self::Provider, Block<Transaction>>, Self::Error>
+    where
+        <Self as Middleware>::Provider: PubsubClient,
+    {
+        self.inner().subscribe_full_blocks().await.map_err(MiddlewareError::from_err)
+    }
+
     /// Subscribe to a stream of pending transaction hashes.
     ///
     /// This function is only available on pubsub clients, such as Websockets
@@ -954,6 +1063,19 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().subscribe_full_pending_txs().await.map_err(MiddlewareError::from_err)
     }
 
+    /// Subscribe to a stream of changes of the node's sync status.
+    ///
+    /// This function is only available on pubsub clients, such as Websockets
+    /// or IPC. For a one-off query, use [`Middleware::syncing`].
+    async fn subscribe_syncing(
+        &self,
+    ) -> Result<SubscriptionStream<'_, Self::Provider, SyncingStatus>, Self::Error>
+    where
+        <Self as Middleware>::Provider: PubsubClient,
+    {
+        self.inner().subscribe_syncing().await.map_err(MiddlewareError::from_err)
+    }
+
     /// Subscribe to a stream of event logs matchin the provided [`Filter`].
     ///
     /// This function is only available on pubsub clients, such as Websockets
@@ -4,11 +4,12 @@ use crate::{
     call_raw::CallBuilder,
     errors::ProviderError,
     ext::{ens, erc},
+    rpc::pubsub::subscriptions,
     stream::{FilterWatcher, DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL},
     utils::maybe,
     Http as HttpProvider, JsonRpcClient, JsonRpcClientWrapper, LogQuery, MiddlewareError,
     MockProvider, NodeInfo, PeerInfo, PendingTransaction, PubsubClient, QuorumProvider, RwClient,
-    SubscriptionStream,
+    SubscriptionKind, SubscriptionStream,
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -234,6 +235,42 @@ impl<P: JsonRpcClient> Provider<P> {
     pub fn call_raw<'a>(&'a self, tx: &'a TypedTransaction) -> CallBuilder<'a, P> {
         CallBuilder::new(self, tx)
     }
+
+    /// Traces the transaction with the given options and deserializes the output into `R`, e.g.
+    /// the output type of a custom JS tracer.
+    ///
+    /// Unlike [`Middleware::debug_trace_transaction`], the output is deserialized straight from the
+    /// response, so no data is lost if it happens to look like the output of a built-in tracer.
+    pub async fn debug_trace_transaction_as<R: DeserializeOwned>(
+        &self,
+        tx_hash: TxHash,
+        trace_options: GethDebugTracingOptions,
+    ) -> Result<R, ProviderError> {
+        let tx_hash = utils::serialize(&tx_hash);
+        let trace_options = utils::serialize(&trace_options);
+        let trace: serde_json::Value =
+            self.request("debug_traceTransaction", [tx_hash, trace_options]).await?;
+        Ok(serde_json::from_value(trace)?)
+    }
+
+    /// Traces the call with the given options and deserializes the output into `R`, e.g. the
+    /// output type of a custom JS tracer.
+    ///
+    /// See [`Provider::debug_trace_transaction_as`].
+    pub async fn debug_trace_call_as<T: Into<TypedTransaction>, R: DeserializeOwned>(
+        &self,
+        req: T,
+        block: Option<BlockId>,
+        trace_options: GethDebugTracingCallOptions,
+    ) -> Result<R, ProviderError> {
+        let req = req.into();
+        let req = utils::serialize(&req);
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        let trace_options = utils::serialize(&trace_options);
+        let trace: serde_json::Value =
+            self.request("debug_traceCall", [req, block, trace_options]).await?;
+        Ok(serde_json::from_value(trace)?)
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -815,6 +852,12 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         let ens_name = ens::reverse_address(address);
         let domain: String =
             self.query_resolver(ParamType::String, &ens_name, ens::NAME_SELECTOR).await?;
+        // the name must be normalized, otherwise it resolves to a different node
+        let normalized = ens::normalize(&domain)
+            .map_err(|e| ProviderError::EnsError(format!("`{domain}`: {e}")))?;
+        if normalized != domain {
+            return Err(ProviderError::EnsError(format!("`{domain}` is not normalized")))
+        }
         let reverse_address = self.resolve_name(&domain).await?;
         if address != reverse_address {
             Err(ProviderError::EnsNotOwned(domain))
@@ -915,6 +958,72 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         Ok(field)
     }
 
+    #[cfg(feature = "ens-records")]
+    async fn resolve_coin_address(
+        &self,
+        ens_name: &str,
+        coin_type: u64,
+    ) -> Result<String, ProviderError> {
+        let coin_type_param = ens::bytes_32ify(coin_type);
+        let data = self
+            .call_resolver(ens_name, ens::COIN_ADDR_SELECTOR, Some(&coin_type_param), true)
+            .await?;
+        let address = decode_resolver_data(&[ParamType::Bytes], data)?.remove(0);
+        let address = address.into_bytes().unwrap_or_default();
+        if address.is_empty() {
+            return Err(ProviderError::EnsError(format!(
+                "`{ens_name}` has no address for coin type {coin_type}"
+            )))
+        }
+        ens::format_coin_address(coin_type, &address)
+            .map_err(|e| ProviderError::EnsError(format!("`{ens_name}`: {e}")))
+    }
+
+    #[cfg(feature = "ens-records")]
+    async fn resolve_contenthash(&self, ens_name: &str) -> Result<ens::Contenthash, ProviderError> {
+        let data = self.call_resolver(ens_name, ens::CONTENTHASH_SELECTOR, None, true).await?;
+        let hash = decode_resolver_data(&[ParamType::Bytes], data)?.remove(0);
+        let hash = hash.into_bytes().unwrap_or_default();
+        if hash.is_empty() {
+            return Err(ProviderError::EnsError(format!("`{ens_name}` has no contenthash")))
+        }
+        ens::Contenthash::decode(&hash)
+            .map_err(|e| ProviderError::EnsError(format!("`{ens_name}`: {e}")))
+    }
+
+    async fn resolve_abi(
+        &self,
+        ens_name: &str,
+        content_types: u64,
+    ) -> Result<(u64, Bytes), ProviderError> {
+        let content_types_param = ens::bytes_32ify(content_types);
+        let data = self
+            .call_resolver(ens_name, ens::ABI_SELECTOR, Some(&content_types_param), true)
+            .await?;
+        let mut tokens =
+            decode_resolver_data(&[ParamType::Uint(256), ParamType::Bytes], data)?.into_iter();
+        let content_type = tokens.next().and_then(|t| t.into_uint()).unwrap_or_default();
+        let abi = tokens.next().and_then(|t| t.into_bytes()).unwrap_or_default();
+        if content_type.is_zero() {
+            return Err(ProviderError::EnsError(format!("`{ens_name}` has no ABI")))
+        }
+        Ok((content_type.low_u64(), abi.into()))
+    }
+
+    async fn resolve_pubkey(&self, ens_name: &str) -> Result<(H256, H256), ProviderError> {
+        let data = self.call_resolver(ens_name, ens::PUBKEY_SELECTOR, None, true).await?;
+        let mut coordinates =
+            decode_resolver_data(&[ParamType::FixedBytes(32), ParamType::FixedBytes(32)], data)?
+                .into_iter()
+                .map(|t| t.into_fixed_bytes().map(|b| H256::from_slice(&b)).unwrap_or_default());
+        let (x, y) =
+            (coordinates.next().unwrap_or_default(), coordinates.next().unwrap_or_default());
+        if x.is_zero() && y.is_zero() {
+            return Err(ProviderError::EnsError(format!("`{ens_name}` has no pubkey")))
+        }
+        Ok((x, y))
+    }
+
     async fn txpool_content(&self) -> Result<TxpoolContent, ProviderError> {
         self.request("txpool_content", ()).await
     }
@@ -1072,13 +1181,35 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("eth_unsubscribe", [id.into()]).await
     }
 
+    async fn subscribe_to<K>(
+        &self,
+        kind: K,
+    ) -> Result<SubscriptionStream<'_, P, K::Item>, ProviderError>
+    where
+        K: SubscriptionKind + Send + Sync,
+        P: PubsubClient,
+    {
+        let mut params = vec![utils::serialize(&kind.name())];
+        params.extend(kind.params());
+        self.subscribe(params).await
+    }
+
     async fn subscribe_blocks(
         &self,
     ) -> Result<SubscriptionStream<'_, P, Block<TxHash>>, ProviderError>
     where
         P: PubsubClient,
     {
-        self.subscribe(["newHeads"]).await
+        self.subscribe_to(subscriptions::NewHeads).await
+    }
+
+    async fn subscribe_full_blocks(
+        &self,
+    ) -> Result<SubscriptionStream<'_, P, Block<Transaction>>, ProviderError>
+    where
+        P: PubsubClient,
+    {
+        self.subscribe_to(subscriptions::NewHeadsWithTransactions).await
     }
 
     async fn subscribe_pending_txs(
@@ -1087,7 +1218,7 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
     where
         P: PubsubClient,
     {
-        self.subscribe(["newPendingTransactions"]).await
+        self.subscribe_to(subscriptions::NewPendingTransactions).await
     }
 
     async fn subscribe_full_pending_txs(
@@ -1096,7 +1227,16 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
     where
         P: PubsubClient,
     {
-        self.subscribe([utils::serialize(&"newPendingTransactions"), utils::serialize(&true)]).await
+        self.subscribe_to(subscriptions::FullPendingTransactions).await
+    }
+
+    async fn subscribe_syncing(
+        &self,
+    ) -> Result<SubscriptionStream<'_, P, SyncingStatus>, ProviderError>
+    where
+        P: PubsubClient,
+    {
+        self.subscribe_to(subscriptions::Syncing).await
     }
 
     async fn subscribe_logs<'a>(
@@ -1183,6 +1323,24 @@ impl<P: JsonRpcClient> Provider<P> {
         selector: Selector,
         parameters: Option<&[u8]>,
     ) -> Result<T, ProviderError> {
+        // Reverse resolver reverts when calling `supportsInterface(bytes4)`
+        let validate = matches!(param, ParamType::Address);
+        let data = self.call_resolver(ens_name, selector, parameters, validate).await?;
+        Ok(decode_bytes(param, data))
+    }
+
+    /// Calls `selector` on the resolver of `ens_name`, after validating that the resolver supports
+    /// it if `validate` is set.
+    async fn call_resolver(
+        &self,
+        ens_name: &str,
+        selector: Selector,
+        parameters: Option<&[u8]>,
+        validate: bool,
+    ) -> Result<Bytes, ProviderError> {
+        let ens_name = &ens::normalize(ens_name)
+            .map_err(|e| ProviderError::EnsError(format!("`{ens_name}`: {e}")))?;
+
         // Get the ENS address, prioritize the local override variable
         let ens_addr = self.ens.unwrap_or(ens::ENS_ADDRESS);
 
@@ -1200,17 +1358,13 @@ impl<P: JsonRpcClient> Provider<P> {
             return Err(ProviderError::EnsError(ens_name.to_string()))
         }
 
-        if let ParamType::Address = param {
-            // Reverse resolver reverts when calling `supportsInterface(bytes4)`
+        if validate {
             self.validate_resolver(resolver_address, selector, ens_name).await?;
         }
 
         // resolve
-        let data = self
-            .call(&ens::resolve(resolver_address, selector, ens_name, parameters).into(), None)
-            .await?;
-
-        Ok(decode_bytes(param, data))
+        self.call(&ens::resolve(resolver_address, selector, ens_name, parameters).into(), None)
+            .await
     }
 
     /// Validates that the resolver supports `selector`.
@@ -1369,6 +1523,15 @@ fn decode_bytes<T: Detokenize>(param: ParamType, bytes: Bytes) -> T {
     T::from_tokens(tokens).expect("could not parse tokens as address")
 }
 
+/// Decodes the data returned by a resolver, which may not be well-formed.
+fn decode_resolver_data(
+    params: &[ParamType],
+    data: Bytes,
+) -> Result<Vec<abi::Token>, ProviderError> {
+    abi::decode(params, data.as_ref())
+        .map_err(|e| ProviderError::EnsError(format!("invalid resolver response: {e}")))
+}
+
 impl TryFrom<&str> for Provider<HttpProvider> {
     type Error = ParseError;
 
@@ -1607,6 +1770,30 @@ mod tests {
             .unwrap_err();
     }
 
+    #[tokio::test]
+    #[cfg(feature = "ens-records")]
+    async fn resolve_contenthash() {
+        let (provider, mock) = Provider::mocked();
+        let hash = hex::decode(
+            "e3010170122029f2d17be6139079dc48696d1f582a8530eb9805b561eda517e22a892c7e3f1f",
+        )
+        .unwrap();
+
+        // resolver, supportsInterface and contenthash calls, popped in reverse order
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[abi::Token::Bytes(hash)]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[abi::Token::Bool(true)]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(abi::encode(&[abi::Token::Address(
+            Address::repeat_byte(1),
+        )])))
+        .unwrap();
+
+        let contenthash = provider.resolve_contenthash("Vitalik.ETH").await.unwrap();
+        assert_eq!(
+            contenthash.to_string(),
+            "ipfs://QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4"
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn mainnet_resolve_avatar() {
@@ -12,6 +12,8 @@ use std::{
 };
 use tracing::error;
 
+pub mod subscriptions;
+
 /// A transport implementation supporting pub sub subscriptions.
 pub trait PubsubClient: JsonRpcClient {
     /// The type of stream this transport returns
@@ -22,6 +24,50 @@ pub trait PubsubClient: JsonRpcClient {
 
     /// Remove a subscription from this transport
     fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error>;
+
+    /// Returns how many times this transport re-established the subscription, e.g. after
+    /// reconnecting to the node.
+    ///
+    /// Defaults to `0` for transports that do not reconnect.
+    fn resubscriptions<T: Into<U256>>(&self, _id: T) -> u64 {
+        0
+    }
+}
+
+/// A typed `eth_subscribe` subscription, i.e. its parameters and the type of its notifications.
+///
+/// The kinds supported by most clients are in [`subscriptions`]. Other kinds, e.g. the ones of
+/// a client's custom namespace, can be subscribed to with [`Middleware::subscribe_to`] by
+/// implementing this trait.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::Transaction;
+/// use ethers_providers::SubscriptionKind;
+///
+/// /// Subscribes to the transactions of a block builder's bundles
+/// struct BundleTransactions;
+///
+/// impl SubscriptionKind for BundleTransactions {
+///     type Item = Transaction;
+///
+///     fn name(&self) -> &str {
+///         "builder_bundleTransactions"
+///     }
+/// }
+/// ```
+pub trait SubscriptionKind {
+    /// The type of the notifications of the subscription
+    type Item: DeserializeOwned + Send + Sync;
+
+    /// The name of the subscription, i.e. the first parameter of `eth_subscribe`
+    fn name(&self) -> &str;
+
+    /// The parameters of the subscription following its name, if any
+    fn params(&self) -> Option<serde_json::Value> {
+        None
+    }
 }
 
 #[must_use = "subscriptions do nothing unless you stream them"]
@@ -39,6 +85,10 @@ pub struct SubscriptionStream<'a, P: PubsubClient, R: DeserializeOwned> {
     rx: P::NotificationStream,
 
     ret: PhantomData<R>,
+
+    resubscriptions: u64,
+
+    on_resubscribe: Option<Box<dyn FnMut(U256) + Send + Sync + 'a>>,
 }
 
 impl<'a, P, R> SubscriptionStream<'a, P, R>
@@ -56,7 +106,15 @@ where
     pub fn new(id: U256, provider: &'a Provider<P>) -> Result<Self, P::Error> {
         // Call the underlying PubsubClient's subscribe
         let rx = provider.as_ref().subscribe(id)?;
-        Ok(Self { id, provider, rx, ret: PhantomData, loaded_elements: VecDeque::new() })
+        Ok(Self {
+            id,
+            provider,
+            rx,
+            ret: PhantomData,
+            loaded_elements: VecDeque::new(),
+            resubscriptions: 0,
+            on_resubscribe: None,
+        })
     }
 
     /// Unsubscribes from the subscription.
@@ -74,6 +132,17 @@ where
     pub fn set_loaded_elements(&mut self, loaded_elements: VecDeque<R>) {
         self.loaded_elements = loaded_elements;
     }
+
+    /// Sets a hook that is called with the subscription's id whenever the transport re-establishes
+    /// the subscription, e.g. after reconnecting to the node.
+    ///
+    /// Notifications emitted by the node while disconnected are lost, so the hook can be used to
+    /// catch up, e.g. by querying the logs of the missed blocks. It is called before the first
+    /// notification of the re-established subscription is returned.
+    pub fn on_resubscribe(mut self, hook: impl FnMut(U256) + Send + Sync + 'a) -> Self {
+        self.on_resubscribe = Some(Box::new(hook));
+        self
+    }
 }
 
 // Each subscription item is a serde_json::Value which must be decoded to the
@@ -94,15 +163,19 @@ where
 
         let mut this = self.project();
         loop {
-            return match futures_util::ready!(this.rx.as_mut().poll_next(ctx)) {
-                Some(item) => match serde_json::from_str(item.get()) {
-                    Ok(res) => Poll::Ready(Some(res)),
-                    Err(err) => {
-                        error!("failed to deserialize item {:?}", err);
-                        continue
-                    }
-                },
-                None => Poll::Ready(None),
+            let Some(item) = futures_util::ready!(this.rx.as_mut().poll_next(ctx)) else {
+                return Poll::Ready(None)
+            };
+            if let Some(hook) = this.on_resubscribe {
+                let resubscriptions = this.provider.as_ref().resubscriptions(*this.id);
+                if resubscriptions > *this.resubscriptions {
+                    *this.resubscriptions = resubscriptions;
+                    hook(*this.id);
+                }
+            }
+            match serde_json::from_str(item.get()) {
+                Ok(res) => return Poll::Ready(Some(res)),
+                Err(err) => error!("failed to deserialize item {:?}", err),
             }
         }
     }
@@ -0,0 +1,166 @@
+//! The [`SubscriptionKind`]s of `eth_subscribe`.
+//!
+//! [`NewHeads`], [`NewPendingTransactions`], [`Logs`] and [`Syncing`] are supported by all
+//! clients. The others are client specific.
+
+use super::SubscriptionKind;
+use ethers_core::{
+    types::{Address, Block, Filter, Log, SyncingStatus, Transaction, TxHash},
+    utils,
+};
+use serde::Serialize;
+
+/// Headers of new blocks
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NewHeads;
+
+impl SubscriptionKind for NewHeads {
+    type Item = Block<TxHash>;
+
+    fn name(&self) -> &str {
+        "newHeads"
+    }
+}
+
+/// New blocks with their transactions.
+///
+/// Note: This is supported by Nethermind, which includes the transactions of a block if the
+/// `includeTransactions` parameter is set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NewHeadsWithTransactions;
+
+impl SubscriptionKind for NewHeadsWithTransactions {
+    type Item = Block<Transaction>;
+
+    fn name(&self) -> &str {
+        "newHeads"
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({ "includeTransactions": true }))
+    }
+}
+
+/// Hashes of new pending transactions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NewPendingTransactions;
+
+impl SubscriptionKind for NewPendingTransactions {
+    type Item = TxHash;
+
+    fn name(&self) -> &str {
+        "newPendingTransactions"
+    }
+}
+
+/// New pending transactions with their bodies.
+///
+/// Note: This is supported by Geth 1.11.0 or later.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FullPendingTransactions;
+
+impl SubscriptionKind for FullPendingTransactions {
+    type Item = Transaction;
+
+    fn name(&self) -> &str {
+        "newPendingTransactions"
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        Some(serde_json::Value::Bool(true))
+    }
+}
+
+/// New pending transactions with their bodies, sent from or to one of the given addresses.
+///
+/// Note: This is supported by Alchemy as `alchemy_pendingTransactions`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredPendingTransactions {
+    /// The senders of the transactions, any sender if empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub from_address: Vec<Address>,
+    /// The recipients of the transactions, any recipient if empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub to_address: Vec<Address>,
+}
+
+impl FilteredPendingTransactions {
+    /// Creates a subscription to all pending transactions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sender to match the transactions against
+    #[must_use]
+    pub fn from(mut self, from: Address) -> Self {
+        self.from_address.push(from);
+        self
+    }
+
+    /// Adds a recipient to match the transactions against
+    #[must_use]
+    pub fn to(mut self, to: Address) -> Self {
+        self.to_address.push(to);
+        self
+    }
+}
+
+impl SubscriptionKind for FilteredPendingTransactions {
+    type Item = Transaction;
+
+    fn name(&self) -> &str {
+        "alchemy_pendingTransactions"
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        Some(utils::serialize(self))
+    }
+}
+
+/// New logs matching a [`Filter`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Logs(pub Filter);
+
+impl SubscriptionKind for Logs {
+    type Item = Log;
+
+    fn name(&self) -> &str {
+        "logs"
+    }
+
+    fn params(&self) -> Option<serde_json::Value> {
+        Some(utils::serialize(&self.0))
+    }
+}
+
+/// Changes of the sync status of the node
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Syncing;
+
+impl SubscriptionKind for Syncing {
+    type Item = SyncingStatus;
+
+    fn name(&self) -> &str {
+        "syncing"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_params() {
+        assert_eq!(NewHeadsWithTransactions.params(), Some(json!({ "includeTransactions": true })));
+        assert_eq!(FullPendingTransactions.params(), Some(json!(true)));
+        assert_eq!(Syncing.params(), None);
+
+        let to = Address::repeat_byte(0x11);
+        assert_eq!(
+            FilteredPendingTransactions::new().to(to).params(),
+            Some(json!({ "toAddress": [to] }))
+        );
+    }
+}
@@ -20,6 +20,9 @@ use std::{
 
 pub type SharedChannelMap = Arc<Mutex<HashMap<U256, mpsc::UnboundedReceiver<Box<RawValue>>>>>;
 
+/// Counts how often each subscription was re-established after a reconnection
+pub type SharedResubscriptionMap = Arc<Mutex<HashMap<U256, u64>>>;
+
 pub const DEFAULT_RECONNECTS: usize = 5;
 
 /// This struct manages the relationship between the u64 request ID, and U256
@@ -35,11 +38,18 @@ pub struct SubscriptionManager {
     aliases: HashMap<U256, u64>,
     // Used to share notification channels with the WsClient(s)
     channel_map: SharedChannelMap,
+    // Used to share resubscription counts with the WsClient(s)
+    resubscriptions: SharedResubscriptionMap,
 }
 
 impl SubscriptionManager {
     fn new(channel_map: SharedChannelMap) -> Self {
-        Self { subs: Default::default(), aliases: Default::default(), channel_map }
+        Self {
+            subs: Default::default(),
+            aliases: Default::default(),
+            channel_map,
+            resubscriptions: Default::default(),
+        }
     }
 
     fn count(&self) -> usize {
@@ -64,6 +74,7 @@ impl SubscriptionManager {
 
     #[tracing::instrument(skip(self))]
     fn end_subscription(&mut self, id: u64) -> Option<Box<RawValue>> {
+        self.resubscriptions.lock().unwrap().remove(&id.into());
         if let Some(sub) = self.subs.remove(&id) {
             if let Some(server_id) = sub.current_server_id {
                 tracing::debug!(server_id = format!("0x{server_id:x}"), "Ending subscription");
@@ -120,12 +131,18 @@ impl SubscriptionManager {
             // TODO: end subcription here?
             self.aliases.remove(&server_id);
             self.subs.remove(&id);
+            self.resubscriptions.lock().unwrap().remove(&id.into());
         }
     }
 
     fn req_success(&mut self, id: u64, result: Box<RawValue>) -> Box<RawValue> {
         if let Ok(server_id) = serde_json::from_str::<SubId>(result.get()) {
             tracing::debug!(id, server_id = %server_id.0, "Registering new sub alias");
+            // a sub with a server id is being re-established after a reconnection
+            if let Some(old_server_id) = self.subs.get(&id).and_then(|sub| sub.current_server_id) {
+                self.remove_alias(old_server_id);
+                *self.resubscriptions.lock().unwrap().entry(id.into()).or_default() += 1;
+            }
             self.add_alias(server_id.0, id);
             let result = U256::from(id);
             to_raw_value(&format!("0x{result:x}")).expect("valid json")
@@ -258,17 +275,20 @@ impl RequestManager {
         let (backend, (instructions_tx, instructions_rx), channel_map) =
             Self::connect_internal(conn.clone()).await?;
 
+        let subs = SubscriptionManager::new(channel_map.clone());
+        let resubscriptions = subs.resubscriptions.clone();
+
         Ok((
             Self {
                 id: Default::default(),
                 reconnects,
-                subs: SubscriptionManager::new(channel_map.clone()),
+                subs,
                 reqs: Default::default(),
                 backend,
                 conn,
                 instructions: instructions_rx,
             },
-            WsClient { instructions: instructions_tx, channel_map },
+            WsClient { instructions: instructions_tx, channel_map, resubscriptions },
         ))
     }
 
@@ -280,18 +300,21 @@ impl RequestManager {
         let (backend, (instructions_tx, instructions_rx), channel_map) =
             Self::connect_internal(conn.clone()).await?;
 
+        let subs = SubscriptionManager::new(channel_map.clone());
+        let resubscriptions = subs.resubscriptions.clone();
+
         Ok((
             Self {
                 id: Default::default(),
                 reconnects,
-                subs: SubscriptionManager::new(channel_map.clone()),
+                subs,
                 reqs: Default::default(),
                 backend,
                 conn,
                 config: None,
                 instructions: instructions_rx,
             },
-            WsClient { instructions: instructions_tx, channel_map },
+            WsClient { instructions: instructions_tx, channel_map, resubscriptions },
         ))
     }
 
@@ -312,18 +335,21 @@ impl RequestManager {
         let (backend, (instructions_tx, instructions_rx), channel_map) =
             Self::connect_with_config_internal(conn.clone(), config).await?;
 
+        let subs = SubscriptionManager::new(channel_map.clone());
+        let resubscriptions = subs.resubscriptions.clone();
+
         Ok((
             Self {
                 id: Default::default(),
                 reconnects,
-                subs: SubscriptionManager::new(channel_map.clone()),
+                subs,
                 reqs: Default::default(),
                 backend,
                 conn,
                 config: Some(config),
                 instructions: instructions_rx,
             },
-            WsClient { instructions: instructions_tx, channel_map },
+            WsClient { instructions: instructions_tx, channel_map, resubscriptions },
         ))
     }
 
@@ -515,3 +541,27 @@ impl RequestManager {
         tokio::spawn(fut);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_resubscriptions() {
+        let mut subs = SubscriptionManager::new(Default::default());
+        let server_id = |id: u64| to_raw_value(&format!("0x{id:x}")).unwrap();
+        subs.service_subscription_request(1, to_raw_value(&["newHeads"]).unwrap()).unwrap();
+
+        // the server id is aliased to the request id
+        assert_eq!(subs.req_success(1, server_id(0xaa)).get(), r#""0x1""#);
+        assert!(subs.resubscriptions.lock().unwrap().is_empty());
+
+        // re-established after a reconnection
+        subs.req_success(1, server_id(0xbb));
+        assert_eq!(subs.resubscriptions.lock().unwrap()[&U256::one()], 1);
+        assert!(!subs.aliases.contains_key(&U256::from(0xaa)));
+
+        subs.end_subscription(1);
+        assert!(subs.resubscriptions.lock().unwrap().is_empty());
+    }
+}
@@ -4,7 +4,7 @@ mod backend;
 
 mod manager;
 
-use manager::{RequestManager, SharedChannelMap};
+use manager::{RequestManager, SharedChannelMap, SharedResubscriptionMap};
 use std::fmt;
 
 mod types;
@@ -30,6 +30,8 @@ pub struct WsClient {
     instructions: mpsc::UnboundedSender<Instruction>,
     // Used to receive sub notifications channels with the backend
     channel_map: SharedChannelMap,
+    // Used to receive how often subs were re-established by the backend
+    resubscriptions: SharedResubscriptionMap,
 }
 
 impl WsClient {
@@ -137,6 +139,10 @@ impl PubsubClient for WsClient {
             .unbounded_send(Instruction::Unsubscribe { id: id.into() })
             .map_err(|_| WsClientError::UnexpectedClose)
     }
+
+    fn resubscriptions<T: Into<U256>>(&self, id: T) -> u64 {
+        self.resubscriptions.lock().unwrap().get(&id.into()).copied().unwrap_or_default()
+    }
 }
 
 impl crate::Provider<WsClient> {
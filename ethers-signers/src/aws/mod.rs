@@ -14,8 +14,11 @@ use rusoto_kms::{
 };
 use tracing::{debug, instrument, trace};
 
+use crate::kms::{
+    apply_eip155, sig_from_digest_bytes_trial_recovery, verifying_key_to_address, SignatureMismatch,
+};
+
 mod utils;
-use utils::{apply_eip155, verifying_key_to_address};
 
 /// An ethers Signer that uses keys held in Amazon AWS KMS.
 ///
@@ -92,6 +95,9 @@ pub enum AwsSignerError {
     /// Error type from Eip712Error message
     #[error("error encoding eip712 struct: {0:?}")]
     Eip712Error(String),
+    /// Thrown when the signature returned by AWS KMS was not made by the key of the signer
+    #[error("signature does not match the public key of the signer")]
+    SignatureMismatch,
 }
 
 impl From<String> for AwsSignerError {
@@ -100,6 +106,12 @@ impl From<String> for AwsSignerError {
     }
 }
 
+impl From<SignatureMismatch> for AwsSignerError {
+    fn from(_: SignatureMismatch) -> Self {
+        Self::SignatureMismatch
+    }
+}
+
 impl From<spki::Error> for AwsSignerError {
     fn from(e: spki::Error) -> Self {
         Self::Spki(e)
@@ -211,8 +223,7 @@ impl AwsSigner {
         chain_id: u64,
     ) -> Result<EthSig, AwsSignerError> {
         let sig = self.sign_digest(digest.into()).await?;
-        let mut sig =
-            utils::sig_from_digest_bytes_trial_recovery(&sig, digest.into(), &self.pubkey);
+        let mut sig = sig_from_digest_bytes_trial_recovery(&sig, digest.into(), &self.pubkey)?;
         apply_eip155(&mut sig, chain_id);
         Ok(sig)
     }
@@ -255,7 +266,7 @@ impl super::Signer for AwsSigner {
             payload.encode_eip712().map_err(|e| Self::Error::Eip712Error(e.to_string()))?;
 
         let sig = self.sign_digest(digest).await?;
-        let sig = utils::sig_from_digest_bytes_trial_recovery(&sig, digest, &self.pubkey);
+        let sig = sig_from_digest_bytes_trial_recovery(&sig, digest, &self.pubkey)?;
 
         Ok(sig)
     }
@@ -0,0 +1,534 @@
+//! Azure Key Vault-based Signer
+
+use crate::kms::{
+    apply_eip155, sig_from_digest_bytes_trial_recovery, verifying_key_to_address,
+    SignatureMismatch, TokenCache, TokenError, TokenProvider, TokenResponse,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ethers_core::{
+    k256::ecdsa::{Error as K256Error, Signature as KSig, VerifyingKey},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature as EthSig, H256,
+    },
+    utils::hash_message,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, instrument, trace};
+
+/// The Key Vault curve of secp256k1 keys.
+const SECP256K1_CURVE: &str = "P-256K";
+
+/// The Key Vault algorithm of ECDSA signatures over secp256k1.
+const SECP256K1_ALGORITHM: &str = "ES256K";
+
+const API_VERSION: &str = "7.4";
+
+const DEFAULT_AUTHORITY: &str = "https://login.microsoftonline.com";
+
+/// The OAuth 2.0 scope of the Key Vault API.
+const KEY_VAULT_SCOPE: &str = "https://vault.azure.net/.default";
+
+/// Errors produced by the AzureSigner
+#[derive(thiserror::Error, Debug)]
+pub enum AzureSignerError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// Error returned by the token provider
+    #[error("failed to get an access token: {0}")]
+    Token(TokenError),
+    /// Error returned by the Key Vault API
+    #[error("Key Vault request failed with status {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("{0}")]
+    K256(#[from] K256Error),
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error("{0}")]
+    Other(String),
+    /// Error type from Eip712Error message
+    #[error("error encoding eip712 struct: {0:?}")]
+    Eip712Error(String),
+    /// Thrown when the signature returned by Key Vault was not made by the key of the signer
+    #[error("signature does not match the public key of the signer")]
+    SignatureMismatch,
+}
+
+impl From<String> for AzureSignerError {
+    fn from(s: String) -> Self {
+        Self::Other(s)
+    }
+}
+
+impl From<SignatureMismatch> for AzureSignerError {
+    fn from(_: SignatureMismatch) -> Self {
+        Self::SignatureMismatch
+    }
+}
+
+/// A [`TokenProvider`] fetching tokens for the Key Vault API with the client credentials of a
+/// Microsoft Entra ID application (service principal).
+pub struct AzureClientSecret {
+    http: reqwest::Client,
+    authority: String,
+    tenant_id: String,
+    client_id: String,
+    client_secret: String,
+    cache: TokenCache,
+}
+
+impl std::fmt::Debug for AzureClientSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzureClientSecret")
+            .field("authority", &self.authority)
+            .field("tenant_id", &self.tenant_id)
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AzureClientSecret {
+    /// Creates a provider authenticating the application with the client id in the tenant with
+    /// its client secret, against the public Microsoft Entra ID authority.
+    pub fn new(
+        tenant_id: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            authority: DEFAULT_AUTHORITY.to_string(),
+            tenant_id: tenant_id.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            cache: TokenCache::default(),
+        }
+    }
+
+    /// Sets the URL of the authority issuing the tokens, e.g. for sovereign clouds.
+    pub fn with_authority(mut self, authority: impl Into<String>) -> Self {
+        self.authority = authority.into();
+        self
+    }
+}
+
+#[async_trait]
+impl TokenProvider for AzureClientSecret {
+    async fn token(&self) -> Result<String, TokenError> {
+        self.cache
+            .get_or_fetch(|| async {
+                let url = format!("{}/{}/oauth2/v2.0/token", self.authority, self.tenant_id);
+                let form = [
+                    ("grant_type", "client_credentials"),
+                    ("client_id", &self.client_id),
+                    ("client_secret", &self.client_secret),
+                    ("scope", KEY_VAULT_SCOPE),
+                ];
+                let resp = self.http.post(url).form(&form).send().await?;
+                Ok(resp.error_for_status()?.json::<TokenResponse>().await?)
+            })
+            .await
+    }
+}
+
+/// A client of the Key Vault REST API of a vault.
+///
+/// ```no_run
+/// use ethers_signers::{AzureClientSecret, AzureKeyVaultClient};
+///
+/// let credentials = AzureClientSecret::new("tenant id", "client id", "client secret");
+/// let client = AzureKeyVaultClient::new("https://my-vault.vault.azure.net", credentials);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AzureKeyVaultClient {
+    http: reqwest::Client,
+    vault_url: String,
+    credentials: Arc<dyn TokenProvider>,
+}
+
+impl AzureKeyVaultClient {
+    /// Creates a client of the vault with the given URL, authenticating its requests with the
+    /// tokens of the provider.
+    pub fn new<T: TokenProvider + 'static>(vault_url: impl Into<String>, credentials: T) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            vault_url: vault_url.into().trim_end_matches('/').to_string(),
+            credentials: Arc::new(credentials),
+        }
+    }
+
+    /// Sets the HTTP client sending the requests.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        req: reqwest::RequestBuilder,
+    ) -> Result<T, AzureSignerError> {
+        let token = self.credentials.token().await.map_err(AzureSignerError::Token)?;
+        let resp = req.query(&[("api-version", API_VERSION)]).bearer_auth(token).send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let message = resp.text().await?;
+            return Err(AzureSignerError::Api { status: status.as_u16(), message })
+        }
+        Ok(resp.json().await?)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, AzureSignerError> {
+        self.request(self.http.get(format!("{}/{path}", self.vault_url))).await
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, AzureSignerError> {
+        self.request(self.http.post(format!("{}/{path}", self.vault_url)).json(body)).await
+    }
+}
+
+#[derive(Deserialize)]
+struct KeyBundle {
+    key: JsonWebKey,
+}
+
+#[derive(Deserialize)]
+struct JsonWebKey {
+    kid: String,
+    crv: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    alg: &'static str,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    value: String,
+}
+
+/// Decode the public key of a JSON web key, returning it with the version of the key
+fn decode_pubkey(key: JsonWebKey) -> Result<(String, VerifyingKey), AzureSignerError> {
+    if key.crv.as_deref() != Some(SECP256K1_CURVE) {
+        return Err(format!("unsupported key curve {:?}", key.crv).into())
+    }
+    let (Some(x), Some(y)) = (key.x, key.y) else {
+        return Err("Pubkey coordinates not found in response".to_owned().into())
+    };
+    let mut sec1 = vec![0x04];
+    sec1.extend(URL_SAFE_NO_PAD.decode(x)?);
+    sec1.extend(URL_SAFE_NO_PAD.decode(y)?);
+    let pubkey = VerifyingKey::from_sec1_bytes(&sec1)?;
+
+    let version = key
+        .kid
+        .rsplit('/')
+        .next()
+        .filter(|version| !version.is_empty())
+        .ok_or_else(|| format!("key version not found in key id {}", key.kid))?;
+    Ok((version.to_string(), pubkey))
+}
+
+/// Decode a signature, which Key Vault encodes as the concatenation of `r` and `s`
+fn decode_signature(resp: SignResponse) -> Result<KSig, AzureSignerError> {
+    let sig = KSig::from_slice(&URL_SAFE_NO_PAD.decode(resp.value)?)?;
+    Ok(sig.normalize_s().unwrap_or(sig))
+}
+
+/// An ethers Signer that uses keys held in Azure Key Vault.
+///
+/// Keys must be EC keys on the `P-256K` curve, which sign with the `ES256K` algorithm. A key is
+/// identified by its name and signs with one of its versions. When no version is given, the
+/// current version of the key is used, which is resolved on instantiation so that the signer
+/// keeps signing with the same key when the key is rotated.
+///
+/// Because the public key is unknown, we retrieve it on instantiation of the
+/// signer. This means that the new function is `async` and must be called
+/// within some runtime.
+///
+/// ```no_run
+/// use ethers_signers::{AzureClientSecret, AzureKeyVaultClient, AzureSigner, Signer};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let credentials = AzureClientSecret::new("tenant id", "client id", "client secret");
+/// let client = AzureKeyVaultClient::new("https://my-vault.vault.azure.net", credentials);
+/// let signer = AzureSigner::new(client, "my-key", None, 1).await?;
+/// let sig = signer.sign_message("hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AzureSigner {
+    client: AzureKeyVaultClient,
+    chain_id: u64,
+    key_name: String,
+    key_version: String,
+    pubkey: VerifyingKey,
+    address: Address,
+}
+
+impl std::fmt::Debug for AzureSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzureSigner")
+            .field("key_name", &self.key_name)
+            .field("key_version", &self.key_version)
+            .field("chain_id", &self.chain_id)
+            .field("pubkey", &hex::encode(self.pubkey.to_sec1_bytes()))
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for AzureSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AzureSigner {{ address: {}, chain_id: {}, key_name: {}, key_version: {} }}",
+            self.address, self.chain_id, self.key_name, self.key_version
+        )
+    }
+}
+
+impl AzureSigner {
+    /// Instantiate a new signer from a client, the name of a key and its version, or `None` for
+    /// its current version.
+    ///
+    /// This function retrieves the public key from Azure and calculates the Ethereum address. It
+    /// is therefore `async`.
+    #[instrument(err, skip(client, key_name, chain_id), fields(key_name = %key_name.as_ref()))]
+    pub async fn new<T>(
+        client: AzureKeyVaultClient,
+        key_name: T,
+        version: Option<&str>,
+        chain_id: u64,
+    ) -> Result<AzureSigner, AzureSignerError>
+    where
+        T: AsRef<str>,
+    {
+        let key_name = key_name.as_ref().to_owned();
+        let (key_version, pubkey) = get_key(&client, &key_name, version.unwrap_or("")).await?;
+        let address = verifying_key_to_address(&pubkey);
+
+        debug!(
+            "Instantiated Azure signer with pubkey 0x{} and address 0x{}",
+            hex::encode(pubkey.to_sec1_bytes()),
+            hex::encode(address)
+        );
+
+        Ok(Self { client, chain_id, key_name, key_version, pubkey, address })
+    }
+
+    /// Returns the name of the key signing.
+    pub fn key_name(&self) -> &str {
+        &self.key_name
+    }
+
+    /// Returns the version of the key signing.
+    pub fn key_version(&self) -> &str {
+        &self.key_version
+    }
+
+    /// Fetch the pubkey associated with a key version, or the current version if `version` is
+    /// `None`
+    pub async fn get_pubkey_for_key<T>(
+        &self,
+        key_name: T,
+        version: Option<&str>,
+    ) -> Result<VerifyingKey, AzureSignerError>
+    where
+        T: AsRef<str>,
+    {
+        Ok(get_key(&self.client, key_name.as_ref(), version.unwrap_or("")).await?.1)
+    }
+
+    /// Fetch the pubkey associated with this signer's key version
+    pub async fn get_pubkey(&self) -> Result<VerifyingKey, AzureSignerError> {
+        self.get_pubkey_for_key(&self.key_name, Some(&self.key_version)).await
+    }
+
+    /// Sign a digest with a key version
+    #[instrument(err, skip(self, digest, key_name), fields(digest = %hex::encode(digest), key_name = %key_name.as_ref()))]
+    pub async fn sign_digest_with_key<T>(
+        &self,
+        key_name: T,
+        version: &str,
+        digest: [u8; 32],
+    ) -> Result<KSig, AzureSignerError>
+    where
+        T: AsRef<str>,
+    {
+        debug!("Dispatching sign");
+        let req = SignRequest { alg: SECP256K1_ALGORITHM, value: URL_SAFE_NO_PAD.encode(digest) };
+        let path = format!("keys/{}/{version}/sign", key_name.as_ref());
+        decode_signature(self.client.post(&path, &req).await?)
+    }
+
+    /// Sign a digest with this signer's key version
+    pub async fn sign_digest(&self, digest: [u8; 32]) -> Result<KSig, AzureSignerError> {
+        self.sign_digest_with_key(&self.key_name, &self.key_version, digest).await
+    }
+
+    /// Sign a digest with this signer's key and add the eip155 `v` value
+    /// corresponding to the input chain_id
+    #[instrument(err, skip(digest), fields(digest = %hex::encode(digest)))]
+    async fn sign_digest_with_eip155(
+        &self,
+        digest: H256,
+        chain_id: u64,
+    ) -> Result<EthSig, AzureSignerError> {
+        let sig = self.sign_digest(digest.into()).await?;
+        let mut sig = sig_from_digest_bytes_trial_recovery(&sig, digest.into(), &self.pubkey)?;
+        apply_eip155(&mut sig, chain_id);
+        Ok(sig)
+    }
+}
+
+/// Returns the version and the public key of a key version, or of the current version if
+/// `version` is empty.
+#[instrument(err, skip(client))]
+async fn get_key(
+    client: &AzureKeyVaultClient,
+    key_name: &str,
+    version: &str,
+) -> Result<(String, VerifyingKey), AzureSignerError> {
+    debug!("Dispatching get key");
+    let bundle: KeyBundle = client.get(&format!("keys/{key_name}/{version}")).await?;
+    decode_pubkey(bundle.key)
+}
+
+#[async_trait]
+impl super::Signer for AzureSigner {
+    type Error = AzureSignerError;
+
+    #[instrument(err, skip(message))]
+    #[allow(clippy::blocks_in_conditions)]
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<EthSig, Self::Error> {
+        let message = message.as_ref();
+        let message_hash = hash_message(message);
+        trace!("{:?}", message_hash);
+        trace!("{:?}", message);
+
+        self.sign_digest_with_eip155(message_hash, self.chain_id).await
+    }
+
+    #[instrument(err)]
+    #[allow(clippy::blocks_in_conditions)]
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<EthSig, Self::Error> {
+        let mut tx_with_chain = tx.clone();
+        let chain_id = tx_with_chain.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx_with_chain.set_chain_id(chain_id);
+
+        let sighash = tx_with_chain.sighash();
+        self.sign_digest_with_eip155(sighash, chain_id).await
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<EthSig, Self::Error> {
+        let digest =
+            payload.encode_eip712().map_err(|e| Self::Error::Eip712Error(e.to_string()))?;
+
+        let sig = self.sign_digest(digest).await?;
+        let sig = sig_from_digest_bytes_trial_recovery(&sig, digest, &self.pubkey)?;
+
+        Ok(sig)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the signer's chain id
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets the signer's chain id
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{kms::test_server::serve, Signer};
+    use ethers_core::{
+        k256::ecdsa::{signature::hazmat::PrehashSigner, SigningKey},
+        types::TransactionRequest,
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn signs_with_current_version() {
+        let key = SigningKey::from_slice(&[7; 32]).unwrap();
+        let pubkey = *key.verifying_key();
+        let expected = verifying_key_to_address(&pubkey);
+
+        let authority = serve(|req| {
+            let body = String::from_utf8(req.body).unwrap();
+            assert_eq!(req.path, "/tenant/oauth2/v2.0/token");
+            assert!(body.contains("grant_type=client_credentials"));
+            (200, json!({ "access_token": "token", "expires_in": 3600, "token_type": "Bearer" }))
+        })
+        .await;
+        let vault = serve(move |req| {
+            assert_eq!(req.headers["authorization"], "Bearer token");
+            let (path, query) = req.path.split_once('?').unwrap();
+            assert_eq!(query, format!("api-version={API_VERSION}"));
+            match (req.method.as_str(), path) {
+                ("GET", "/keys/eth/") | ("GET", "/keys/eth/v2") => {
+                    let point = key.verifying_key().to_encoded_point(false);
+                    let jwk = json!({
+                        "kid": "https://vault/keys/eth/v2",
+                        "kty": "EC",
+                        "crv": SECP256K1_CURVE,
+                        "x": URL_SAFE_NO_PAD.encode(point.x().unwrap()),
+                        "y": URL_SAFE_NO_PAD.encode(point.y().unwrap()),
+                    });
+                    (200, json!({ "key": jwk }))
+                }
+                ("POST", "/keys/eth/v2/sign") => {
+                    let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                    assert_eq!(body["alg"], SECP256K1_ALGORITHM);
+                    let digest = URL_SAFE_NO_PAD.decode(body["value"].as_str().unwrap()).unwrap();
+                    let sig: KSig = key.sign_prehash(&digest).unwrap();
+                    (200, json!({ "value": URL_SAFE_NO_PAD.encode(sig.to_bytes()) }))
+                }
+                _ => (404, json!({ "error": { "code": "KeyNotFound" } })),
+            }
+        })
+        .await;
+
+        let credentials =
+            AzureClientSecret::new("tenant", "client", "secret").with_authority(authority);
+        let client = AzureKeyVaultClient::new(vault, credentials);
+        let signer = AzureSigner::new(client, "eth", None, 1).await.unwrap();
+        assert_eq!(signer.key_version(), "v2");
+        assert_eq!(signer.address(), expected);
+        assert_eq!(signer.get_pubkey().await.unwrap(), pubkey);
+
+        let tx: TypedTransaction =
+            TransactionRequest::pay(Address::zero(), 1).nonce(0).chain_id(1).into();
+        let sig = signer.sign_transaction(&tx).await.unwrap();
+        sig.verify(tx.sighash(), expected).expect("valid sig");
+        assert_eq!(sig.v, 37 + sig.recovery_id().unwrap().to_byte() as u64);
+
+        let err = signer.get_pubkey_for_key("missing", None).await;
+        assert!(matches!(err, Err(AzureSignerError::Api { status: 404, .. })));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::Signer;
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::{
+        eip2718::TypedTransaction,
+        eip712::{EIP712Domain, Eip712},
+    },
+    Address, Signature,
+};
+use std::{error::Error, fmt, sync::Arc};
+use thiserror::Error;
+
+/// Error thrown by a [`DynSigner`], wrapping the error of the underlying signer.
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct DynSignerError(Box<dyn Error + Send + Sync>);
+
+impl DynSignerError {
+    /// Returns the error of the underlying signer.
+    pub fn into_inner(self) -> Box<dyn Error + Send + Sync> {
+        self.0
+    }
+}
+
+/// Error thrown when an EIP-712 payload can not be encoded before being passed to the underlying
+/// signer of a [`DynSigner`].
+#[derive(Debug, Error)]
+#[error("failed to encode EIP-712 payload: {0}")]
+struct ErasedEip712Error(String);
+
+/// The pre-computed hashes of an EIP-712 payload, so that it can be passed to signers through a
+/// [`DynSigner`].
+///
+/// [`Eip712::type_hash`] is not available for erased payloads.
+#[derive(Clone, Debug)]
+struct ErasedEip712 {
+    domain: EIP712Domain,
+    domain_separator: [u8; 32],
+    struct_hash: [u8; 32],
+    digest: [u8; 32],
+}
+
+impl ErasedEip712 {
+    fn new<T: Eip712>(payload: &T) -> Result<Self, ErasedEip712Error> {
+        let err = |e: T::Error| ErasedEip712Error(e.to_string());
+        Ok(Self {
+            domain: payload.domain().map_err(err)?,
+            domain_separator: payload.domain_separator().map_err(err)?,
+            struct_hash: payload.struct_hash().map_err(err)?,
+            digest: payload.encode_eip712().map_err(err)?,
+        })
+    }
+}
+
+impl Eip712 for ErasedEip712 {
+    type Error = ErasedEip712Error;
+
+    fn domain_separator(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(self.domain_separator)
+    }
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(self.domain.clone())
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Err(ErasedEip712Error("the type hash of an erased payload is unknown".to_string()))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(self.struct_hash)
+    }
+
+    fn encode_eip712(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(self.digest)
+    }
+}
+
+/// Object safe version of [`Signer`].
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+trait ErasedSigner: fmt::Debug + Send + Sync {
+    async fn erased_sign_message(&self, message: &[u8]) -> Result<Signature, DynSignerError>;
+
+    async fn erased_sign_transaction(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<Signature, DynSignerError>;
+
+    async fn erased_sign_typed_data(
+        &self,
+        payload: &ErasedEip712,
+    ) -> Result<Signature, DynSignerError>;
+
+    fn erased_address(&self) -> Address;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S> ErasedSigner for S
+where
+    S: Signer,
+    S::Error: 'static,
+{
+    async fn erased_sign_message(&self, message: &[u8]) -> Result<Signature, DynSignerError> {
+        Signer::sign_message(self, message).await.map_err(|e| DynSignerError(Box::new(e)))
+    }
+
+    async fn erased_sign_transaction(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<Signature, DynSignerError> {
+        Signer::sign_transaction(self, tx).await.map_err(|e| DynSignerError(Box::new(e)))
+    }
+
+    async fn erased_sign_typed_data(
+        &self,
+        payload: &ErasedEip712,
+    ) -> Result<Signature, DynSignerError> {
+        Signer::sign_typed_data(self, payload).await.map_err(|e| DynSignerError(Box::new(e)))
+    }
+
+    fn erased_address(&self) -> Address {
+        Signer::address(self)
+    }
+}
+
+/// A type-erased [`Signer`], which allows handling heterogeneous signers, e.g. local wallets,
+/// Ledgers and AWS KMS keys, as a single type.
+///
+/// Cloning a `DynSigner` is cheap, the underlying signer is shared.
+///
+/// # Example
+///
+/// ```
+/// use ethers_signers::{DynSigner, LocalWallet, Signer};
+///
+/// let signers: Vec<DynSigner> = vec![
+///     DynSigner::new(LocalWallet::new(&mut rand::thread_rng())),
+///     DynSigner::new(LocalWallet::new(&mut rand::thread_rng()).with_chain_id(5u64)),
+/// ];
+/// assert_eq!(signers[1].chain_id(), 5);
+/// ```
+#[derive(Clone)]
+pub struct DynSigner {
+    signer: Arc<dyn ErasedSigner>,
+    chain_id: u64,
+}
+
+impl DynSigner {
+    /// Erases the type of the signer.
+    pub fn new<S>(signer: S) -> Self
+    where
+        S: Signer + 'static,
+        S::Error: 'static,
+    {
+        let chain_id = signer.chain_id();
+        Self { signer: Arc::new(signer), chain_id }
+    }
+}
+
+impl fmt::Debug for DynSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynSigner")
+            .field("signer", &self.signer)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Signer for DynSigner {
+    type Error = DynSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        self.signer.erased_sign_message(message.as_ref()).await
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        // the chain id may have been changed with `with_chain_id`, which the underlying signer
+        // is not aware of
+        if tx.chain_id().is_none() {
+            let mut tx = tx.clone();
+            tx.set_chain_id(self.chain_id);
+            return self.signer.erased_sign_transaction(&tx).await
+        }
+        self.signer.erased_sign_transaction(tx).await
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let payload = ErasedEip712::new(payload).map_err(|e| DynSignerError(Box::new(e)))?;
+        self.signer.erased_sign_typed_data(&payload).await
+    }
+
+    fn address(&self) -> Address {
+        self.signer.erased_address()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::LocalWallet;
+    use ethers_core::types::{transaction::eip712::TypedData, TransactionRequest};
+
+    #[tokio::test]
+    async fn signs_like_underlying_signer() {
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+        let signer = DynSigner::new(wallet.clone()).with_chain_id(5u64);
+        assert_eq!(signer.address(), wallet.address());
+
+        let msg = signer.sign_message("hello").await.unwrap();
+        assert_eq!(msg, wallet.sign_message("hello").await.unwrap());
+
+        let tx: TypedTransaction = TransactionRequest::pay(Address::zero(), 1).nonce(0).into();
+        let sig = signer.sign_transaction(&tx).await.unwrap();
+        let expected = wallet.with_chain_id(5u64).sign_transaction(&tx).await.unwrap();
+        assert_eq!(sig, expected);
+
+        let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [{ "name": "name", "type": "string" }],
+                "Mail": [{ "name": "contents", "type": "string" }]
+            },
+            "primaryType": "Mail",
+            "domain": { "name": "Ether Mail" },
+            "message": { "contents": "Hello, Bob!" }
+        }))
+        .unwrap();
+        let sig = signer.sign_typed_data(&typed_data).await.unwrap();
+        sig.verify(typed_data.encode_eip712().unwrap(), signer.address()).unwrap();
+    }
+}
@@ -0,0 +1,580 @@
+//! GCP Cloud KMS-based Signer
+
+use crate::kms::{
+    apply_eip155, decode_der_signature, sig_from_digest_bytes_trial_recovery,
+    verifying_key_to_address, SignatureMismatch, TokenCache, TokenError, TokenProvider,
+    TokenResponse,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ethers_core::{
+    k256::ecdsa::{Error as K256Error, Signature as KSig, VerifyingKey},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature as EthSig, H256,
+    },
+    utils::hash_message,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, instrument, trace};
+
+/// The Cloud KMS algorithm of secp256k1 signing keys.
+const SECP256K1_ALGORITHM: &str = "EC_SIGN_SECP256K1_SHA256";
+
+const DEFAULT_ENDPOINT: &str = "https://cloudkms.googleapis.com";
+
+const DEFAULT_METADATA_ENDPOINT: &str = "http://metadata.google.internal";
+
+/// Errors produced by the GcpSigner
+#[derive(thiserror::Error, Debug)]
+pub enum GcpSignerError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// Error returned by the token provider
+    #[error("failed to get an access token: {0}")]
+    Token(TokenError),
+    /// Error returned by the Cloud KMS API
+    #[error("Cloud KMS request failed with status {status}: {message}")]
+    Api { status: u16, message: String },
+    #[error("{0}")]
+    K256(#[from] K256Error),
+    #[error("{0}")]
+    Spki(spki::Error),
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error("{0}")]
+    Other(String),
+    /// Error type from Eip712Error message
+    #[error("error encoding eip712 struct: {0:?}")]
+    Eip712Error(String),
+    /// Thrown when the signature returned by Cloud KMS was not made by the key of the signer
+    #[error("signature does not match the public key of the signer")]
+    SignatureMismatch,
+}
+
+impl From<String> for GcpSignerError {
+    fn from(s: String) -> Self {
+        Self::Other(s)
+    }
+}
+
+impl From<SignatureMismatch> for GcpSignerError {
+    fn from(_: SignatureMismatch) -> Self {
+        Self::SignatureMismatch
+    }
+}
+
+impl From<spki::Error> for GcpSignerError {
+    fn from(e: spki::Error) -> Self {
+        Self::Spki(e)
+    }
+}
+
+/// A [`TokenProvider`] fetching the tokens of the service account of the instance from the
+/// metadata server of GCE, GKE or Cloud Run.
+#[derive(Debug)]
+pub struct GcpMetadataToken {
+    http: reqwest::Client,
+    endpoint: String,
+    cache: TokenCache,
+}
+
+impl Default for GcpMetadataToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GcpMetadataToken {
+    /// Creates a provider querying the metadata server at `http://metadata.google.internal`.
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: DEFAULT_METADATA_ENDPOINT.to_string(),
+            cache: TokenCache::default(),
+        }
+    }
+
+    /// Sets the URL of the metadata server.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+}
+
+#[async_trait]
+impl TokenProvider for GcpMetadataToken {
+    async fn token(&self) -> Result<String, TokenError> {
+        self.cache
+            .get_or_fetch(|| async {
+                let url = format!(
+                    "{}/computeMetadata/v1/instance/service-accounts/default/token",
+                    self.endpoint
+                );
+                let resp = self.http.get(url).header("Metadata-Flavor", "Google").send().await?;
+                Ok(resp.error_for_status()?.json::<TokenResponse>().await?)
+            })
+            .await
+    }
+}
+
+/// A client of the Cloud KMS REST API.
+///
+/// ```no_run
+/// use ethers_signers::{GcpKmsClient, GcpMetadataToken};
+///
+/// let client = GcpKmsClient::new(GcpMetadataToken::new());
+/// ```
+#[derive(Clone, Debug)]
+pub struct GcpKmsClient {
+    http: reqwest::Client,
+    endpoint: String,
+    credentials: Arc<dyn TokenProvider>,
+}
+
+impl GcpKmsClient {
+    /// Creates a client authenticating its requests with the tokens of the provider.
+    pub fn new<T: TokenProvider + 'static>(credentials: T) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            credentials: Arc::new(credentials),
+        }
+    }
+
+    /// Sets the URL of the Cloud KMS API, e.g. a regional or private endpoint.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Sets the HTTP client sending the requests.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        req: reqwest::RequestBuilder,
+    ) -> Result<T, GcpSignerError> {
+        let token = self.credentials.token().await.map_err(GcpSignerError::Token)?;
+        let resp = req.bearer_auth(token).send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let message = resp.text().await?;
+            return Err(GcpSignerError::Api { status: status.as_u16(), message })
+        }
+        Ok(resp.json().await?)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, GcpSignerError> {
+        self.request(self.http.get(format!("{}/v1/{path}", self.endpoint))).await
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, GcpSignerError> {
+        self.request(self.http.post(format!("{}/v1/{path}", self.endpoint)).json(body)).await
+    }
+}
+
+/// The version of a Cloud KMS key used by a [`GcpSigner`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GcpKeyVersion {
+    /// The enabled secp256k1 version with the highest number, resolved when the signer is
+    /// instantiated
+    #[default]
+    Latest,
+    /// The version with the given number
+    Number(u64),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CryptoKeyVersion {
+    name: String,
+    algorithm: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListCryptoKeyVersionsResponse {
+    #[serde(default)]
+    crypto_key_versions: Vec<CryptoKeyVersion>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+    algorithm: String,
+}
+
+#[derive(Serialize)]
+struct AsymmetricSignRequest {
+    digest: Digest,
+}
+
+#[derive(Serialize)]
+struct Digest {
+    sha256: String,
+}
+
+#[derive(Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+/// Returns the number of a key version from its resource name.
+fn version_number(name: &str) -> Option<u64> {
+    name.rsplit_once("/cryptoKeyVersions/")?.1.parse().ok()
+}
+
+/// Decode the PEM encoded public key of a key version
+fn decode_pubkey(resp: PublicKeyResponse) -> Result<VerifyingKey, GcpSignerError> {
+    if resp.algorithm != SECP256K1_ALGORITHM {
+        return Err(format!("unsupported key algorithm {}", resp.algorithm).into())
+    }
+    let body: String = resp.pem.lines().filter(|line| !line.starts_with("-----")).collect();
+    let der = STANDARD.decode(body)?;
+
+    let spki = spki::SubjectPublicKeyInfoRef::try_from(der.as_slice())?;
+    let key = VerifyingKey::from_sec1_bytes(spki.subject_public_key.raw_bytes())?;
+
+    Ok(key)
+}
+
+/// An ethers Signer that uses keys held in GCP Cloud KMS.
+///
+/// Keys must have the `EC_SIGN_SECP256K1_SHA256` algorithm. A key is identified by its resource
+/// name, `projects/{project}/locations/{location}/keyRings/{key_ring}/cryptoKeys/{key}`, and
+/// signs with one of its versions, see [`GcpKeyVersion`].
+///
+/// Because the public key is unknown, we retrieve it on instantiation of the
+/// signer. This means that the new function is `async` and must be called
+/// within some runtime.
+///
+/// ```no_run
+/// use ethers_signers::{GcpKeyVersion, GcpKmsClient, GcpMetadataToken, GcpSigner, Signer};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = GcpKmsClient::new(GcpMetadataToken::new());
+/// let key = "projects/my-project/locations/global/keyRings/my-ring/cryptoKeys/my-key";
+/// let signer = GcpSigner::new(client, key, GcpKeyVersion::Latest, 1).await?;
+/// let sig = signer.sign_message("hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GcpSigner {
+    client: GcpKmsClient,
+    chain_id: u64,
+    key_version: String,
+    pubkey: VerifyingKey,
+    address: Address,
+}
+
+impl std::fmt::Debug for GcpSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcpSigner")
+            .field("key_version", &self.key_version)
+            .field("chain_id", &self.chain_id)
+            .field("pubkey", &hex::encode(self.pubkey.to_sec1_bytes()))
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for GcpSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "GcpSigner {{ address: {}, chain_id: {}, key_version: {} }}",
+            self.address, self.chain_id, self.key_version
+        )
+    }
+}
+
+impl GcpSigner {
+    /// Instantiate a new signer from a client, the resource name of a key and its version.
+    ///
+    /// This function resolves the key version, retrieves its public key from GCP and calculates
+    /// the Ethereum address. It is therefore `async`.
+    #[instrument(err, skip(client, key, chain_id), fields(key = %key.as_ref()))]
+    pub async fn new<T>(
+        client: GcpKmsClient,
+        key: T,
+        version: GcpKeyVersion,
+        chain_id: u64,
+    ) -> Result<GcpSigner, GcpSignerError>
+    where
+        T: AsRef<str>,
+    {
+        let key = key.as_ref().trim_end_matches('/');
+        let key_version = match version {
+            GcpKeyVersion::Number(number) => format!("{key}/cryptoKeyVersions/{number}"),
+            GcpKeyVersion::Latest => latest_version(&client, key).await?,
+        };
+        let pubkey = get_pubkey(&client, &key_version).await?;
+        let address = verifying_key_to_address(&pubkey);
+
+        debug!(
+            "Instantiated GCP signer with pubkey 0x{} and address 0x{}",
+            hex::encode(pubkey.to_sec1_bytes()),
+            hex::encode(address)
+        );
+
+        Ok(Self { client, chain_id, key_version, pubkey, address })
+    }
+
+    /// Returns the resource name of the key version signing.
+    pub fn key_version(&self) -> &str {
+        &self.key_version
+    }
+
+    /// Fetch the pubkey associated with a key version
+    pub async fn get_pubkey_for_key_version<T>(
+        &self,
+        key_version: T,
+    ) -> Result<VerifyingKey, GcpSignerError>
+    where
+        T: AsRef<str>,
+    {
+        get_pubkey(&self.client, key_version.as_ref()).await
+    }
+
+    /// Fetch the pubkey associated with this signer's key version
+    pub async fn get_pubkey(&self) -> Result<VerifyingKey, GcpSignerError> {
+        self.get_pubkey_for_key_version(&self.key_version).await
+    }
+
+    /// Sign a digest with the key version with the given resource name
+    #[instrument(err, skip(self, digest, key_version), fields(digest = %hex::encode(digest), key_version = %key_version.as_ref()))]
+    pub async fn sign_digest_with_key_version<T>(
+        &self,
+        key_version: T,
+        digest: [u8; 32],
+    ) -> Result<KSig, GcpSignerError>
+    where
+        T: AsRef<str>,
+    {
+        debug!("Dispatching asymmetricSign");
+        let req = AsymmetricSignRequest { digest: Digest { sha256: STANDARD.encode(digest) } };
+        let resp: AsymmetricSignResponse =
+            self.client.post(&format!("{}:asymmetricSign", key_version.as_ref()), &req).await?;
+        trace!("{:?}", resp.signature);
+        Ok(decode_der_signature(&STANDARD.decode(resp.signature)?)?)
+    }
+
+    /// Sign a digest with this signer's key version
+    pub async fn sign_digest(&self, digest: [u8; 32]) -> Result<KSig, GcpSignerError> {
+        self.sign_digest_with_key_version(&self.key_version, digest).await
+    }
+
+    /// Sign a digest with this signer's key and add the eip155 `v` value
+    /// corresponding to the input chain_id
+    #[instrument(err, skip(digest), fields(digest = %hex::encode(digest)))]
+    async fn sign_digest_with_eip155(
+        &self,
+        digest: H256,
+        chain_id: u64,
+    ) -> Result<EthSig, GcpSignerError> {
+        let sig = self.sign_digest(digest.into()).await?;
+        let mut sig = sig_from_digest_bytes_trial_recovery(&sig, digest.into(), &self.pubkey)?;
+        apply_eip155(&mut sig, chain_id);
+        Ok(sig)
+    }
+}
+
+#[instrument(err, skip(client))]
+async fn get_pubkey(
+    client: &GcpKmsClient,
+    key_version: &str,
+) -> Result<VerifyingKey, GcpSignerError> {
+    debug!("Dispatching getPublicKey");
+    decode_pubkey(client.get(&format!("{key_version}/publicKey")).await?)
+}
+
+/// Returns the resource name of the enabled secp256k1 version of the key with the highest
+/// number.
+#[instrument(err, skip(client))]
+async fn latest_version(client: &GcpKmsClient, key: &str) -> Result<String, GcpSignerError> {
+    let mut latest: Option<(u64, String)> = None;
+    let mut page_token = None;
+    loop {
+        let mut path = format!("{key}/cryptoKeyVersions?filter=state%3DENABLED");
+        if let Some(token) = &page_token {
+            path.push_str(&format!("&pageToken={token}"));
+        }
+        let page: ListCryptoKeyVersionsResponse = client.get(&path).await?;
+        for version in page.crypto_key_versions {
+            if version.algorithm != SECP256K1_ALGORITHM {
+                continue
+            }
+            let Some(number) = version_number(&version.name) else { continue };
+            if latest.as_ref().map_or(true, |(latest, _)| number > *latest) {
+                latest = Some((number, version.name));
+            }
+        }
+        page_token = page.next_page_token.filter(|token| !token.is_empty());
+        if page_token.is_none() {
+            break
+        }
+    }
+    latest
+        .map(|(_, name)| name)
+        .ok_or_else(|| format!("no enabled {SECP256K1_ALGORITHM} version of key {key}").into())
+}
+
+#[async_trait]
+impl super::Signer for GcpSigner {
+    type Error = GcpSignerError;
+
+    #[instrument(err, skip(message))]
+    #[allow(clippy::blocks_in_conditions)]
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<EthSig, Self::Error> {
+        let message = message.as_ref();
+        let message_hash = hash_message(message);
+        trace!("{:?}", message_hash);
+        trace!("{:?}", message);
+
+        self.sign_digest_with_eip155(message_hash, self.chain_id).await
+    }
+
+    #[instrument(err)]
+    #[allow(clippy::blocks_in_conditions)]
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<EthSig, Self::Error> {
+        let mut tx_with_chain = tx.clone();
+        let chain_id = tx_with_chain.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        tx_with_chain.set_chain_id(chain_id);
+
+        let sighash = tx_with_chain.sighash();
+        self.sign_digest_with_eip155(sighash, chain_id).await
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<EthSig, Self::Error> {
+        let digest =
+            payload.encode_eip712().map_err(|e| Self::Error::Eip712Error(e.to_string()))?;
+
+        let sig = self.sign_digest(digest).await?;
+        let sig = sig_from_digest_bytes_trial_recovery(&sig, digest, &self.pubkey)?;
+
+        Ok(sig)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the signer's chain id
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets the signer's chain id
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        kms::{test_server::serve, StaticToken},
+        Signer,
+    };
+    use ethers_core::k256::ecdsa::{signature::hazmat::PrehashSigner, SigningKey};
+    use serde_json::json;
+
+    const KEY: &str = "projects/p/locations/global/keyRings/r/cryptoKeys/k";
+
+    fn pem(key: &SigningKey) -> String {
+        // SubjectPublicKeyInfo header of uncompressed secp256k1 public keys
+        let mut der = hex::decode("3056301006072a8648ce3d020106052b8104000a034200").unwrap();
+        der.extend_from_slice(key.verifying_key().to_encoded_point(false).as_bytes());
+        format!("-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n", STANDARD.encode(der))
+    }
+
+    #[tokio::test]
+    async fn signs_with_latest_version() {
+        let keys: Vec<_> = (1u8..=2).map(|i| SigningKey::from_slice(&[i; 32]).unwrap()).collect();
+        let expected = verifying_key_to_address(keys[1].verifying_key());
+
+        let url = serve(move |req| {
+            assert_eq!(req.headers["authorization"], "Bearer token");
+            let path = req.path.strip_prefix(&format!("/v1/{KEY}/cryptoKeyVersions")).unwrap();
+            if path.starts_with('?') {
+                let versions: Vec<_> = (1..=2)
+                    .map(|i| {
+                        json!({
+                            "name": format!("{KEY}/cryptoKeyVersions/{i}"),
+                            "algorithm": SECP256K1_ALGORITHM,
+                        })
+                    })
+                    .collect();
+                return (200, json!({ "cryptoKeyVersions": versions }))
+            }
+            let (version, method) = path[1..].split_once([':', '/']).unwrap();
+            let key = &keys[version.parse::<usize>().unwrap() - 1];
+            match (req.method.as_str(), method) {
+                ("GET", "publicKey") => {
+                    (200, json!({ "pem": pem(key), "algorithm": SECP256K1_ALGORITHM }))
+                }
+                ("POST", "asymmetricSign") => {
+                    let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+                    let digest = STANDARD.decode(body["digest"]["sha256"].as_str().unwrap());
+                    let sig: KSig = key.sign_prehash(&digest.unwrap()).unwrap();
+                    (200, json!({ "signature": STANDARD.encode(sig.to_der()) }))
+                }
+                _ => (404, json!({ "error": { "message": "not found" } })),
+            }
+        })
+        .await;
+
+        let client = GcpKmsClient::new(StaticToken::new("token")).with_endpoint(url);
+        let signer = GcpSigner::new(client, KEY, GcpKeyVersion::Latest, 1).await.unwrap();
+        assert_eq!(signer.key_version(), format!("{KEY}/cryptoKeyVersions/2"));
+        assert_eq!(signer.address(), expected);
+
+        let message = vec![0, 1, 2, 3];
+        let sig = signer.sign_message(&message).await.unwrap();
+        sig.verify(message, expected).expect("valid sig");
+
+        let err = signer.get_pubkey_for_key_version(format!("{KEY}/cryptoKeyVersions/1/x")).await;
+        assert!(matches!(err, Err(GcpSignerError::Api { status: 404, .. })));
+    }
+
+    #[tokio::test]
+    async fn rejects_signature_of_another_key() {
+        let key = SigningKey::from_slice(&[1; 32]).unwrap();
+        let other = SigningKey::from_slice(&[2; 32]).unwrap();
+
+        let url = serve(move |req| {
+            if req.method == "GET" {
+                return (200, json!({ "pem": pem(&key), "algorithm": SECP256K1_ALGORITHM }))
+            }
+            let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+            let digest = STANDARD.decode(body["digest"]["sha256"].as_str().unwrap());
+            let sig: KSig = other.sign_prehash(&digest.unwrap()).unwrap();
+            (200, json!({ "signature": STANDARD.encode(sig.to_der()) }))
+        })
+        .await;
+
+        let client = GcpKmsClient::new(StaticToken::new("token")).with_endpoint(url);
+        let signer = GcpSigner::new(client, KEY, GcpKeyVersion::Number(1), 1).await.unwrap();
+        let err = signer.sign_message([0, 1, 2, 3]).await.unwrap_err();
+        assert!(matches!(err, GcpSignerError::SignatureMismatch));
+    }
+}
@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Error returned by a [`TokenProvider`].
+pub type TokenError = Box<dyn Error + Send + Sync>;
+
+/// Cached tokens are refreshed this long before they expire.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Provides the OAuth 2.0 bearer tokens authenticating the requests to a key management service.
+#[async_trait]
+pub trait TokenProvider: Send + Sync + fmt::Debug {
+    /// Returns a valid access token.
+    async fn token(&self) -> Result<String, TokenError>;
+}
+
+/// A [`TokenProvider`] returning a fixed token, e.g. obtained with `gcloud auth
+/// print-access-token` or `az account get-access-token`.
+#[derive(Clone)]
+pub struct StaticToken(String);
+
+impl StaticToken {
+    /// Creates a provider always returning `token`.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+}
+
+impl fmt::Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StaticToken").field(&"<redacted>").finish()
+    }
+}
+
+#[async_trait]
+impl TokenProvider for StaticToken {
+    async fn token(&self) -> Result<String, TokenError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// The response of an OAuth 2.0 token endpoint.
+#[derive(Deserialize)]
+pub(crate) struct TokenResponse {
+    pub access_token: String,
+    /// The lifetime of the token, in seconds
+    pub expires_in: u64,
+}
+
+/// Caches a token until shortly before it expires.
+#[derive(Default)]
+pub(crate) struct TokenCache {
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl fmt::Debug for TokenCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenCache").finish_non_exhaustive()
+    }
+}
+
+impl TokenCache {
+    /// Returns the cached token, or fetches a new one if it expires soon.
+    pub(crate) async fn get_or_fetch<F, Fut>(&self, fetch: F) -> Result<String, TokenError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<TokenResponse, TokenError>>,
+    {
+        if let Some((token, expires)) = &*self.token.lock().unwrap() {
+            if Instant::now() + REFRESH_MARGIN < *expires {
+                return Ok(token.clone())
+            }
+        }
+        let response = fetch().await?;
+        let expires = Instant::now() + Duration::from_secs(response.expires_in);
+        *self.token.lock().unwrap() = Some((response.access_token.clone(), expires));
+        Ok(response.access_token)
+    }
+}
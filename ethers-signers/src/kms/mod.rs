@@ -0,0 +1,148 @@
+//! Helpers shared by the signers using keys held in cloud key management services.
+//!
+//! These utils are NOT meant for general usage. They assume that their inputs come from the key
+//! management service of the signer, and only return an error if a signature does not match the
+//! key of the signer.
+
+use ethers_core::{
+    k256::{
+        ecdsa::{RecoveryId, Signature as KSig, VerifyingKey},
+        FieldBytes,
+    },
+    types::{Address, Signature as EthSig, U256},
+    utils::keccak256,
+};
+
+#[cfg(any(feature = "gcp", feature = "azure"))]
+mod credentials;
+#[cfg(any(feature = "gcp", feature = "azure"))]
+pub use credentials::{StaticToken, TokenError, TokenProvider};
+#[cfg(any(feature = "gcp", feature = "azure"))]
+pub(crate) use credentials::{TokenCache, TokenResponse};
+
+/// Makes a trial recovery to check whether a signature corresponds to a known `VerifyingKey`
+fn check_candidate(
+    sig: &KSig,
+    recovery_id: RecoveryId,
+    digest: [u8; 32],
+    vk: &VerifyingKey,
+) -> bool {
+    VerifyingKey::recover_from_prehash(digest.as_slice(), sig, recovery_id)
+        .map(|key| key == *vk)
+        .unwrap_or(false)
+}
+
+/// Error returned when a signature was not made by the expected key
+#[derive(Debug)]
+pub(crate) struct SignatureMismatch;
+
+/// Recover an rsig from a signature under a known key by trial/error
+pub(crate) fn sig_from_digest_bytes_trial_recovery(
+    sig: &KSig,
+    digest: [u8; 32],
+    vk: &VerifyingKey,
+) -> Result<EthSig, SignatureMismatch> {
+    let r_bytes: FieldBytes = sig.r().into();
+    let s_bytes: FieldBytes = sig.s().into();
+    let r = U256::from_big_endian(r_bytes.as_slice());
+    let s = U256::from_big_endian(s_bytes.as_slice());
+
+    if check_candidate(sig, RecoveryId::from_byte(0).unwrap(), digest, vk) {
+        Ok(EthSig { r, s, v: 0 })
+    } else if check_candidate(sig, RecoveryId::from_byte(1).unwrap(), digest, vk) {
+        Ok(EthSig { r, s, v: 1 })
+    } else {
+        Err(SignatureMismatch)
+    }
+}
+
+/// Modify the v value of a signature to conform to eip155
+pub(crate) fn apply_eip155(sig: &mut EthSig, chain_id: u64) {
+    let v = (chain_id * 2 + 35) + sig.v;
+    sig.v = v;
+}
+
+/// Convert a verifying key to an ethereum address
+pub(crate) fn verifying_key_to_address(key: &VerifyingKey) -> Address {
+    // false for uncompressed
+    let uncompressed_pub_key = key.to_encoded_point(false);
+    let public_key = uncompressed_pub_key.to_bytes();
+    debug_assert_eq!(public_key[0], 0x04);
+    let hash = keccak256(&public_key[1..]);
+    Address::from_slice(&hash[12..])
+}
+
+/// Decode a DER encoded signature, normalizing its `s` value to the lower half of the curve order
+/// as required by Ethereum
+#[cfg(any(feature = "aws", feature = "gcp"))]
+pub(crate) fn decode_der_signature(der: &[u8]) -> Result<KSig, ethers_core::k256::ecdsa::Error> {
+    let sig = KSig::from_der(der)?;
+    Ok(sig.normalize_s().unwrap_or(sig))
+}
+
+/// A minimal HTTP server standing in for the APIs of the key management services in tests.
+#[cfg(all(test, any(feature = "gcp", feature = "azure")))]
+pub(crate) mod test_server {
+    use std::{collections::HashMap, sync::Arc};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    /// A request received by the server.
+    #[derive(Debug)]
+    pub(crate) struct Request {
+        pub method: String,
+        pub path: String,
+        pub headers: HashMap<String, String>,
+        pub body: Vec<u8>,
+    }
+
+    /// Serves the responses returned by the handler, as a status and a JSON body, and returns
+    /// the URL of the server.
+    pub(crate) async fn serve<F>(handler: F) -> String
+    where
+        F: Fn(Request) -> (u16, serde_json::Value) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handler = Arc::new(handler);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    let mut line = String::new();
+                    // keep-alive connections send several requests
+                    while matches!(stream.read_line(&mut line).await, Ok(n) if n > 0) {
+                        let mut parts = line.split_whitespace();
+                        let method = parts.next().unwrap_or_default().to_string();
+                        let path = parts.next().unwrap_or_default().to_string();
+                        let mut headers = HashMap::new();
+                        loop {
+                            let mut header = String::new();
+                            stream.read_line(&mut header).await.unwrap();
+                            let Some((name, value)) = header.trim_end().split_once(':') else {
+                                break
+                            };
+                            headers.insert(name.to_lowercase(), value.trim().to_string());
+                        }
+                        let len = headers.get("content-length").map_or(0, |l| l.parse().unwrap());
+                        let mut body = vec![0; len];
+                        stream.read_exact(&mut body).await.unwrap();
+
+                        let (status, json) = handler(Request { method, path, headers, body });
+                        let json = json.to_string();
+                        let response = format!(
+                            "HTTP/1.1 {status} OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{json}",
+                            json.len()
+                        );
+                        stream.get_mut().write_all(response.as_bytes()).await.unwrap();
+                        line.clear();
+                    }
+                });
+            }
+        });
+        url
+    }
+}
@@ -3,7 +3,17 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 mod wallet;
-pub use wallet::{MnemonicBuilder, Wallet, WalletError};
+pub use wallet::{
+    DerivationScheme, DiscoveredAccount, DiscoveryError, HdKeychain, MnemonicBuilder, Wallet,
+    WalletError, WatchOnlyKeychain, DEFAULT_GAP_LIMIT,
+};
+#[cfg(all(feature = "keystore", not(target_arch = "wasm32")))]
+pub use wallet::{
+    Keystore, KeystoreAccount, KeystoreDirError, KeystoreEvent, KeystoreSigner, KeystoreWatcher,
+};
+
+mod dyn_signer;
+pub use dyn_signer::{DynSigner, DynSignerError};
 
 /// Re-export the BIP-32 crate so that wordlists can be accessed conveniently.
 pub use coins_bip39;
@@ -34,11 +44,26 @@ pub use trezor::{
 #[cfg(all(feature = "yubihsm", not(target_arch = "wasm32")))]
 pub use yubihsm;
 
+#[cfg(any(feature = "aws", feature = "gcp", feature = "azure"))]
+mod kms;
+#[cfg(any(feature = "gcp", feature = "azure"))]
+pub use kms::{StaticToken, TokenError, TokenProvider};
+
 #[cfg(feature = "aws")]
 mod aws;
 #[cfg(feature = "aws")]
 pub use aws::{AwsSigner, AwsSignerError};
 
+#[cfg(feature = "gcp")]
+mod gcp;
+#[cfg(feature = "gcp")]
+pub use gcp::{GcpKeyVersion, GcpKmsClient, GcpMetadataToken, GcpSigner, GcpSignerError};
+
+#[cfg(feature = "azure")]
+mod azure;
+#[cfg(feature = "azure")]
+pub use azure::{AzureClientSecret, AzureKeyVaultClient, AzureSigner, AzureSignerError};
+
 use async_trait::async_trait;
 use ethers_core::types::{
     transaction::{eip2718::TypedTransaction, eip712::Eip712},
@@ -0,0 +1,432 @@
+//! Hierarchical deterministic keychains following BIP-32/BIP-44, deriving ranges of accounts
+//! from a mnemonic or watch-only addresses from an extended public key
+use crate::{Wallet, WalletError};
+
+use coins_bip32::{
+    enc::{MainnetEncoder, XKeyEncoder},
+    path::DerivationPath,
+    prelude::{Parent, XPriv, XPub},
+    Bip32Error, BIP32_HARDEN,
+};
+use coins_bip39::{Mnemonic, Wordlist};
+use ethers_core::{
+    k256::ecdsa::SigningKey,
+    types::Address,
+    utils::{public_key_to_address, secret_key_to_address},
+};
+use std::{fmt, future::Future, ops::Range};
+use thiserror::Error;
+
+#[cfg(feature = "providers")]
+use ethers_core::types::BlockId;
+#[cfg(feature = "providers")]
+use ethers_providers::Middleware;
+
+/// The number of consecutive unused accounts after which account discovery stops, as recommended
+/// by BIP-44.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// The derivation paths of the accounts of a keychain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DerivationScheme {
+    /// BIP-44 `m/44'/60'/0'/0/{index}`, used by most software wallets.
+    #[default]
+    Bip44,
+    /// Ledger Live `m/44'/60'/{index}'/0/0`.
+    ///
+    /// The account index is hardened, so these accounts can not be derived from an extended
+    /// public key.
+    LedgerLive,
+    /// Legacy Ledger `m/44'/60'/0'/{index}`, also used by MyEtherWallet.
+    LedgerLegacy,
+}
+
+impl DerivationScheme {
+    /// Returns the derivation path of the account with the given index.
+    pub fn path(&self, index: u32) -> Result<DerivationPath, WalletError> {
+        Ok(self.parent_path().iter().copied().chain(self.child_path(index)?).collect())
+    }
+
+    /// Returns the path of the deepest key all accounts are derived from, i.e. the key exported
+    /// as an extended public key.
+    pub fn parent_path(&self) -> DerivationPath {
+        let path: &[u32] = match self {
+            DerivationScheme::Bip44 => &[44 | BIP32_HARDEN, 60 | BIP32_HARDEN, BIP32_HARDEN, 0],
+            DerivationScheme::LedgerLive => &[44 | BIP32_HARDEN, 60 | BIP32_HARDEN],
+            DerivationScheme::LedgerLegacy => &[44 | BIP32_HARDEN, 60 | BIP32_HARDEN, BIP32_HARDEN],
+        };
+        path.into()
+    }
+
+    /// Returns the path of the account with the given index, relative to the parent path.
+    fn child_path(&self, index: u32) -> Result<Vec<u32>, WalletError> {
+        if index >= BIP32_HARDEN {
+            return Err(Bip32Error::InvalidBip32Path.into())
+        }
+        Ok(match self {
+            DerivationScheme::Bip44 | DerivationScheme::LedgerLegacy => vec![index],
+            DerivationScheme::LedgerLive => vec![index | BIP32_HARDEN, 0, 0],
+        })
+    }
+
+    /// Returns an error if the accounts can not be derived from an extended public key.
+    fn ensure_watchable(&self) -> Result<(), WalletError> {
+        match self {
+            DerivationScheme::LedgerLive => Err(Bip32Error::HardenedDerivationFailed.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An account found by account discovery.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DiscoveredAccount {
+    /// The index of the account in the keychain
+    pub index: u32,
+    /// The address of the account
+    pub address: Address,
+}
+
+/// Error thrown during account discovery
+#[derive(Debug, Error)]
+pub enum DiscoveryError<E> {
+    /// An account could not be derived
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+    /// The activity of an account could not be looked up
+    #[error("failed to look up account activity: {0}")]
+    Lookup(E),
+}
+
+/// Scans the accounts from index 0 until `gap_limit` consecutive accounts are unused.
+async fn discover_accounts<D, F, Fut, E>(
+    derive: D,
+    gap_limit: u32,
+    mut is_used: F,
+) -> Result<Vec<DiscoveredAccount>, DiscoveryError<E>>
+where
+    D: Fn(u32) -> Result<Address, WalletError>,
+    F: FnMut(Address) -> Fut,
+    Fut: Future<Output = Result<bool, E>>,
+{
+    let mut accounts = Vec::new();
+    let mut gap = 0;
+    let mut index = 0;
+    while gap < gap_limit {
+        let address = derive(index)?;
+        if is_used(address).await.map_err(DiscoveryError::Lookup)? {
+            accounts.push(DiscoveredAccount { index, address });
+            gap = 0;
+        } else {
+            gap += 1;
+        }
+        index += 1;
+    }
+    Ok(accounts)
+}
+
+/// Returns whether the account has sent a transaction or holds a balance.
+#[cfg(feature = "providers")]
+async fn has_activity<M: Middleware>(
+    client: &M,
+    address: Address,
+    block: Option<BlockId>,
+) -> Result<bool, M::Error> {
+    if !client.get_transaction_count(address, block).await?.is_zero() {
+        return Ok(true)
+    }
+    Ok(!client.get_balance(address, block).await?.is_zero())
+}
+
+/// A hierarchical deterministic keychain, deriving the wallets of the accounts of a
+/// [`DerivationScheme`] from a single root key.
+///
+/// # Example
+///
+/// ```
+/// use ethers_signers::{
+///     coins_bip39::English, DerivationScheme, HdKeychain, Signer, WatchOnlyKeychain,
+/// };
+///
+/// # fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+/// let keychain = HdKeychain::from_phrase::<English>(phrase, None)?;
+/// let wallets = keychain.wallets(0..5)?;
+///
+/// // the addresses can be derived without the mnemonic from the extended public key
+/// let xpub = keychain.xpub()?;
+/// let watch_only = WatchOnlyKeychain::from_xpub(&xpub, DerivationScheme::Bip44)?;
+/// assert_eq!(watch_only.address(4)?, wallets[4].address());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct HdKeychain {
+    root: XPriv,
+    scheme: DerivationScheme,
+    chain_id: u64,
+}
+
+impl fmt::Debug for HdKeychain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HdKeychain")
+            .field("scheme", &self.scheme)
+            .field("chain_id", &self.chain_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HdKeychain {
+    /// Creates a keychain from a mnemonic phrase and an optional password.
+    pub fn from_phrase<W: Wordlist>(
+        phrase: &str,
+        password: Option<&str>,
+    ) -> Result<Self, WalletError> {
+        Self::from_mnemonic(&Mnemonic::<W>::new_from_phrase(phrase)?, password)
+    }
+
+    /// Creates a keychain from a mnemonic and an optional password.
+    pub fn from_mnemonic<W: Wordlist>(
+        mnemonic: &Mnemonic<W>,
+        password: Option<&str>,
+    ) -> Result<Self, WalletError> {
+        Ok(Self::from_root(mnemonic.master_key(password)?))
+    }
+
+    /// Creates a keychain from a BIP-32 seed.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, WalletError> {
+        Ok(Self::from_root(XPriv::root_from_seed(seed, None)?))
+    }
+
+    fn from_root(root: XPriv) -> Self {
+        Self { root, scheme: DerivationScheme::default(), chain_id: 1 }
+    }
+
+    /// Sets the derivation scheme of the accounts, which defaults to BIP-44.
+    pub fn with_scheme(mut self, scheme: DerivationScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Sets the chain id of the derived wallets, which defaults to 1.
+    pub fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+
+    /// Returns the derivation scheme of the accounts.
+    pub fn scheme(&self) -> DerivationScheme {
+        self.scheme
+    }
+
+    /// Returns the wallet of the account with the given index.
+    pub fn wallet(&self, index: u32) -> Result<Wallet<SigningKey>, WalletError> {
+        let derived = self.root.derive_path(self.scheme.path(index)?)?;
+        let key: &coins_bip32::prelude::SigningKey = derived.as_ref();
+        let signer = SigningKey::from_bytes(&key.to_bytes())?;
+        let address = secret_key_to_address(&signer);
+        Ok(Wallet::<SigningKey> { signer, address, chain_id: self.chain_id })
+    }
+
+    /// Returns the wallets of the accounts in the range of indices.
+    pub fn wallets(&self, indices: Range<u32>) -> Result<Vec<Wallet<SigningKey>>, WalletError> {
+        indices.map(|index| self.wallet(index)).collect()
+    }
+
+    /// Returns the address of the account with the given index.
+    pub fn address(&self, index: u32) -> Result<Address, WalletError> {
+        Ok(self.wallet(index)?.address)
+    }
+
+    /// Returns the addresses of the accounts in the range of indices.
+    pub fn addresses(&self, indices: Range<u32>) -> Result<Vec<Address>, WalletError> {
+        indices.map(|index| self.address(index)).collect()
+    }
+
+    /// Returns a keychain deriving the addresses of the accounts without their secret keys.
+    ///
+    /// Fails for [`DerivationScheme::LedgerLive`], whose account indices are hardened.
+    pub fn watch_only(&self) -> Result<WatchOnlyKeychain, WalletError> {
+        self.scheme.ensure_watchable()?;
+        let parent = self.root.derive_path(self.scheme.parent_path())?;
+        Ok(WatchOnlyKeychain { xpub: parent.verify_key(), scheme: self.scheme })
+    }
+
+    /// Returns the base58 encoded extended public key the addresses of the accounts are derived
+    /// from, see [`WatchOnlyKeychain::from_xpub`].
+    pub fn xpub(&self) -> Result<String, WalletError> {
+        self.watch_only()?.xpub()
+    }
+
+    /// Returns the accounts in use, scanning the accounts from index 0 until `gap_limit`
+    /// consecutive accounts are not in use according to `is_used`.
+    pub async fn discover_with<F, Fut, E>(
+        &self,
+        gap_limit: u32,
+        is_used: F,
+    ) -> Result<Vec<DiscoveredAccount>, DiscoveryError<E>>
+    where
+        F: FnMut(Address) -> Fut,
+        Fut: Future<Output = Result<bool, E>>,
+    {
+        discover_accounts(|index| self.address(index), gap_limit, is_used).await
+    }
+
+    /// Returns the accounts which have sent a transaction or hold a balance at the given block,
+    /// scanning the accounts from index 0 until `gap_limit` consecutive accounts are unused.
+    #[cfg(feature = "providers")]
+    pub async fn discover<M: Middleware>(
+        &self,
+        client: &M,
+        gap_limit: u32,
+        block: Option<BlockId>,
+    ) -> Result<Vec<DiscoveredAccount>, DiscoveryError<M::Error>> {
+        self.discover_with(gap_limit, |address| has_activity(client, address, block)).await
+    }
+}
+
+/// A keychain deriving the addresses of the accounts of a [`DerivationScheme`] from an extended
+/// public key, without access to their secret keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchOnlyKeychain {
+    xpub: XPub,
+    scheme: DerivationScheme,
+}
+
+impl WatchOnlyKeychain {
+    /// Creates a keychain from a base58 encoded extended public key of the parent key of the
+    /// accounts, see [`DerivationScheme::parent_path`].
+    ///
+    /// Fails for [`DerivationScheme::LedgerLive`], whose account indices are hardened.
+    pub fn from_xpub(xpub: &str, scheme: DerivationScheme) -> Result<Self, WalletError> {
+        scheme.ensure_watchable()?;
+        Ok(Self { xpub: MainnetEncoder::xpub_from_base58(xpub)?, scheme })
+    }
+
+    /// Returns the base58 encoded extended public key.
+    pub fn xpub(&self) -> Result<String, WalletError> {
+        Ok(MainnetEncoder::xpub_to_base58(&self.xpub)?)
+    }
+
+    /// Returns the derivation scheme of the accounts.
+    pub fn scheme(&self) -> DerivationScheme {
+        self.scheme
+    }
+
+    /// Returns the address of the account with the given index.
+    pub fn address(&self, index: u32) -> Result<Address, WalletError> {
+        let derived = self.xpub.derive_path(self.scheme.child_path(index)?)?;
+        Ok(public_key_to_address(derived.as_ref()))
+    }
+
+    /// Returns the addresses of the accounts in the range of indices.
+    pub fn addresses(&self, indices: Range<u32>) -> Result<Vec<Address>, WalletError> {
+        indices.map(|index| self.address(index)).collect()
+    }
+
+    /// Returns the accounts in use, scanning the accounts from index 0 until `gap_limit`
+    /// consecutive accounts are not in use according to `is_used`.
+    pub async fn discover_with<F, Fut, E>(
+        &self,
+        gap_limit: u32,
+        is_used: F,
+    ) -> Result<Vec<DiscoveredAccount>, DiscoveryError<E>>
+    where
+        F: FnMut(Address) -> Fut,
+        Fut: Future<Output = Result<bool, E>>,
+    {
+        discover_accounts(|index| self.address(index), gap_limit, is_used).await
+    }
+
+    /// Returns the accounts which have sent a transaction or hold a balance at the given block,
+    /// scanning the accounts from index 0 until `gap_limit` consecutive accounts are unused.
+    #[cfg(feature = "providers")]
+    pub async fn discover<M: Middleware>(
+        &self,
+        client: &M,
+        gap_limit: u32,
+        block: Option<BlockId>,
+    ) -> Result<Vec<DiscoveredAccount>, DiscoveryError<M::Error>> {
+        self.discover_with(gap_limit, |address| has_activity(client, address, block)).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{coins_bip39::English, MnemonicBuilder};
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn derives_standard_paths() {
+        let keychain = HdKeychain::from_phrase::<English>(PHRASE, None).unwrap();
+        assert_eq!(
+            keychain.address(0).unwrap(),
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94".parse::<Address>().unwrap()
+        );
+
+        let ledger_live = keychain.clone().with_scheme(DerivationScheme::LedgerLive);
+        for (chain, index, path) in
+            [(&keychain, 3, "m/44'/60'/0'/0/3"), (&ledger_live, 2, "m/44'/60'/2'/0/0")]
+        {
+            let expected = MnemonicBuilder::<English>::default()
+                .phrase(PHRASE)
+                .derivation_path(path)
+                .unwrap()
+                .build()
+                .unwrap();
+            assert_eq!(chain.wallet(index).unwrap().address, expected.address);
+        }
+    }
+
+    #[test]
+    fn watch_only_derives_same_addresses() {
+        for scheme in [DerivationScheme::Bip44, DerivationScheme::LedgerLegacy] {
+            let keychain = HdKeychain::from_phrase::<English>(PHRASE, Some("password"))
+                .unwrap()
+                .with_scheme(scheme);
+            let xpub = keychain.xpub().unwrap();
+            let watch_only = WatchOnlyKeychain::from_xpub(&xpub, scheme).unwrap();
+            assert_eq!(watch_only.xpub().unwrap(), xpub);
+            assert_eq!(watch_only.addresses(0..5).unwrap(), keychain.addresses(0..5).unwrap());
+        }
+
+        let ledger_live = HdKeychain::from_phrase::<English>(PHRASE, None)
+            .unwrap()
+            .with_scheme(DerivationScheme::LedgerLive);
+        assert!(ledger_live.xpub().is_err());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "providers")]
+    async fn discovers_accounts_until_gap_limit() {
+        use ethers_core::types::U256;
+        use ethers_providers::Provider;
+
+        let keychain = HdKeychain::from_phrase::<English>(PHRASE, None).unwrap();
+        let addresses = keychain.addresses(0..4).unwrap();
+
+        // account 0 has sent a transaction, account 1 only holds a balance, accounts 2 and 3 are
+        // unused
+        let (provider, mock) = Provider::mocked();
+        for response in [0u64, 0, 0, 0, 5, 0, 1] {
+            mock.push(U256::from(response)).unwrap();
+        }
+
+        let found = keychain.discover(&provider, 2, None).await.unwrap();
+        assert_eq!(
+            found,
+            vec![
+                DiscoveredAccount { index: 0, address: addresses[0] },
+                DiscoveredAccount { index: 1, address: addresses[1] },
+            ]
+        );
+
+        let latest = ethers_core::types::BlockNumber::Latest;
+        mock.assert_request("eth_getTransactionCount", (addresses[0], latest)).unwrap();
+        mock.assert_request("eth_getTransactionCount", (addresses[1], latest)).unwrap();
+        mock.assert_request("eth_getBalance", (addresses[1], latest)).unwrap();
+    }
+}
@@ -0,0 +1,572 @@
+//! A directory of encrypted JSON keys, managed the way geth's `accounts/keystore` does
+use crate::{Signer, Wallet, WalletError};
+
+use async_trait::async_trait;
+use ethers_core::{
+    k256::ecdsa::SigningKey,
+    rand::{CryptoRng, Rng},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature,
+    },
+    utils::{hex, secret_key_to_address},
+};
+use futures_core::Stream;
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+
+/// Error thrown by the [`Keystore`] and its signers
+#[derive(Debug, Error)]
+pub enum KeystoreDirError {
+    /// Error propagated from the wallet module
+    #[error(transparent)]
+    WalletError(#[from] WalletError),
+    /// Error propagated from the eth-keystore crate, e.g. when the password is wrong
+    #[error(transparent)]
+    EthKeystoreError(#[from] eth_keystore::KeystoreError),
+    /// Error propagated by IO operations
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+    /// Error propagated while editing a key file
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    /// No key file of the account is in the keystore
+    #[error("no key for account {0:?}")]
+    UnknownAccount(Address),
+    /// Several key files of the account are in the keystore
+    #[error("multiple keys for account {0:?}")]
+    AmbiguousAccount(Address, Vec<PathBuf>),
+    /// A key file of the account is already in the keystore
+    #[error("account {0:?} already exists")]
+    AccountExists(Address),
+    /// The account is not unlocked, or its unlock timed out
+    #[error("account {0:?} is locked")]
+    Locked(Address),
+}
+
+/// An account of a [`Keystore`], and the file its key is stored in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeystoreAccount {
+    /// The address of the account
+    pub address: Address,
+    /// The path of the key file
+    pub path: PathBuf,
+}
+
+/// A change of the accounts of a [`Keystore`], reported by a [`KeystoreWatcher`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeystoreEvent {
+    /// A key file was added to the keystore
+    Added(KeystoreAccount),
+    /// A key file was removed from the keystore
+    Removed(KeystoreAccount),
+}
+
+#[derive(Debug)]
+struct Unlocked {
+    wallet: Wallet<SigningKey>,
+    expires: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    dir: PathBuf,
+    unlocked: Mutex<HashMap<Address, Unlocked>>,
+}
+
+/// A directory of encrypted JSON keys compatible with geth's keystore.
+///
+/// Key files are named `UTC--<created at>--<address>` and store the address of their key, so
+/// that accounts can be listed without decrypting them. Accounts must be unlocked with their
+/// password before they can sign, see [`Keystore::unlock`] and [`Keystore::signer`].
+///
+/// Cloning a `Keystore` is cheap, clones share their unlocked accounts.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_signers::{Keystore, Signer};
+/// use std::time::Duration;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let keystore = Keystore::open("/home/user/.ethereum/keystore")?;
+/// for account in keystore.accounts()? {
+///     println!("{:?} in {}", account.address, account.path.display());
+/// }
+///
+/// let account = keystore.new_account(&mut rand::thread_rng(), "password")?;
+/// keystore.unlock(account.address, "password", Some(Duration::from_secs(60)))?;
+/// let signer = keystore.signer(account.address)?.with_chain_id(1u64);
+/// let signature = signer.sign_message("hello").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Keystore {
+    inner: Arc<Inner>,
+}
+
+impl Keystore {
+    /// Opens the keystore in the given directory, creating the directory if it does not exist.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, KeystoreDirError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { inner: Arc::new(Inner { dir, unlocked: Default::default() }) })
+    }
+
+    /// Returns the directory of the keystore.
+    pub fn dir(&self) -> &Path {
+        &self.inner.dir
+    }
+
+    /// Returns the accounts of the keystore, sorted by the path of their key file.
+    ///
+    /// The address of an account is parsed from the name of its key file, or from its JSON for
+    /// files which are not named like geth names them. Hidden files, backup files ending with
+    /// `~` and files without an address are skipped.
+    pub fn accounts(&self) -> Result<Vec<KeystoreAccount>, KeystoreDirError> {
+        let mut accounts = Vec::new();
+        for entry in fs::read_dir(self.dir())? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else { continue };
+            if name.starts_with('.') || name.ends_with('~') || !entry.file_type()?.is_file() {
+                continue
+            }
+            let path = entry.path();
+            if let Some(address) = address_from_file_name(name).or_else(|| address_from_json(&path))
+            {
+                accounts.push(KeystoreAccount { address, path });
+            }
+        }
+        accounts.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(accounts)
+    }
+
+    /// Returns whether a key of the account is in the keystore.
+    pub fn has_address(&self, address: Address) -> Result<bool, KeystoreDirError> {
+        Ok(self.accounts()?.iter().any(|account| account.address == address))
+    }
+
+    /// Returns the account with the given address, which must have a single key file.
+    pub fn find(&self, address: Address) -> Result<KeystoreAccount, KeystoreDirError> {
+        let mut matches: Vec<_> =
+            self.accounts()?.into_iter().filter(|account| account.address == address).collect();
+        match matches.len() {
+            0 => Err(KeystoreDirError::UnknownAccount(address)),
+            1 => Ok(matches.remove(0)),
+            _ => Err(KeystoreDirError::AmbiguousAccount(
+                address,
+                matches.into_iter().map(|account| account.path).collect(),
+            )),
+        }
+    }
+
+    /// Generates a new key, stores it encrypted with the password and returns its account.
+    pub fn new_account<R: Rng + CryptoRng>(
+        &self,
+        rng: &mut R,
+        password: impl AsRef<[u8]>,
+    ) -> Result<KeystoreAccount, KeystoreDirError> {
+        let key = SigningKey::random(&mut *rng);
+        self.store_key(rng, &key, password)
+    }
+
+    /// Stores the private key encrypted with the password and returns its account.
+    ///
+    /// Fails if a key of the account is already in the keystore.
+    pub fn import_key<R: Rng + CryptoRng>(
+        &self,
+        rng: &mut R,
+        private_key: impl AsRef<[u8]>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<KeystoreAccount, KeystoreDirError> {
+        let key = SigningKey::from_slice(private_key.as_ref()).map_err(WalletError::from)?;
+        let address = secret_key_to_address(&key);
+        if self.has_address(address)? {
+            return Err(KeystoreDirError::AccountExists(address))
+        }
+        self.store_key(rng, &key, password)
+    }
+
+    /// Imports the key file at the given path, encrypted with `password`, re-encrypting it with
+    /// `new_password`.
+    ///
+    /// Fails if a key of the account is already in the keystore.
+    pub fn import<R: Rng + CryptoRng>(
+        &self,
+        rng: &mut R,
+        path: impl AsRef<Path>,
+        password: impl AsRef<[u8]>,
+        new_password: impl AsRef<[u8]>,
+    ) -> Result<KeystoreAccount, KeystoreDirError> {
+        let private_key = eth_keystore::decrypt_key(path, password)?;
+        self.import_key(rng, private_key, new_password)
+    }
+
+    /// Writes the key of the account, re-encrypted with `new_password`, to the file at `dest`.
+    pub fn export<R: Rng + CryptoRng>(
+        &self,
+        rng: &mut R,
+        address: Address,
+        password: impl AsRef<[u8]>,
+        new_password: impl AsRef<[u8]>,
+        dest: impl AsRef<Path>,
+    ) -> Result<(), KeystoreDirError> {
+        let key = self.decrypt(address, password)?;
+        write_key_file(rng, &key, new_password, dest.as_ref())
+    }
+
+    /// Re-encrypts the key of the account with `new_password`.
+    pub fn update<R: Rng + CryptoRng>(
+        &self,
+        rng: &mut R,
+        address: Address,
+        password: impl AsRef<[u8]>,
+        new_password: impl AsRef<[u8]>,
+    ) -> Result<(), KeystoreDirError> {
+        let account = self.find(address)?;
+        let key = decrypt_account(&account, password)?;
+        write_key_file(rng, &key, new_password, &account.path)
+    }
+
+    /// Deletes the key of the account, which is locked. The password is required to make sure
+    /// the key is not deleted by mistake.
+    pub fn delete(
+        &self,
+        address: Address,
+        password: impl AsRef<[u8]>,
+    ) -> Result<(), KeystoreDirError> {
+        let account = self.find(address)?;
+        decrypt_account(&account, password)?;
+        fs::remove_file(&account.path)?;
+        self.lock(address);
+        Ok(())
+    }
+
+    /// Unlocks the account until `timeout` elapses, or until it is locked if `timeout` is `None`.
+    ///
+    /// Unlocking an unlocked account replaces its timeout.
+    pub fn unlock(
+        &self,
+        address: Address,
+        password: impl AsRef<[u8]>,
+        timeout: Option<Duration>,
+    ) -> Result<(), KeystoreDirError> {
+        let wallet = Wallet::from(self.decrypt(address, password)?);
+        let expires = timeout.map(|timeout| Instant::now() + timeout);
+        self.inner.unlocked.lock().unwrap().insert(address, Unlocked { wallet, expires });
+        Ok(())
+    }
+
+    /// Locks the account, removing its decrypted key from memory.
+    pub fn lock(&self, address: Address) {
+        self.inner.unlocked.lock().unwrap().remove(&address);
+    }
+
+    /// Returns whether the account is unlocked.
+    pub fn is_unlocked(&self, address: Address) -> bool {
+        self.unlocked_wallet(address).is_ok()
+    }
+
+    /// Returns a signer for the account, which signs as long as the account is unlocked.
+    pub fn signer(&self, address: Address) -> Result<KeystoreSigner, KeystoreDirError> {
+        self.find(address)?;
+        Ok(KeystoreSigner { keystore: self.clone(), address, chain_id: 1 })
+    }
+
+    /// Watches the directory for added and removed key files, checking it at every interval.
+    ///
+    /// The directory is checked by a task spawned on the current tokio runtime, so this must be
+    /// called from within a runtime.
+    pub fn watch(&self, interval: Duration) -> Result<KeystoreWatcher, KeystoreDirError> {
+        let mut known: HashSet<_> = self.accounts()?.into_iter().collect();
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let keystore = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // the first tick completes immediately
+            interval.tick().await;
+            // the task stops once the watcher is dropped, which closes the channel
+            while !events_tx.is_closed() {
+                interval.tick().await;
+                let keystore = keystore.clone();
+                let Ok(Ok(accounts)) =
+                    tokio::task::spawn_blocking(move || keystore.accounts()).await
+                else {
+                    continue
+                };
+                let current: HashSet<_> = accounts.into_iter().collect();
+                let removed = known.difference(&current).cloned().map(KeystoreEvent::Removed);
+                let added = current.difference(&known).cloned().map(KeystoreEvent::Added);
+                for event in removed.chain(added) {
+                    if events_tx.send(event).is_err() {
+                        return
+                    }
+                }
+                known = current;
+            }
+        });
+        Ok(KeystoreWatcher { events })
+    }
+
+    fn decrypt(
+        &self,
+        address: Address,
+        password: impl AsRef<[u8]>,
+    ) -> Result<SigningKey, KeystoreDirError> {
+        decrypt_account(&self.find(address)?, password)
+    }
+
+    fn store_key<R: Rng + CryptoRng>(
+        &self,
+        rng: &mut R,
+        key: &SigningKey,
+        password: impl AsRef<[u8]>,
+    ) -> Result<KeystoreAccount, KeystoreDirError> {
+        let address = secret_key_to_address(key);
+        let created = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S%.9fZ");
+        let path = self.dir().join(format!("UTC--{created}--{}", hex::encode(address)));
+        write_key_file(rng, key, password, &path)?;
+        Ok(KeystoreAccount { address, path })
+    }
+
+    fn unlocked_wallet(&self, address: Address) -> Result<Wallet<SigningKey>, KeystoreDirError> {
+        let mut unlocked = self.inner.unlocked.lock().unwrap();
+        match unlocked.get(&address) {
+            Some(Unlocked { expires: Some(expires), .. }) if *expires <= Instant::now() => {
+                unlocked.remove(&address);
+                Err(KeystoreDirError::Locked(address))
+            }
+            Some(Unlocked { wallet, .. }) => Ok(wallet.clone()),
+            None => Err(KeystoreDirError::Locked(address)),
+        }
+    }
+}
+
+/// Parses the address out of a key file name of the form `UTC--<created at>--<address>`.
+fn address_from_file_name(name: &str) -> Option<Address> {
+    let address = name.strip_prefix("UTC--")?.rsplit("--").next()?;
+    if address.len() != 40 {
+        return None
+    }
+    address.parse().ok()
+}
+
+/// Reads the address stored in a key file.
+fn address_from_json(path: &Path) -> Option<Address> {
+    let json: serde_json::Value = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    json.get("address")?.as_str()?.parse().ok()
+}
+
+fn decrypt_account(
+    account: &KeystoreAccount,
+    password: impl AsRef<[u8]>,
+) -> Result<SigningKey, KeystoreDirError> {
+    let private_key = eth_keystore::decrypt_key(&account.path, password)?;
+    Ok(SigningKey::from_slice(&private_key).map_err(WalletError::from)?)
+}
+
+/// Writes the key encrypted with the password to the file at `path`, replacing it atomically.
+///
+/// The address of the key is added to the JSON, as geth expects it.
+fn write_key_file<R: Rng + CryptoRng>(
+    rng: &mut R,
+    key: &SigningKey,
+    password: impl AsRef<[u8]>,
+    path: &Path,
+) -> Result<(), KeystoreDirError> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("key");
+    // hidden files are skipped when listing the accounts
+    let tmp_name = format!(".{name}.tmp");
+    let tmp = dir.join(&tmp_name);
+    // only the owner may read the key, as geth does. The file is created before the key is
+    // written to it, eth-keystore truncates it, which keeps its permissions.
+    create_private_file(&tmp)?;
+    eth_keystore::encrypt_key(dir, rng, key.to_bytes(), password, Some(&tmp_name))?;
+
+    let mut json: serde_json::Value = serde_json::from_slice(&fs::read(&tmp)?)?;
+    json["address"] = hex::encode(secret_key_to_address(key)).into();
+    fs::write(&tmp, serde_json::to_vec(&json)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Creates an empty file which only the owner may read and write, replacing a stale one.
+fn create_private_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path).map(drop)
+}
+
+/// A [`Stream`] of the changes of the accounts of a [`Keystore`], see [`Keystore::watch`].
+///
+/// The directory stops being watched when the watcher is dropped.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct KeystoreWatcher {
+    events: mpsc::UnboundedReceiver<KeystoreEvent>,
+}
+
+impl Stream for KeystoreWatcher {
+    type Item = KeystoreEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+/// A [`Signer`] for an account of a [`Keystore`], which fails with [`KeystoreDirError::Locked`]
+/// unless the account is unlocked.
+#[derive(Clone, Debug)]
+pub struct KeystoreSigner {
+    keystore: Keystore,
+    address: Address,
+    chain_id: u64,
+}
+
+#[async_trait]
+impl Signer for KeystoreSigner {
+    type Error = KeystoreDirError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        let wallet = self.keystore.unlocked_wallet(self.address)?;
+        Ok(wallet.sign_message(message).await?)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let wallet = self.keystore.unlocked_wallet(self.address)?.with_chain_id(self.chain_id);
+        Ok(wallet.sign_transaction(tx).await?)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let wallet = self.keystore.unlocked_wallet(self.address)?;
+        Ok(wallet.sign_typed_data(payload).await?)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn manages_accounts() {
+        let dir = tempdir().unwrap();
+        let keystore = Keystore::open(dir.path()).unwrap();
+        let mut rng = rand::thread_rng();
+
+        let account = keystore.new_account(&mut rng, "password").unwrap();
+        let name = account.path.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("UTC--"));
+        assert!(name.ends_with(&hex::encode(account.address)));
+        assert_eq!(address_from_json(&account.path), Some(account.address));
+        assert_eq!(keystore.accounts().unwrap(), vec![account.clone()]);
+
+        // the key files are compatible with `Wallet::decrypt_keystore`
+        let wallet = Wallet::decrypt_keystore(&account.path, "password").unwrap();
+        assert_eq!(wallet.address(), account.address);
+        assert!(matches!(
+            keystore.import_key(&mut rng, wallet.signer().to_bytes(), "other"),
+            Err(KeystoreDirError::AccountExists(_))
+        ));
+
+        keystore.update(&mut rng, account.address, "password", "new password").unwrap();
+        assert!(keystore.unlock(account.address, "password", None).is_err());
+
+        let export_dir = tempdir().unwrap();
+        let exported = export_dir.path().join("exported.json");
+        keystore.export(&mut rng, account.address, "new password", "export", &exported).unwrap();
+        keystore.delete(account.address, "new password").unwrap();
+        assert!(keystore.accounts().unwrap().is_empty());
+
+        let imported = keystore.import(&mut rng, &exported, "export", "password").unwrap();
+        assert_eq!(imported.address, account.address);
+        assert_eq!(keystore.find(account.address).unwrap(), imported);
+    }
+
+    #[tokio::test]
+    async fn signs_while_unlocked() {
+        let dir = tempdir().unwrap();
+        let keystore = Keystore::open(dir.path()).unwrap();
+        let account = keystore.new_account(&mut rand::thread_rng(), "password").unwrap();
+        let signer = keystore.signer(account.address).unwrap();
+        assert!(matches!(signer.sign_message("hello").await, Err(KeystoreDirError::Locked(_))));
+
+        keystore.unlock(account.address, "password", None).unwrap();
+        let signature = signer.sign_message("hello").await.unwrap();
+        signature.verify("hello", account.address).unwrap();
+
+        keystore.unlock(account.address, "password", Some(Duration::ZERO)).unwrap();
+        assert!(!keystore.is_unlocked(account.address));
+        assert!(matches!(signer.sign_message("hello").await, Err(KeystoreDirError::Locked(_))));
+    }
+
+    #[tokio::test]
+    async fn watches_directory() {
+        let dir = tempdir().unwrap();
+        let keystore = Keystore::open(dir.path()).unwrap();
+        let mut watcher = keystore.watch(Duration::from_millis(10)).unwrap();
+        let timeout = Duration::from_secs(5);
+
+        let account = keystore.new_account(&mut rand::thread_rng(), "password").unwrap();
+        assert_eq!(
+            tokio::time::timeout(timeout, watcher.next()).await.unwrap(),
+            Some(KeystoreEvent::Added(account.clone()))
+        );
+        fs::remove_file(&account.path).unwrap();
+        assert_eq!(
+            tokio::time::timeout(timeout, watcher.next()).await.unwrap(),
+            Some(KeystoreEvent::Removed(account))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn key_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let keystore = Keystore::open(dir.path()).unwrap();
+        let account = keystore.new_account(&mut rand::thread_rng(), "password").unwrap();
+        let mode = fs::metadata(&account.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -1,6 +1,19 @@
 mod mnemonic;
 pub use mnemonic::MnemonicBuilder;
 
+mod keychain;
+pub use keychain::{
+    DerivationScheme, DiscoveredAccount, DiscoveryError, HdKeychain, WatchOnlyKeychain,
+    DEFAULT_GAP_LIMIT,
+};
+
+#[cfg(all(feature = "keystore", not(target_arch = "wasm32")))]
+mod keystore;
+#[cfg(all(feature = "keystore", not(target_arch = "wasm32")))]
+pub use keystore::{
+    Keystore, KeystoreAccount, KeystoreDirError, KeystoreEvent, KeystoreSigner, KeystoreWatcher,
+};
+
 mod private_key;
 pub use private_key::WalletError;
 
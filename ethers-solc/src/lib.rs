@@ -34,6 +34,7 @@ pub mod error;
 mod filter;
 pub mod report;
 pub mod utils;
+pub mod verify;
 pub use filter::{FileFilter, TestFileFilter};
 
 use crate::{
@@ -0,0 +1,275 @@
+//! Matching deployed runtime bytecode against compiled artifacts.
+//!
+//! This is the local equivalent of a [Sourcify](https://sourcify.dev) verification: the runtime
+//! code of a deployed contract (as returned by `eth_getCode`) is compared against the
+//! `deployedBytecode` of compiled artifacts. Immutable variables and linked library addresses are
+//! filled in at deployment time, so these regions are extracted from the deployed code instead of
+//! compared.
+
+use crate::{
+    artifacts::{BytecodeObject, CompactDeployedBytecode, Offsets},
+    Artifact, ArtifactId, ArtifactOutput, ProjectCompileOutput,
+};
+use ethers_core::types::{bytecode::split_metadata, Address, Bytes};
+use std::{collections::BTreeMap, fmt};
+
+/// How closely deployed bytecode matches an artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchKind {
+    /// The bytecode matches including the metadata hash, which means that the sources and all
+    /// compiler settings are identical.
+    Exact,
+    /// The executable bytecode matches but the metadata hash differs, for example because of
+    /// changed comments, file names or metadata settings.
+    Partial,
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchKind::Exact => f.write_str("exact"),
+            MatchKind::Partial => f.write_str("partial"),
+        }
+    }
+}
+
+/// A successful match of deployed bytecode against compiled bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytecodeMatch {
+    /// Whether the match is exact or partial.
+    pub kind: MatchKind,
+    /// The values of the immutable variables, keyed by the AST id of the variable declaration.
+    pub immutables: BTreeMap<String, Bytes>,
+    /// The addresses of the linked libraries, keyed by the fully qualified library name
+    /// `<file>:<library>`.
+    pub libraries: BTreeMap<String, Address>,
+}
+
+impl BytecodeMatch {
+    /// Whether this is an exact match.
+    pub fn is_exact(&self) -> bool {
+        self.kind == MatchKind::Exact
+    }
+}
+
+/// Compares the deployed runtime `code` against the compiled `deployed` bytecode.
+///
+/// Returns `None` if the code does not match. Immutable references and library link references
+/// of the compiled bytecode are ignored during the comparison, and their deployed values are
+/// returned with the match instead.
+///
+/// If the compiled bytecode is a library, the address pushed by its call protection prelude is
+/// ignored as well.
+pub fn match_deployed_bytecode(
+    code: &[u8],
+    deployed: &CompactDeployedBytecode,
+) -> Option<BytecodeMatch> {
+    let bytecode = deployed.bytecode.as_ref()?;
+    let compiled = unlinked_bytes(&bytecode.object, &bytecode.link_references)?;
+    if compiled.is_empty() || code.is_empty() {
+        return None
+    }
+
+    // copy of the deployed code with all regions that are filled at deployment time replaced
+    // with the compiled values
+    let mut masked = code.to_vec();
+
+    let mut libraries = BTreeMap::new();
+    for (file, libs) in &bytecode.link_references {
+        for (lib, offsets) in libs {
+            let name = format!("{file}:{lib}");
+            for offset in offsets {
+                let value = mask(&mut masked, &compiled, offset)?;
+                let addr = Address::from_slice(value.get(..20)?);
+                if *libraries.entry(name.clone()).or_insert(addr) != addr {
+                    return None
+                }
+            }
+        }
+    }
+
+    let mut immutables = BTreeMap::new();
+    for (id, offsets) in &deployed.immutable_references {
+        for offset in offsets {
+            let value = mask(&mut masked, &compiled, offset)?;
+            if *immutables.entry(id.clone()).or_insert_with(|| value.clone()) != value {
+                return None
+            }
+        }
+    }
+
+    // libraries start with `PUSH20 <address> ADDRESS EQ` where the address is only known once the
+    // library is deployed
+    if is_library_prelude(&compiled) && code.len() > 21 && code[0] == compiled[0] {
+        masked[1..21].copy_from_slice(&compiled[1..21]);
+    }
+
+    let kind = if masked == compiled {
+        MatchKind::Exact
+    } else if split_metadata(&masked).0 == split_metadata(&compiled).0 {
+        MatchKind::Partial
+    } else {
+        return None
+    };
+
+    Some(BytecodeMatch { kind, immutables, libraries })
+}
+
+/// Replaces the region at `offset` in `code` with the bytes of `compiled` and returns the
+/// replaced bytes
+fn mask(code: &mut [u8], compiled: &[u8], offset: &Offsets) -> Option<Bytes> {
+    let start = offset.start as usize;
+    let end = start.checked_add(offset.length as usize)?;
+    if end > code.len() || end > compiled.len() {
+        return None
+    }
+    let value = Bytes::from(code[start..end].to_vec());
+    code[start..end].copy_from_slice(&compiled[start..end]);
+    Some(value)
+}
+
+/// Returns the bytes of the bytecode object with all library placeholders zeroed
+fn unlinked_bytes(
+    object: &BytecodeObject,
+    link_references: &BTreeMap<String, BTreeMap<String, Vec<Offsets>>>,
+) -> Option<Vec<u8>> {
+    match object {
+        BytecodeObject::Bytecode(bytes) => Some(bytes.to_vec()),
+        BytecodeObject::Unlinked(unlinked) => {
+            let mut unlinked = unlinked.strip_prefix("0x").unwrap_or(unlinked).as_bytes().to_vec();
+            for offset in link_references.values().flat_map(|libs| libs.values()).flatten() {
+                let start = offset.start as usize * 2;
+                let end = start + offset.length as usize * 2;
+                unlinked.get_mut(start..end)?.fill(b'0');
+            }
+            hex::decode(unlinked).ok()
+        }
+    }
+}
+
+fn is_library_prelude(code: &[u8]) -> bool {
+    // PUSH20 <zero address> ADDRESS EQ
+    code.len() > 22 && code[0] == 0x73 && code[1..21].iter().all(|b| *b == 0) && code[21] == 0x30
+}
+
+impl<T: ArtifactOutput> ProjectCompileOutput<T> {
+    /// Returns all artifacts whose deployed bytecode matches the given runtime `code`, exact
+    /// matches first.
+    ///
+    /// See [`match_deployed_bytecode`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_solc::Project;
+    /// # fn get_code() -> Vec<u8> { vec![] }
+    ///
+    /// let project = Project::builder().build().unwrap();
+    /// let output = project.compile().unwrap();
+    /// let code = get_code();
+    /// if let Some((id, m)) = output.find_deployed_bytecode_match(&code) {
+    ///     println!("{} is a {} match", id.identifier(), m.kind);
+    /// }
+    /// ```
+    pub fn find_deployed_bytecode_matches(
+        &self,
+        code: impl AsRef<[u8]>,
+    ) -> Vec<(ArtifactId, BytecodeMatch)> {
+        let code = code.as_ref();
+        let mut matches = self
+            .artifact_ids()
+            .filter_map(|(id, artifact)| {
+                let deployed = artifact.get_deployed_bytecode()?;
+                match_deployed_bytecode(code, &deployed).map(|m| (id, m))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(_, m)| m.kind);
+        matches
+    }
+
+    /// Returns the best matching artifact for the given runtime `code`, preferring exact matches.
+    ///
+    /// See [`Self::find_deployed_bytecode_matches`].
+    pub fn find_deployed_bytecode_match(
+        &self,
+        code: impl AsRef<[u8]>,
+    ) -> Option<(ArtifactId, BytecodeMatch)> {
+        self.find_deployed_bytecode_matches(code).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts::CompactBytecode;
+
+    // solc 0.8.17 metadata
+    const METADATA: &str = "a264697066735822122084f2e6b8c1ad3d34e5ad7e0e2e4a02ff05b3a1da5468e44b1b44bdb2e6a8bec364736f6c63430008110033";
+
+    // PUSH32 <immutable> PUSH20 <library> JUMPDEST STOP INVALID <metadata>
+    fn compiled() -> CompactDeployedBytecode {
+        let object = format!(
+            "7f{}73__$b3f8c5d8a5a4b6bfb22a3c5e5ba9b3a5c2$__5b00fe{METADATA}",
+            "00".repeat(32)
+        );
+        let bytecode = CompactBytecode {
+            object: BytecodeObject::Unlinked(object),
+            source_map: None,
+            link_references: BTreeMap::from([(
+                "src/Lib.sol".to_string(),
+                BTreeMap::from([("Lib".to_string(), vec![Offsets { start: 34, length: 20 }])]),
+            )]),
+        };
+        CompactDeployedBytecode {
+            bytecode: Some(bytecode),
+            immutable_references: BTreeMap::from([(
+                "7".to_string(),
+                vec![Offsets { start: 1, length: 32 }],
+            )]),
+        }
+    }
+
+    fn deployed(metadata: &str) -> Vec<u8> {
+        hex::decode(format!("7f{}73{}5b00fe{metadata}", "11".repeat(32), "22".repeat(20))).unwrap()
+    }
+
+    #[test]
+    fn can_match_exact() {
+        let m = match_deployed_bytecode(&deployed(METADATA), &compiled()).unwrap();
+        assert_eq!(m.kind, MatchKind::Exact);
+        assert_eq!(m.immutables["7"], Bytes::from(vec![0x11; 32]));
+        assert_eq!(m.libraries["src/Lib.sol:Lib"], Address::repeat_byte(0x22));
+    }
+
+    #[test]
+    fn can_match_partial() {
+        let other = METADATA.replace("84f2e6b8", "00000000");
+        let m = match_deployed_bytecode(&deployed(&other), &compiled()).unwrap();
+        assert_eq!(m.kind, MatchKind::Partial);
+    }
+
+    #[test]
+    fn rejects_different_code() {
+        let mut code = deployed(METADATA);
+        // replace STOP with RETURN
+        code[55] = 0xf3;
+        assert!(match_deployed_bytecode(&code, &compiled()).is_none());
+        assert!(match_deployed_bytecode(&code[..40], &compiled()).is_none());
+    }
+
+    #[test]
+    fn ignores_library_address() {
+        let object = format!("73{}30146080{METADATA}", "00".repeat(20));
+        let compiled = CompactDeployedBytecode {
+            bytecode: Some(CompactBytecode {
+                object: BytecodeObject::Bytecode(hex::decode(object).unwrap().into()),
+                source_map: None,
+                link_references: Default::default(),
+            }),
+            immutable_references: Default::default(),
+        };
+        let code = hex::decode(format!("73{}30146080{METADATA}", "ab".repeat(20))).unwrap();
+        let m = match_deployed_bytecode(&code, &compiled).unwrap();
+        assert!(m.is_exact());
+    }
+}